@@ -25,6 +25,9 @@ pub struct Apu {
     pub channel_buffers: [[i16; 128]; 10],
     #[serde(skip)]
     pub buffer_idx: usize,
+    /// Mixed sample pairs produced since power-on
+    #[serde(default)]
+    pub samples_generated: u64,
 }
 
 fn default_channel_buffers() -> [[i16; 128]; 10] {
@@ -44,12 +47,15 @@ impl Apu {
             fm: Ym2612::new(),
             channel_buffers: [[0; 128]; 10],
             buffer_idx: 0,
+            samples_generated: 0,
         }
     }
 
     pub fn reset(&mut self) {
         self.psg.reset();
         self.fm.reset();
+        self.channel_buffers = default_channel_buffers();
+        self.buffer_idx = 0;
     }
 
     pub fn write_psg(&mut self, data: u8) {
@@ -82,6 +88,7 @@ impl Apu {
         let left = Self::mix_sample(((fm_l as i32) * 3 + (psg as i32) * 2) / 4);
         let right = Self::mix_sample(((fm_r as i32) * 3 + (psg as i32) * 2) / 4);
 
+        self.samples_generated += 1;
         (left, right)
     }
 
@@ -128,6 +135,73 @@ mod tests {
         assert_eq!(apu.fm.status, 0);
     }
 
+    #[test]
+    fn test_reset_restores_power_on_state() {
+        let mut apu = Apu::new();
+        // PSG: every volume up, tone 2 and noise programmed
+        for latch in [0x90, 0xB0, 0xD0, 0xF0] {
+            apu.write_psg(latch);
+        }
+        apu.write_psg(0xC5);
+        apu.write_psg(0x3F);
+        apu.write_psg(0xE7);
+        // FM: timers running, every channel keyed on with a fast attack
+        for (reg, value) in [(0x24, 0xFF), (0x25, 0x03), (0x26, 0xFF), (0x27, 0x3F)] {
+            apu.write_fm_addr(Bank::Bank0, reg);
+            apu.write_fm_data(Bank::Bank0, value);
+        }
+        for op in 0..16 {
+            apu.write_fm_addr(Bank::Bank0, 0x50 + op);
+            apu.write_fm_data(Bank::Bank0, 0x1F);
+        }
+        for (reg, value) in [(0xA4, 0x22), (0xA0, 0x69), (0x2B, 0x80), (0x2A, 0x20)] {
+            apu.write_fm_addr(Bank::Bank0, reg);
+            apu.write_fm_data(Bank::Bank0, value);
+        }
+        for ch in [0xF0, 0xF1, 0xF2, 0xF4, 0xF5, 0xF6] {
+            apu.write_fm_addr(Bank::Bank0, 0x28);
+            apu.write_fm_data(Bank::Bank0, ch);
+        }
+        for _ in 0..2000 {
+            apu.tick_cycles(200);
+            apu.generate_sample();
+            apu.update_visualization();
+        }
+        assert_ne!(apu.fm.read_status() & 0x03, 0, "Timers should have fired");
+        assert!(apu.fm.envelope_level(0, 3) < 0x3FF);
+
+        apu.reset();
+
+        for tone in &apu.psg.tones {
+            assert_eq!(tone.volume, 0x0F);
+            assert_eq!(tone.frequency, 0);
+            assert_eq!(tone.counter, 0);
+        }
+        assert_eq!(apu.psg.noise.volume, 0x0F);
+        assert_eq!(apu.psg.noise.lfsr, 0x4000);
+        assert!(!apu.psg.noise.white_noise);
+        assert_eq!(apu.psg.noise.shift_rate, 0);
+        assert_eq!((apu.psg.latch_channel, apu.psg.latch_volume), (0, false));
+
+        assert_eq!(apu.fm.read_status(), 0);
+        let fresh = Ym2612::new();
+        assert_eq!(apu.fm.registers, fresh.registers);
+        for ch in 0..6 {
+            for op in 0..4 {
+                assert_eq!(apu.fm.envelope_level(ch, op), 0x3FF);
+            }
+        }
+        assert_eq!(apu.fm.total_mclocks, 0);
+        assert_eq!(apu.fm.blip_l.read_instant(), 0);
+        assert_eq!(apu.fm.blip_r.read_instant(), 0);
+        assert_eq!(apu.buffer_idx, 0);
+        assert!(apu.channel_buffers.iter().flatten().all(|&s| s == 0));
+
+        // Nothing left over from before the reset reaches the output
+        apu.tick_cycles(200);
+        assert_eq!(apu.generate_sample(), (0, 0));
+    }
+
     #[test]
     fn test_psg_passthrough() {
         let mut apu = Apu::new();
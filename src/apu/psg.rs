@@ -56,9 +56,16 @@ impl Psg {
         }
     }
 
+    /// Power-on state: every channel muted (attenuation 0x0F), the tone
+    /// flip-flops low and all amplitudes 0, so nothing reaches the output
+    /// until a volume is written and a flip-flop goes high
     pub fn new() -> Self {
-        let mut psg = Self {
-            tones: std::array::from_fn(|_| ToneChannel::default()),
+        Self {
+            tones: std::array::from_fn(|_| ToneChannel {
+                volume: 0x0F,
+                output: false,
+                ..Default::default()
+            }),
             noise: NoiseChannel {
                 volume: 0x0F,
                 lfsr: 0x4000,
@@ -69,11 +76,7 @@ impl Psg {
             total_mclocks: 1, // Start at 1 to allow delta at 0 if needed
             mclk_debt: 0,
             blip: BlipBuf::new(audio::NTSC_MCLK, audio::SAMPLE_RATE),
-        };
-        for tone in &mut psg.tones {
-            tone.volume = 0x0F;
         }
-        psg
     }
 
     pub fn reset(&mut self) {
@@ -129,3 +129,37 @@ fn test_psg_noise_rate_3_tone_2() {
     psg.step_cycles(1);
     assert_eq!(psg.noise.counter, 50);
 }
+
+#[test]
+fn test_psg_is_silent_after_power_on_and_reset() {
+    let mut fresh = Psg::new();
+    let mut reset = Psg::new();
+    // Leave every channel loud with its flip-flop high, then reset
+    for latch in [0x90, 0xB0, 0xD0, 0xF0] {
+        reset.write(latch);
+    }
+    reset.write(0x81);
+    reset.step_cycles(1);
+    assert!(reset.tones[0].output);
+    assert_ne!(reset.current_sample(), 0);
+    reset.reset();
+
+    for psg in [&mut fresh, &mut reset] {
+        for tone in &psg.tones {
+            assert_eq!((tone.volume, tone.output, tone.last_amp), (0x0F, false, 0));
+        }
+        assert_eq!((psg.noise.volume, psg.noise.last_amp), (0x0F, 0));
+
+        // Channels count and toggle while muted without moving the output
+        psg.step_m68k_cycles(20_000);
+        let mut samples = [1i16; 64];
+        let read = psg.blip.read_samples(&mut samples);
+        assert!(samples[..read].iter().all(|&s| s == 0));
+
+        // Raising a volume while the flip-flop is low doesn't step the
+        // output either
+        psg.tones[1].counter = 100;
+        psg.write(0xB0);
+        assert_eq!(psg.blip.read_instant(), 0);
+    }
+}
@@ -1,4 +1,4 @@
-use super::ym2612::{Bank, Ym2612};
+use super::ym2612::{mix_dac, Bank, Ym2612};
 
 #[test]
 fn test_ym2612_all_channels_enable() {
@@ -104,3 +104,294 @@ fn test_ym2612_timer_ab_simultaneous() {
         "Both timers should have fired"
     );
 }
+
+/// Key on channel 0 with OP4 as the only audible operator (algorithm 0,
+/// modulators fully attenuated) and an instant attack.
+fn key_on_single_carrier(ym: &mut Ym2612, tl: u8) {
+    for (op_off, level) in [(0u8, 0x7F), (4, 0x7F), (8, 0x7F), (12, tl)] {
+        ym.write_addr(Bank::Bank0, 0x40 + op_off);
+        ym.write_data_bank(Bank::Bank0, level);
+        ym.write_addr(Bank::Bank0, 0x50 + op_off);
+        ym.write_data_bank(Bank::Bank0, 0x1F);
+    }
+    ym.write_addr(Bank::Bank0, 0x28);
+    ym.write_data_bank(Bank::Bank0, 0xF0);
+}
+
+/// Advance the chip by `n` internal FM samples (144 MCLK each).
+fn step_fm_samples(ym: &mut Ym2612, n: u32) {
+    for _ in 0..n {
+        ym.step(144 / 7 + 1);
+    }
+}
+
+#[test]
+fn test_ym2612_tl_log_domain_attenuation() {
+    let mut ym = Ym2612::new();
+    key_on_single_carrier(&mut ym, 0x00);
+    step_fm_samples(&mut ym, 4);
+    let loud = ym.generate_channel_samples()[0] as i32;
+    assert!(loud > 0, "Carrier should produce output at TL=0");
+
+    // 8 TL steps = 8 * 0.75dB = 6dB, i.e. exactly one octave in the log domain
+    ym.write_addr(Bank::Bank0, 0x4C);
+    ym.write_data_bank(Bank::Bank0, 0x08);
+    step_fm_samples(&mut ym, 16);
+    let quiet = ym.generate_channel_samples()[0] as i32;
+    assert_eq!(loud, quiet * 2, "TL=8 should halve the amplitude");
+
+    // 16 TL steps = 12dB
+    ym.write_addr(Bank::Bank0, 0x4C);
+    ym.write_data_bank(Bank::Bank0, 0x10);
+    step_fm_samples(&mut ym, 16);
+    let quieter = ym.generate_channel_samples()[0] as i32;
+    assert!(
+        (loud / 4 - quieter).abs() <= 1,
+        "TL=16 should quarter the amplitude ({} vs {})",
+        loud,
+        quieter
+    );
+}
+
+#[test]
+fn test_ym2612_tl_ramps_mid_note() {
+    let mut ym = Ym2612::new();
+    key_on_single_carrier(&mut ym, 0x00);
+    step_fm_samples(&mut ym, 4);
+    let loud = ym.generate_channel_samples()[0] as i32;
+
+    ym.write_addr(Bank::Bank0, 0x4C);
+    ym.write_data_bank(Bank::Bank0, 0x08);
+    step_fm_samples(&mut ym, 1);
+    let ramping = ym.generate_channel_samples()[0] as i32;
+    assert!(
+        ramping < loud && ramping > loud / 2,
+        "TL change mid-note should ramp, got {} (from {})",
+        ramping,
+        loud
+    );
+
+    step_fm_samples(&mut ym, 16);
+    assert_eq!(ym.generate_channel_samples()[0] as i32 * 2, loud);
+}
+
+#[test]
+fn test_ym2612_tl_snaps_when_keyed_off() {
+    let mut ym = Ym2612::new();
+    // TL written before key-on takes effect immediately
+    key_on_single_carrier(&mut ym, 0x08);
+    step_fm_samples(&mut ym, 1);
+    let first = ym.generate_channel_samples()[0] as i32;
+    step_fm_samples(&mut ym, 16);
+    assert_eq!(first, ym.generate_channel_samples()[0] as i32);
+}
+
+/// Key on channel 0's OP4 with instant attack, the given decay rate and key
+/// scaling, at `block`/`fnum`, and return its envelope after `samples`.
+fn decay_after(ks: u8, block: u8, fnum: u16, samples: u32) -> u16 {
+    let mut ym = Ym2612::new();
+    ym.write_addr(Bank::Bank0, 0xA4);
+    ym.write_data_bank(Bank::Bank0, (block << 3) | (fnum >> 8) as u8);
+    ym.write_addr(Bank::Bank0, 0xA0);
+    ym.write_data_bank(Bank::Bank0, fnum as u8);
+
+    ym.write_addr(Bank::Bank0, 0x5C);
+    ym.write_data_bank(Bank::Bank0, (ks << 6) | 0x1F);
+    ym.write_addr(Bank::Bank0, 0x6C);
+    ym.write_data_bank(Bank::Bank0, 0x08); // DR
+    ym.write_addr(Bank::Bank0, 0x8C);
+    ym.write_data_bank(Bank::Bank0, 0xFF); // SL 15, RR 15
+
+    ym.write_addr(Bank::Bank0, 0x28);
+    ym.write_data_bank(Bank::Bank0, 0x80);
+    step_fm_samples(&mut ym, samples);
+    ym.envelope_level(0, 3)
+}
+
+#[test]
+fn test_ym2612_ksr_speeds_up_high_notes() {
+    let low = decay_after(3, 0, 0x100, 2000);
+    let high = decay_after(3, 7, 0x7FF, 2000);
+    assert!(
+        high > low,
+        "High note should have decayed further (low={}, high={})",
+        low,
+        high
+    );
+
+    // With KS=0 only the top two key code bits contribute
+    let low_ks0 = decay_after(0, 0, 0x100, 2000);
+    let high_ks0 = decay_after(0, 7, 0x7FF, 2000);
+    assert!(high - low > high_ks0 - low_ks0);
+    assert_eq!(low, low_ks0, "Key code 0 isn't affected by KS");
+}
+
+/// Key on channel 0 with algorithm 7 and only OP1 audible, at the given
+/// feedback level, and collect its output for the first `samples`.
+fn op1_output_with_feedback(feedback: u8, samples: usize) -> Vec<i16> {
+    let mut ym = Ym2612::new();
+    ym.write_addr(Bank::Bank0, 0xA4);
+    ym.write_data_bank(Bank::Bank0, 0x20); // block 4
+    ym.write_addr(Bank::Bank0, 0xA0);
+    ym.write_data_bank(Bank::Bank0, 0x00);
+    ym.write_addr(Bank::Bank0, 0xB0);
+    ym.write_data_bank(Bank::Bank0, (feedback << 3) | 0x07);
+    for (op_off, level) in [(0u8, 0x00), (4, 0x7F), (8, 0x7F), (12, 0x7F)] {
+        ym.write_addr(Bank::Bank0, 0x30 + op_off);
+        ym.write_data_bank(Bank::Bank0, 0x01); // MUL 1
+        ym.write_addr(Bank::Bank0, 0x40 + op_off);
+        ym.write_data_bank(Bank::Bank0, level);
+        ym.write_addr(Bank::Bank0, 0x50 + op_off);
+        ym.write_data_bank(Bank::Bank0, 0x1F);
+    }
+    ym.write_addr(Bank::Bank0, 0x28);
+    ym.write_data_bank(Bank::Bank0, 0xF0);
+    (0..samples)
+        .map(|_| {
+            step_fm_samples(&mut ym, 1);
+            ym.generate_channel_samples()[0]
+        })
+        .collect()
+}
+
+#[test]
+fn test_ym2612_op1_feedback_advances_phase() {
+    let plain = op1_output_with_feedback(0, 8);
+    let fed = op1_output_with_feedback(7, 8);
+    assert_eq!(
+        plain[0], fed[0],
+        "No history to feed back on the first sample"
+    );
+
+    // Early in the rising quarter the fed-back output is positive, so it
+    // pushes OP1's phase forward and the sine climbs faster
+    let first_diff = (1..8)
+        .find(|&i| plain[i] != fed[i])
+        .expect("FB=7 must change OP1");
+    assert!(plain[first_diff] > 0);
+    assert!(
+        fed[first_diff] > plain[first_diff],
+        "Feedback should lead the plain sine (plain={:?}, fed={:?})",
+        plain,
+        fed
+    );
+
+    // Lower amounts deviate less from the pure sine
+    let mild = op1_output_with_feedback(1, 8);
+    assert!(
+        (mild[first_diff] - plain[first_diff]).abs() < (fed[first_diff] - plain[first_diff]).abs()
+    );
+}
+
+/// Key on channel 0's OP4 with the given attack rate and key scaling at
+/// `block`/`fnum`, and return its envelope after `samples`.
+fn attack_after(ar: u8, ks: u8, block: u8, fnum: u16, samples: u32) -> u16 {
+    let mut ym = Ym2612::new();
+    ym.write_addr(Bank::Bank0, 0xA4);
+    ym.write_data_bank(Bank::Bank0, (block << 3) | (fnum >> 8) as u8);
+    ym.write_addr(Bank::Bank0, 0xA0);
+    ym.write_data_bank(Bank::Bank0, fnum as u8);
+
+    ym.write_addr(Bank::Bank0, 0x5C);
+    ym.write_data_bank(Bank::Bank0, (ks << 6) | ar);
+    ym.write_addr(Bank::Bank0, 0x28);
+    ym.write_data_bank(Bank::Bank0, 0x80);
+    step_fm_samples(&mut ym, samples);
+    ym.envelope_level(0, 3)
+}
+
+#[test]
+fn test_ym2612_attack_rate_31_is_instant() {
+    assert_eq!(attack_after(0x1F, 0, 0, 0x100, 1), 0);
+
+    // AR 30 on a low note ramps, but KSR on a high note lifts it to rate 63
+    let ramped = attack_after(0x1E, 3, 0, 0x100, 1);
+    assert!(ramped > 0, "Rate 60 should still ramp, got {}", ramped);
+    assert_eq!(attack_after(0x1E, 3, 7, 0x7FF, 1), 0);
+}
+
+#[test]
+fn test_ym2612_dac_quantizes_each_channel_to_9_bits() {
+    // Loud mix: three channels at the positive limit, one at the negative
+    // limit, and two quiet ones whose low bits the DAC can't represent
+    let outputs = [0x1FFF, 0x1FFF, 0x1FFF, -0x2000, 100, 31];
+    let all = [true; 6];
+    // 255 * 3 - 256 + 3 + 0, scaled by 4
+    assert_eq!(mix_dac(&outputs, all), 2048);
+    // Summing first and quantizing after would give (sum >> 3) = 2064
+    assert_ne!(mix_dac(&outputs, all), outputs.iter().sum::<i32>() >> 3);
+
+    // Negative values round towards minus infinity, as the DAC truncates
+    // two's complement
+    assert_eq!(
+        mix_dac(&[-1, -31, -32, -33, 0, 0], all),
+        (-1 - 1 - 1 - 2) * 4
+    );
+
+    // Only channels panned to this side contribute
+    let left_only = [true, false, true, false, false, false];
+    assert_eq!(mix_dac(&outputs, left_only), (255 + 255) * 4);
+}
+
+#[test]
+fn test_ym2612_dac_channel_reaches_the_9_bit_extremes() {
+    let mut ym = Ym2612::new();
+    ym.write_addr(Bank::Bank0, 0x2B);
+    ym.write_data_bank(Bank::Bank0, 0x80);
+    for (dac, expected) in [(0xFF, 254 * 4), (0x00, -256 * 4), (0x80, 0)] {
+        ym.write_addr(Bank::Bank0, 0x2A);
+        ym.write_data_bank(Bank::Bank0, dac);
+        step_fm_samples(&mut ym, 1);
+        assert_eq!(ym.last_left, expected, "DAC {:02X}", dac);
+        assert_eq!(ym.last_right, expected, "DAC {:02X}", dac);
+    }
+}
+
+#[test]
+fn test_ym2612_algorithm_change_reroutes_a_sounding_note() {
+    let mut ym = Ym2612::new();
+    ym.write_addr(Bank::Bank0, 0xA4);
+    ym.write_data_bank(Bank::Bank0, 0x22);
+    ym.write_addr(Bank::Bank0, 0xA0);
+    ym.write_data_bank(Bank::Bank0, 0x69);
+    // Algorithm 7: all four operators are carriers, but only OP1 is loud
+    ym.write_addr(Bank::Bank0, 0xB0);
+    ym.write_data_bank(Bank::Bank0, 0x07);
+    for (op_off, level) in [(0u8, 0x00), (4, 0x7F), (8, 0x7F), (12, 0x7F)] {
+        ym.write_addr(Bank::Bank0, 0x30 + op_off);
+        ym.write_data_bank(Bank::Bank0, 0x01); // MUL 1
+        ym.write_addr(Bank::Bank0, 0x40 + op_off);
+        ym.write_data_bank(Bank::Bank0, level);
+        ym.write_addr(Bank::Bank0, 0x50 + op_off);
+        ym.write_data_bank(Bank::Bank0, 0x1F);
+    }
+    ym.write_addr(Bank::Bank0, 0x28);
+    ym.write_data_bank(Bank::Bank0, 0xF0);
+
+    let peak = |ym: &mut Ym2612| {
+        (0..64)
+            .map(|_| {
+                step_fm_samples(ym, 1);
+                (ym.generate_channel_samples()[0] as i32).abs()
+            })
+            .max()
+            .unwrap()
+    };
+    let as_carrier = peak(&mut ym);
+    assert!(as_carrier > 1000, "OP1 is heard directly ({as_carrier})");
+
+    // Algorithm 0 without re-keying: OP1 only modulates OP2, and the chain
+    // ends in the silent OP4
+    ym.write_addr(Bank::Bank0, 0xB0);
+    ym.write_data_bank(Bank::Bank0, 0x00);
+    let as_modulator = peak(&mut ym);
+    assert!(
+        as_modulator < as_carrier / 16,
+        "{as_modulator} vs {as_carrier}"
+    );
+
+    ym.write_addr(Bank::Bank0, 0xB0);
+    ym.write_data_bank(Bank::Bank0, 0x07);
+    assert!(peak(&mut ym) > 1000, "back to a carrier");
+    assert_eq!(ym.envelope_level(0, 0), 0, "the note was never re-keyed");
+}
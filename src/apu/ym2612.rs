@@ -190,6 +190,9 @@ struct FmOperator {
     key_on: bool,
     last_output: i16,
     last_output2: i16,
+    /// Effective total level (7-bit), ramped towards the register value while keyed on
+    #[serde(default)]
+    total_level: u16,
 }
 
 impl FmOperator {
@@ -201,15 +204,31 @@ impl FmOperator {
             key_on: false,
             last_output: 0,
             last_output2: 0,
+            total_level: 0,
         }
     }
 
-    fn set_key_on(&mut self, on: bool) {
+    /// Move the effective TL towards the register value.
+    /// While a note is sounding, TL changes are applied one step (0.75dB) per
+    /// sample so that mid-note volume writes don't click; otherwise (and at
+    /// key-on) they snap.
+    fn update_total_level(&mut self, target: u16) {
+        if !self.key_on {
+            self.total_level = target;
+        } else if self.total_level < target {
+            self.total_level += 1;
+        } else if self.total_level > target {
+            self.total_level -= 1;
+        }
+    }
+
+    fn set_key_on(&mut self, on: bool, total_level: u16) {
         if on == self.key_on {
             return;
         }
         self.key_on = on;
         if on {
+            self.total_level = total_level;
             self.phase_counter = 0;
             self.env_phase = AdsrPhase::Attack;
         } else {
@@ -247,18 +266,7 @@ impl FmOperator {
             AdsrPhase::Sustain => params.sr,
             AdsrPhase::Release => (params.rr << 1) | 1,
         };
-        let ks_shift = match params.ks {
-            0 => 3,
-            1 => 2,
-            2 => 1,
-            3 => 0,
-            _ => 3,
-        };
-        let rate = if base_rate == 0 {
-            0
-        } else {
-            ((base_rate as u16 * 2) + (params.kc >> ks_shift) as u16).min(63) as u8
-        };
+        let rate = key_scaled_rate(base_rate, params.ks, params.kc);
         let shift = 11u8.saturating_sub(rate / 4);
         if rate >= 48 || (params.counter & ((1 << shift) - 1)) == 0 {
             let step_idx = ((params.counter >> shift) & 7) as usize;
@@ -266,6 +274,8 @@ impl FmOperator {
             if increment > 0 {
                 match self.env_phase {
                     AdsrPhase::Attack => {
+                        // Rates 62-63 (AR 31, or a high AR lifted by KSR)
+                        // skip the exponential ramp entirely
                         if rate >= 62 {
                             self.env_level = 0;
                         } else {
@@ -287,8 +297,14 @@ impl FmOperator {
         }
     }
 
-    fn compute_output(&self, phase_mod: i16, total_level: u16) -> i16 {
-        let total_atten = (self.env_level + (total_level << 3)).min(0x3FF);
+    /// Compute the operator output for the current phase.
+    ///
+    /// The OPN2 has no waveform select (unlike the OPM/OPL families), so the
+    /// output is always the sine from `LOG_SINE_TABLE`. All attenuation (EG
+    /// level plus TL, both 10-bit, TL in 0.75dB steps) is summed in the log
+    /// domain before the single exp conversion.
+    fn compute_output(&self, phase_mod: i16) -> i16 {
+        let total_atten = (self.env_level + (self.total_level << 3)).min(0x3FF);
         let phase = (((self.phase_counter >> 10) & 0x3FF) as i32 + phase_mod as i32) as u32;
         let sign = (phase >> 9) & 1;
         let table_idx = if phase & (1 << 8) == 0 {
@@ -365,45 +381,89 @@ impl FmChannel {
                 counter,
             });
         }
-        let tl: [u16; 4] =
-            std::array::from_fn(|i| (regs[0x40 + op_offsets[i] + ch_off] & 0x7F) as u16);
+        for (op, op_offset) in self.operators.iter_mut().zip(op_offsets) {
+            op.update_total_level((regs[0x40 + op_offset + ch_off] & 0x7F) as u16);
+        }
+        // OP1 self-modulation: the average of its last two outputs, scaled
+        // down so that FB=1 is pi/16 and FB=7 is 4*pi of phase deviation
         let fb = if self.feedback > 0 {
             ((self.operators[0].last_output as i32 + self.operators[0].last_output2 as i32) >> 1)
                 >> (9 - self.feedback as i32)
         } else {
             0
         } as i16;
-        let out1 = self.operators[0].compute_output(fb, tl[0]);
+        let out1 = self.operators[0].compute_output(fb);
+        // Modulator inputs are halved; OP3 takes OP2's output from the
+        // previous sample wherever OP2 feeds it, as on hardware.
+        let op2_prev = self.operators[1].last_output as i32;
+        let m1 = out1 as i32;
+        let modulate = |op: &FmOperator, input: i32| op.compute_output((input >> 1) as i16);
         let (out2, out3, out4) = match self.algorithm {
             0 => {
-                let o3 =
-                    self.operators[2].compute_output(self.operators[1].last_output >> 1, tl[2]);
+                let o3 = modulate(&self.operators[2], op2_prev);
                 (
-                    self.operators[1].compute_output(out1 >> 1, tl[1]),
+                    modulate(&self.operators[1], m1),
                     o3,
-                    self.operators[3].compute_output(o3 >> 1, tl[3]),
+                    modulate(&self.operators[3], o3 as i32),
                 )
             }
-            7 => (
-                self.operators[1].compute_output(0, tl[1]),
-                self.operators[2].compute_output(0, tl[2]),
-                self.operators[3].compute_output(0, tl[3]),
+            1 => {
+                let o3 = modulate(&self.operators[2], m1 + op2_prev);
+                (
+                    modulate(&self.operators[1], 0),
+                    o3,
+                    modulate(&self.operators[3], o3 as i32),
+                )
+            }
+            2 => {
+                let o3 = modulate(&self.operators[2], op2_prev);
+                (
+                    modulate(&self.operators[1], 0),
+                    o3,
+                    modulate(&self.operators[3], m1 + o3 as i32),
+                )
+            }
+            3 => {
+                let o2 = modulate(&self.operators[1], m1);
+                let o3 = modulate(&self.operators[2], 0);
+                (o2, o3, modulate(&self.operators[3], o2 as i32 + o3 as i32))
+            }
+            4 => {
+                let o3 = modulate(&self.operators[2], 0);
+                (
+                    modulate(&self.operators[1], m1),
+                    o3,
+                    modulate(&self.operators[3], o3 as i32),
+                )
+            }
+            5 => (
+                modulate(&self.operators[1], m1),
+                modulate(&self.operators[2], m1),
+                modulate(&self.operators[3], m1),
+            ),
+            6 => (
+                modulate(&self.operators[1], m1),
+                modulate(&self.operators[2], 0),
+                modulate(&self.operators[3], 0),
+            ),
+            _ => (
+                modulate(&self.operators[1], 0),
+                modulate(&self.operators[2], 0),
+                modulate(&self.operators[3], 0),
             ),
-            _ => (0, 0, self.operators[3].compute_output(0, tl[3])),
         };
         self.operators[0].last_output2 = self.operators[0].last_output;
         self.operators[0].last_output = out1;
         self.operators[1].last_output = out2;
         self.operators[2].last_output = out3;
         self.operators[3].last_output = out4;
-        let channel_out = match self.algorithm {
-            0 => out4,
-            7 => out1
-                .wrapping_add(out2)
-                .wrapping_add(out3)
-                .wrapping_add(out4),
-            _ => out4,
+        let carriers = match self.algorithm {
+            0..=3 => out4 as i32,
+            4 => out2 as i32 + out4 as i32,
+            5 | 6 => out2 as i32 + out3 as i32 + out4 as i32,
+            _ => out1 as i32 + out2 as i32 + out3 as i32 + out4 as i32,
         };
+        let channel_out = limit_channel_output(carriers);
         self.last_sample = channel_out;
         channel_out
     }
@@ -470,6 +530,10 @@ impl Ym2612 {
         *self = Self::new();
         self.blip_l = bl;
         self.blip_r = br;
+        // The clock restarts at 0, so deltas queued against the old clock
+        // would otherwise land in the wrong place
+        self.blip_l.clear();
+        self.blip_r.clear();
     }
 
     pub fn read_status(&self) -> u8 {
@@ -537,10 +601,9 @@ impl Ym2612 {
     /// Advance internal FM synthesis by one sample point (53.26 kHz)
     fn internal_step(&mut self) {
         self.env_counter = (self.env_counter + 1) & 0xFFF;
-        let mut left = 0i32;
-        let mut right = 0i32;
-        for i in 0..6 {
-            let out = if i == 5 && self.dac_en {
+        let mut outputs = [0i32; 6];
+        for (i, out) in outputs.iter_mut().enumerate() {
+            *out = if i == 5 && self.dac_en {
                 (self.dac_val as i32 - 128) << 6
             } else {
                 self.channels[i].clock(
@@ -549,15 +612,15 @@ impl Ym2612 {
                     self.env_counter,
                 ) as i32
             };
-            if self.channels[i].panning_l {
-                left += out;
-            }
-            if self.channels[i].panning_r {
-                right += out;
-            }
         }
-        left >>= 3;
-        right >>= 3;
+        let left = mix_dac(
+            &outputs,
+            std::array::from_fn(|i| self.channels[i].panning_l),
+        );
+        let right = mix_dac(
+            &outputs,
+            std::array::from_fn(|i| self.channels[i].panning_r),
+        );
         let dl = left - self.last_left;
         if dl != 0 {
             self.blip_l.add_delta(self.total_mclocks, dl);
@@ -600,8 +663,10 @@ impl Ym2612 {
                     _ => 7,
                 } as usize;
                 if c < 6 {
-                    for i in 0..4 {
-                        self.channels[c].operators[i].set_key_on((v & (0x10 << i)) != 0);
+                    let regs = &self.registers[c / 3];
+                    for (i, op_offset) in [0usize, 8, 4, 12].into_iter().enumerate() {
+                        let tl = (regs[0x40 + op_offset + c % 3] & 0x7F) as u16;
+                        self.channels[c].operators[i].set_key_on((v & (0x10 << i)) != 0, tl);
                     }
                 }
             }
@@ -624,6 +689,24 @@ impl Ym2612 {
                 // Actually internal_step() will pick it up next time it runs.
             }
             (Bank::Bank0, 0x2B) => self.dac_en = (v & 0x80) != 0,
+            // Frequency: the high byte (block + fnum bits 8-10) is latched
+            // and only takes effect when the low byte is written
+            (_, 0xA4..=0xA6) => {
+                let c = (a - 0xA4) as usize + bank_idx * 3;
+                self.channels[c].fnum_latch = v & 0x3F;
+            }
+            (_, 0xA0..=0xA2) => {
+                let ch = &mut self.channels[(a - 0xA0) as usize + bank_idx * 3];
+                ch.fnum = (((ch.fnum_latch & 0x07) as u16) << 8) | v as u16;
+                ch.block = (ch.fnum_latch >> 3) & 0x07;
+            }
+            // Feedback (bits 3-5) and algorithm (bits 0-2) are read live
+            // by every sample, so a change reroutes a sounding note at once
+            (_, 0xB0..=0xB2) => {
+                let ch = &mut self.channels[(a - 0xB0) as usize + bank_idx * 3];
+                ch.feedback = (v >> 3) & 0x07;
+                ch.algorithm = v & 0x07;
+            }
             (_, 0xB4..=0xB6) => {
                 let c = (a - 0xB4) as usize + bank_idx * 3;
                 if c < 6 {
@@ -646,6 +729,12 @@ impl Ym2612 {
         }
     }
 
+    /// Current 10-bit envelope attenuation of an operator (0 = loudest).
+    /// Operators are numbered OP1-OP4 as 0-3.
+    pub fn envelope_level(&self, channel: usize, operator: usize) -> u16 {
+        self.channels[channel].operators[operator].env_level
+    }
+
     pub fn generate_channel_samples(&mut self) -> [i16; 6] {
         std::array::from_fn(|i| self.channels[i].last_sample)
     }
@@ -657,6 +746,38 @@ impl Default for Ym2612 {
     }
 }
 
+/// The output accumulator sums a channel's carriers and saturates to the
+/// 14-bit range rather than wrapping
+fn limit_channel_output(carriers: i32) -> i16 {
+    carriers.clamp(-0x2000, 0x1FFF) as i16
+}
+
+/// DAC value for one side. The DAC is time-multiplexed between the six
+/// channels and only sees the top 9 bits of each 14-bit output, so the low
+/// bits are dropped per channel before mixing, not after. The sum is scaled
+/// by 4 to keep the level of the full-precision mix (14-bit >> 3).
+pub(crate) fn mix_dac(outputs: &[i32; 6], panned: [bool; 6]) -> i32 {
+    outputs
+        .iter()
+        .zip(panned)
+        .filter(|&(_, on)| on)
+        .map(|(&out, _)| (out >> 5).clamp(-0x100, 0xFF))
+        .sum::<i32>()
+        * 4
+}
+
+/// Effective 6-bit envelope rate: `2 * R + (KC >> (3 - KS))`, clamped to 63.
+/// Key scaling (RS bits 6-7 of 0x50-0x5E) adds up to the full 5-bit key code
+/// at KS=3, so higher notes run their envelopes faster. A zero rate stays
+/// zero regardless of KS.
+fn key_scaled_rate(base_rate: u8, ks: u8, kc: u8) -> u8 {
+    if base_rate == 0 {
+        return 0;
+    }
+    let rks = kc >> (3 - (ks & 3));
+    ((base_rate as u16 * 2) + rks as u16).min(63) as u8
+}
+
 fn compute_key_code(f: u32, b: u8) -> u8 {
     let f11 = (f >> 10) & 1;
     let f10 = (f >> 9) & 1;
@@ -0,0 +1,123 @@
+//! Cheat Code Lists
+//!
+//! A cheat list is a labelled set of ROM patches that can be toggled and
+//! saved alongside a game. Codes use the raw decoded form, a 24-bit ROM
+//! address and the 16-bit word that replaces it:
+//!
+//! ```text
+//! 00A1C4:6002
+//! ```
+//!
+//! Lists are stored as JSON:
+//!
+//! ```json
+//! { "cheats": [ { "label": "Infinite lives", "code": "00A1C4:6002", "enabled": true } ] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Maximum cheat list size in bytes (1MB) to prevent OOM
+const MAX_CHEAT_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A single labelled code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cheat {
+    pub label: String,
+    pub code: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Cheat {
+    pub fn new(label: &str, code: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            code: code.to_string(),
+            enabled: true,
+        }
+    }
+
+    /// Decode the code into the ROM address it patches and the new word
+    pub fn patch(&self) -> Result<(u32, u16), String> {
+        let (addr, value) = self
+            .code
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| format!("Cheat '{}': expected AAAAAA:VVVV", self.label))?;
+        let addr = u32::from_str_radix(addr, 16)
+            .map_err(|e| format!("Cheat '{}': bad address: {}", self.label, e))?;
+        let value = u16::from_str_radix(value, 16)
+            .map_err(|e| format!("Cheat '{}': bad value: {}", self.label, e))?;
+        if addr > 0x3FFFFF || !addr.is_multiple_of(2) {
+            return Err(format!(
+                "Cheat '{}': address {:06X} is not an even ROM address",
+                self.label, addr
+            ));
+        }
+        Ok((addr, value))
+    }
+}
+
+/// The set of codes kept for a game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheatList {
+    pub cheats: Vec<Cheat>,
+}
+
+impl CheatList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a code, rejecting ones that don't decode
+    pub fn add(&mut self, cheat: Cheat) -> Result<(), String> {
+        cheat.patch()?;
+        self.cheats.push(cheat);
+        Ok(())
+    }
+
+    /// Patches for every enabled code, in list order
+    pub fn active_patches(&self) -> Vec<(u32, u16)> {
+        self.cheats
+            .iter()
+            .filter(|cheat| cheat.enabled)
+            .filter_map(|cheat| cheat.patch().ok())
+            .collect()
+    }
+
+    /// Load a list from a JSON file. Every code must decode.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open cheat list: {}", e))?;
+
+        let mut buffer = Vec::new();
+        file.take(MAX_CHEAT_FILE_SIZE + 1)
+            .read_to_end(&mut buffer)
+            .map_err(|e| format!("Failed to read cheat list: {}", e))?;
+        if buffer.len() as u64 > MAX_CHEAT_FILE_SIZE {
+            return Err(format!(
+                "Cheat list too large: exceeds {} bytes",
+                MAX_CHEAT_FILE_SIZE
+            ));
+        }
+
+        let list: Self =
+            serde_json::from_slice(&buffer).map_err(|e| format!("Invalid cheat list: {}", e))?;
+        for cheat in &list.cheats {
+            cheat.patch()?;
+        }
+        Ok(list)
+    }
+
+    /// Save the list as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write cheat list: {}", e))
+    }
+}
@@ -53,7 +53,8 @@ fn decode_group_0(opcode: u16) -> Instruction {
                 opcode,
             }))
     } else {
-        decode_static_bit(opcode)
+        decode_moves(opcode)
+            .or_else(|| decode_static_bit(opcode))
             .or_else(|| decode_ccr_sr_immediate(opcode))
             .or_else(|| decode_immediate_alu(opcode))
             .unwrap_or(Instruction::System(SystemInstruction::Unimplemented {
@@ -62,6 +63,15 @@ fn decode_group_0(opcode: u16) -> Instruction {
     }
 }
 
+/// MOVES (0x0Exx) only exists from the 68010 on, so the model decides at
+/// execute time whether the row traps
+fn decode_moves(opcode: u16) -> Option<Instruction> {
+    if opcode & 0xFF00 == 0x0E00 {
+        return Some(Instruction::System(SystemInstruction::Moves { opcode }));
+    }
+    None
+}
+
 fn decode_movep(opcode: u16) -> Option<Instruction> {
     if opcode & 0x0138 == 0x0108 {
         let reg = ((opcode >> 9) & 0x07) as u8;
@@ -308,18 +318,14 @@ fn decode_group_4_control(opcode: u16) -> Option<Instruction> {
         }
     }
 
-    // JMP
-    if opcode & 0xFFC0 == 0x4EC0 {
-        if let Some(dst) = AddressingMode::from_mode_reg(mode, reg) {
-            return Some(Instruction::System(SystemInstruction::Jmp { dst }));
-        }
-    }
-
-    // JSR
-    if opcode & 0xFFC0 == 0x4E80 {
-        if let Some(dst) = AddressingMode::from_mode_reg(mode, reg) {
-            return Some(Instruction::System(SystemInstruction::Jsr { dst }));
-        }
+    // JMP / JSR: only control addressing modes; the rest are illegal
+    if opcode & 0xFF80 == 0x4E80 {
+        let instr = match AddressingMode::from_mode_reg(mode, reg) {
+            Some(dst) if dst.is_control() && opcode & 0x0040 != 0 => SystemInstruction::Jmp { dst },
+            Some(dst) if dst.is_control() => SystemInstruction::Jsr { dst },
+            _ => SystemInstruction::Illegal,
+        };
+        return Some(Instruction::System(instr));
     }
 
     // CHK - 0100 rrr 1s0 mmm xxx (s=0 word, s=1 long for 68020+)
@@ -415,7 +421,18 @@ fn decode_group_4_arithmetic(opcode: u16) -> Option<Instruction> {
     // MOVE from SR
     if opcode & 0xFFC0 == 0x40C0 {
         if let Some(dst) = AddressingMode::from_mode_reg(mode, reg) {
-            return Some(Instruction::System(SystemInstruction::MoveFromSr { dst }));
+            if dst.is_data_alterable() {
+                return Some(Instruction::System(SystemInstruction::MoveFromSr { dst }));
+            }
+        }
+    }
+
+    // MOVE from CCR (68010+; the executor rejects it on a 68000)
+    if opcode & 0xFFC0 == 0x42C0 {
+        if let Some(dst) = AddressingMode::from_mode_reg(mode, reg) {
+            if dst.is_data_alterable() {
+                return Some(Instruction::System(SystemInstruction::MoveFromCcr { dst }));
+            }
         }
     }
 
@@ -483,8 +500,8 @@ fn decode_group_6(opcode: u16) -> Instruction {
     let condition_bits = ((opcode >> 8) & 0x0F) as u8;
     let displacement_byte = (opcode & 0xFF) as i8;
 
-    // Displacement of 0 means 16-bit displacement follows
-    // Displacement of 0xFF means 32-bit displacement follows (68020+)
+    // Displacement of 0 means 16-bit displacement follows. 0xFF introduces
+    // a 32-bit displacement only on the 68020+; the 68000 takes it as -1
     let displacement = displacement_byte as i16;
 
     match condition_bits {
@@ -579,6 +596,10 @@ fn decode_sub(opcode: u16) -> Instruction {
         0..=2 => {
             // SUB <ea>, Dn
             if let Some(size) = Size::from_bits(opmode) {
+                if size == Size::Byte && ea_mode == 0b001 {
+                    // Address registers have no byte form (word/long SUBA only)
+                    return Instruction::System(SystemInstruction::Illegal);
+                }
                 if let Some(src) = AddressingMode::from_mode_reg(ea_mode, ea_reg) {
                     return Instruction::Arithmetic(ArithmeticInstruction::Sub {
                         size,
@@ -773,6 +794,10 @@ fn decode_add(opcode: u16) -> Instruction {
         0..=2 => {
             // ADD <ea>, Dn
             if let Some(size) = Size::from_bits(opmode) {
+                if size == Size::Byte && ea_mode == 0b001 {
+                    // Address registers have no byte form (word/long ADDA only)
+                    return Instruction::System(SystemInstruction::Illegal);
+                }
                 if let Some(src) = AddressingMode::from_mode_reg(ea_mode, ea_reg) {
                     return Instruction::Arithmetic(ArithmeticInstruction::Add {
                         size,
@@ -0,0 +1,527 @@
+//! M68k Disassembler
+//!
+//! Turns decoded instructions back into Motorola syntax, reading extension
+//! words from memory so operands show real displacements, immediates and
+//! branch targets. Each instruction is advanced over by the same length the
+//! CPU uses (`Instruction::length_words`).
+
+use super::decode;
+use super::instructions::{
+    AddressingMode, ArithmeticInstruction, BitSource, BitsInstruction, DataInstruction,
+    Instruction, ShiftCount, Size, SystemInstruction,
+};
+use crate::memory::MemoryInterface;
+
+/// Disassemble `count` instructions starting at `start`, returning each one's
+/// address, text and length in bytes.
+///
+/// These are debugger reads, so any wait states they incur on the bus are
+/// dropped rather than charged to the CPU's next instruction.
+pub fn disassemble_range<M: MemoryInterface + ?Sized>(
+    memory: &mut M,
+    start: u32,
+    count: usize,
+) -> Vec<(u32, String, u32)> {
+    let mut lines = Vec::with_capacity(count);
+    let mut addr = start & 0x00FF_FFFE;
+    for _ in 0..count {
+        let instr = decode(memory.read_word(addr));
+        let length = instr.length_words() * 2;
+        lines.push((addr, disassemble(memory, addr, &instr), length));
+        addr = addr.wrapping_add(length) & 0x00FF_FFFF;
+    }
+    memory.take_wait_cycles();
+    lines
+}
+
+/// Format `instr`, located at `addr`, reading its extension words
+pub fn disassemble<M: MemoryInterface + ?Sized>(
+    memory: &mut M,
+    addr: u32,
+    instr: &Instruction,
+) -> String {
+    let mut ext = Extensions {
+        memory,
+        addr: addr.wrapping_add(2),
+    };
+    match instr {
+        Instruction::Data(data) => format_data(&mut ext, data),
+        Instruction::Arithmetic(arith) => format_arithmetic(&mut ext, arith),
+        Instruction::Bits(bits) => format_bits(&mut ext, bits),
+        Instruction::System(sys) => format_system(&mut ext, addr, sys),
+    }
+}
+
+/// Reads the extension words following an opcode, in order
+struct Extensions<'a, M: ?Sized> {
+    memory: &'a mut M,
+    addr: u32,
+}
+
+impl<M: MemoryInterface + ?Sized> Extensions<'_, M> {
+    fn word(&mut self) -> u16 {
+        let value = self.memory.read_word(self.addr);
+        self.addr = self.addr.wrapping_add(2);
+        value
+    }
+
+    fn long(&mut self) -> u32 {
+        let high = self.word() as u32;
+        (high << 16) | self.word() as u32
+    }
+
+    fn immediate(&mut self, size: Size) -> String {
+        match size {
+            Size::Byte => format!("#${:02X}", self.word() & 0xFF),
+            Size::Word => format!("#${:04X}", self.word()),
+            Size::Long => format!("#${:08X}", self.long()),
+        }
+    }
+
+    fn operand(&mut self, mode: &AddressingMode, size: Size) -> String {
+        match mode {
+            AddressingMode::AddressDisplacement(r) => {
+                format!("{}(A{})", signed_hex(self.word() as i16 as i32), r)
+            }
+            AddressingMode::AddressIndex(r) => {
+                let index = self.word();
+                format!(
+                    "{}(A{},{})",
+                    signed_hex(index as i8 as i32),
+                    r,
+                    index_reg(index)
+                )
+            }
+            AddressingMode::AbsoluteShort => format!("(${:04X}).W", self.word()),
+            AddressingMode::AbsoluteLong => format!("(${:08X}).L", self.long()),
+            AddressingMode::PcDisplacement => {
+                let base = self.addr;
+                let target = base.wrapping_add(self.word() as i16 as u32);
+                format!("${:06X}(PC)", target & 0x00FF_FFFF)
+            }
+            AddressingMode::PcIndex => {
+                let index = self.word();
+                format!(
+                    "{}(PC,{})",
+                    signed_hex(index as i8 as i32),
+                    index_reg(index)
+                )
+            }
+            AddressingMode::Immediate => self.immediate(size),
+            _ => mode.to_string(),
+        }
+    }
+}
+
+fn signed_hex(value: i32) -> String {
+    if value < 0 {
+        format!("-${:X}", -value)
+    } else {
+        format!("${:X}", value)
+    }
+}
+
+/// Index register named by a brief extension word, e.g. `D3.W`
+fn index_reg(ext: u16) -> String {
+    let kind = if ext & 0x8000 != 0 { 'A' } else { 'D' };
+    let size = if ext & 0x0800 != 0 { 'L' } else { 'W' };
+    format!("{}{}.{}", kind, (ext >> 12) & 7, size)
+}
+
+/// MOVEM register list, e.g. `D0-D3/A6`. Predecrement masks run A7..D0.
+fn register_list(mask: u16, predecrement: bool) -> String {
+    let mask = if predecrement {
+        mask.reverse_bits()
+    } else {
+        mask
+    };
+    let mut groups = Vec::new();
+    for (kind, bits) in [('D', mask & 0xFF), ('A', mask >> 8)] {
+        let mut reg = 0;
+        while reg < 8 {
+            if bits & (1 << reg) == 0 {
+                reg += 1;
+                continue;
+            }
+            let first = reg;
+            while reg < 8 && bits & (1 << reg) != 0 {
+                reg += 1;
+            }
+            if reg - 1 == first {
+                groups.push(format!("{}{}", kind, first));
+            } else {
+                groups.push(format!("{}{}-{}{}", kind, first, kind, reg - 1));
+            }
+        }
+    }
+    groups.join("/")
+}
+
+fn shift_operands<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    size: Size,
+    dst: &AddressingMode,
+    count: &ShiftCount,
+) -> String {
+    match (dst, count) {
+        (AddressingMode::DataRegister(r), ShiftCount::Immediate(n)) => {
+            format!("{} #{},D{}", size, n, r)
+        }
+        (AddressingMode::DataRegister(r), ShiftCount::Register(c)) => {
+            format!("{} D{},D{}", size, c, r)
+        }
+        _ => format!(".W {}", ext.operand(dst, Size::Word)),
+    }
+}
+
+fn bit_operands<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    bit: &BitSource,
+    dst: &AddressingMode,
+) -> String {
+    let bit = match bit {
+        BitSource::Immediate => format!("#{}", ext.word() & 0xFF),
+        BitSource::Register(r) => format!("D{}", r),
+    };
+    format!("{},{}", bit, ext.operand(dst, Size::Byte))
+}
+
+fn register_pair(name: &str, size: &str, src: u8, dst: u8, memory_mode: bool) -> String {
+    if memory_mode {
+        format!("{}{} -(A{}),-(A{})", name, size, src, dst)
+    } else {
+        format!("{}{} D{},D{}", name, size, src, dst)
+    }
+}
+
+fn format_data<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    data: &DataInstruction,
+) -> String {
+    match data {
+        DataInstruction::Move { size, src, dst } => {
+            let src = ext.operand(src, *size);
+            format!("MOVE{} {},{}", size, src, ext.operand(dst, *size))
+        }
+        DataInstruction::MoveA { size, src, dst_reg } => {
+            format!("MOVEA{} {},A{}", size, ext.operand(src, *size), dst_reg)
+        }
+        DataInstruction::MoveQ { dst_reg, data } => format!("MOVEQ #{},D{}", data, dst_reg),
+        DataInstruction::Lea { src, dst_reg } => {
+            format!("LEA {},A{}", ext.operand(src, Size::Long), dst_reg)
+        }
+        DataInstruction::Pea { src } => format!("PEA {}", ext.operand(src, Size::Long)),
+        DataInstruction::Clr { size, dst } => format!("CLR{} {}", size, ext.operand(dst, *size)),
+        DataInstruction::Exg { rx, ry, mode } => match mode {
+            0x08 => format!("EXG D{},D{}", rx, ry),
+            0x09 => format!("EXG A{},A{}", rx, ry),
+            _ => format!("EXG D{},A{}", rx, ry),
+        },
+        DataInstruction::Movep {
+            size,
+            reg,
+            an,
+            direction,
+        } => {
+            let mem = format!("{}(A{})", signed_hex(ext.word() as i16 as i32), an);
+            if *direction {
+                format!("MOVEP{} D{},{}", size, reg, mem)
+            } else {
+                format!("MOVEP{} {},D{}", size, mem, reg)
+            }
+        }
+        DataInstruction::Movem {
+            size,
+            direction,
+            ea,
+            ..
+        } => {
+            let predecrement = matches!(ea, AddressingMode::AddressPreDecrement(_));
+            let list = register_list(ext.word(), predecrement);
+            let ea = ext.operand(ea, *size);
+            if *direction {
+                format!("MOVEM{} {},{}", size, list, ea)
+            } else {
+                format!("MOVEM{} {},{}", size, ea, list)
+            }
+        }
+        DataInstruction::Swap { reg } => format!("SWAP D{}", reg),
+        DataInstruction::Ext { size, reg } => format!("EXT{} D{}", size, reg),
+    }
+}
+
+fn format_arithmetic<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    arith: &ArithmeticInstruction,
+) -> String {
+    use ArithmeticInstruction as A;
+    match arith {
+        A::Add { size, src, dst, .. } | A::Sub { size, src, dst, .. } => {
+            let name = if matches!(arith, A::Add { .. }) {
+                "ADD"
+            } else {
+                "SUB"
+            };
+            let src = ext.operand(src, *size);
+            format!("{}{} {},{}", name, size, src, ext.operand(dst, *size))
+        }
+        A::AddA { size, src, dst_reg }
+        | A::SubA { size, src, dst_reg }
+        | A::CmpA { size, src, dst_reg } => {
+            let name = match arith {
+                A::AddA { .. } => "ADDA",
+                A::SubA { .. } => "SUBA",
+                _ => "CMPA",
+            };
+            format!("{}{} {},A{}", name, size, ext.operand(src, *size), dst_reg)
+        }
+        A::AddI { size, dst } | A::SubI { size, dst } | A::CmpI { size, dst } => {
+            let name = match arith {
+                A::AddI { .. } => "ADDI",
+                A::SubI { .. } => "SUBI",
+                _ => "CMPI",
+            };
+            let imm = ext.immediate(*size);
+            format!("{}{} {},{}", name, size, imm, ext.operand(dst, *size))
+        }
+        A::AddQ { size, dst, data } | A::SubQ { size, dst, data } => {
+            let name = if matches!(arith, A::AddQ { .. }) {
+                "ADDQ"
+            } else {
+                "SUBQ"
+            };
+            format!("{}{} #{},{}", name, size, data, ext.operand(dst, *size))
+        }
+        A::MulU { src, dst_reg }
+        | A::MulS { src, dst_reg }
+        | A::DivU { src, dst_reg }
+        | A::DivS { src, dst_reg }
+        | A::Chk { src, dst_reg } => {
+            let name = match arith {
+                A::MulU { .. } => "MULU",
+                A::MulS { .. } => "MULS",
+                A::DivU { .. } => "DIVU",
+                A::DivS { .. } => "DIVS",
+                _ => "CHK",
+            };
+            format!("{}.W {},D{}", name, ext.operand(src, Size::Word), dst_reg)
+        }
+        A::Neg { size, dst } | A::NegX { size, dst } | A::Tst { size, dst } => {
+            let name = match arith {
+                A::Neg { .. } => "NEG",
+                A::NegX { .. } => "NEGX",
+                _ => "TST",
+            };
+            format!("{}{} {}", name, size, ext.operand(dst, *size))
+        }
+        A::Abcd {
+            src_reg,
+            dst_reg,
+            memory_mode,
+        } => register_pair("ABCD", "", *src_reg, *dst_reg, *memory_mode),
+        A::Sbcd {
+            src_reg,
+            dst_reg,
+            memory_mode,
+        } => register_pair("SBCD", "", *src_reg, *dst_reg, *memory_mode),
+        A::Nbcd { dst } => format!("NBCD {}", ext.operand(dst, Size::Byte)),
+        A::AddX {
+            size,
+            src_reg,
+            dst_reg,
+            memory_mode,
+        } => register_pair("ADDX", &size.to_string(), *src_reg, *dst_reg, *memory_mode),
+        A::SubX {
+            size,
+            src_reg,
+            dst_reg,
+            memory_mode,
+        } => register_pair("SUBX", &size.to_string(), *src_reg, *dst_reg, *memory_mode),
+        A::Cmp { size, src, dst_reg } => {
+            format!("CMP{} {},D{}", size, ext.operand(src, *size), dst_reg)
+        }
+        A::CmpM { size, ax, ay } => format!("CMPM{} (A{})+,(A{})+", size, ay, ax),
+    }
+}
+
+fn format_bits<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    bits: &BitsInstruction,
+) -> String {
+    use BitsInstruction as B;
+    match bits {
+        B::And { size, src, dst, .. } | B::Or { size, src, dst, .. } => {
+            let name = if matches!(bits, B::And { .. }) {
+                "AND"
+            } else {
+                "OR"
+            };
+            let src = ext.operand(src, *size);
+            format!("{}{} {},{}", name, size, src, ext.operand(dst, *size))
+        }
+        B::AndI { size, dst } | B::OrI { size, dst } | B::EorI { size, dst } => {
+            let name = match bits {
+                B::AndI { .. } => "ANDI",
+                B::OrI { .. } => "ORI",
+                _ => "EORI",
+            };
+            let imm = ext.immediate(*size);
+            format!("{}{} {},{}", name, size, imm, ext.operand(dst, *size))
+        }
+        B::Eor { size, src_reg, dst } => {
+            format!("EOR{} D{},{}", size, src_reg, ext.operand(dst, *size))
+        }
+        B::Not { size, dst } => format!("NOT{} {}", size, ext.operand(dst, *size)),
+        B::Lsl { size, dst, count }
+        | B::Lsr { size, dst, count }
+        | B::Asl { size, dst, count }
+        | B::Asr { size, dst, count }
+        | B::Rol { size, dst, count }
+        | B::Ror { size, dst, count }
+        | B::Roxl { size, dst, count }
+        | B::Roxr { size, dst, count } => {
+            let name = match bits {
+                B::Lsl { .. } => "LSL",
+                B::Lsr { .. } => "LSR",
+                B::Asl { .. } => "ASL",
+                B::Asr { .. } => "ASR",
+                B::Rol { .. } => "ROL",
+                B::Ror { .. } => "ROR",
+                B::Roxl { .. } => "ROXL",
+                _ => "ROXR",
+            };
+            format!("{}{}", name, shift_operands(ext, *size, dst, count))
+        }
+        B::AslM { dst } => format!("ASL.W {}", ext.operand(dst, Size::Word)),
+        B::AsrM { dst } => format!("ASR.W {}", ext.operand(dst, Size::Word)),
+        B::Btst { bit, dst } => format!("BTST {}", bit_operands(ext, bit, dst)),
+        B::Bset { bit, dst } => format!("BSET {}", bit_operands(ext, bit, dst)),
+        B::Bclr { bit, dst } => format!("BCLR {}", bit_operands(ext, bit, dst)),
+        B::Bchg { bit, dst } => format!("BCHG {}", bit_operands(ext, bit, dst)),
+        B::Tas { dst } => format!("TAS {}", ext.operand(dst, Size::Byte)),
+    }
+}
+
+fn format_system<M: MemoryInterface + ?Sized>(
+    ext: &mut Extensions<M>,
+    addr: u32,
+    sys: &SystemInstruction,
+) -> String {
+    use SystemInstruction as S;
+    // Branch displacements are relative to the word after the opcode
+    let base = addr.wrapping_add(2);
+    let branch = |ext: &mut Extensions<M>, displacement: i16| {
+        let displacement = if displacement == 0 {
+            ext.word() as i16
+        } else {
+            displacement
+        };
+        base.wrapping_add(displacement as u32) & 0x00FF_FFFF
+    };
+    match sys {
+        S::Bra { displacement } => format!("BRA ${:06X}", branch(ext, *displacement)),
+        S::Bsr { displacement } => format!("BSR ${:06X}", branch(ext, *displacement)),
+        S::Bcc {
+            condition,
+            displacement,
+        } => format!(
+            "B{} ${:06X}",
+            condition.mnemonic(),
+            branch(ext, *displacement)
+        ),
+        S::Scc { condition, dst } => {
+            format!("S{} {}", condition.mnemonic(), ext.operand(dst, Size::Byte))
+        }
+        S::DBcc { condition, reg } => {
+            let target = base.wrapping_add(ext.word() as i16 as u32) & 0x00FF_FFFF;
+            format!("DB{} D{},${:06X}", condition.mnemonic(), reg, target)
+        }
+        S::Jmp { dst } => format!("JMP {}", ext.operand(dst, Size::Long)),
+        S::Jsr { dst } => format!("JSR {}", ext.operand(dst, Size::Long)),
+        S::Rts => "RTS".to_string(),
+        S::Rte => "RTE".to_string(),
+        S::Rtr => "RTR".to_string(),
+        S::Nop => "NOP".to_string(),
+        S::Reset => "RESET".to_string(),
+        S::Stop => format!("STOP {}", ext.immediate(Size::Word)),
+        S::MoveUsp { reg, to_usp } => {
+            if *to_usp {
+                format!("MOVE A{},USP", reg)
+            } else {
+                format!("MOVE USP,A{}", reg)
+            }
+        }
+        S::Trap { vector } => format!("TRAP #{}", vector),
+        S::TrapV => "TRAPV".to_string(),
+        S::Link { reg } => format!("LINK A{},#{}", reg, signed_hex(ext.word() as i16 as i32)),
+        S::Unlk { reg } => format!("UNLK A{}", reg),
+        S::MoveToSr { src } => format!("MOVE {},SR", ext.operand(src, Size::Word)),
+        S::MoveFromSr { dst } => format!("MOVE SR,{}", ext.operand(dst, Size::Word)),
+        S::MoveFromCcr { dst } => format!("MOVE CCR,{}", ext.operand(dst, Size::Word)),
+        S::MoveToCcr { src } => format!("MOVE {},CCR", ext.operand(src, Size::Word)),
+        S::AndiToCcr => format!("ANDI {},CCR", ext.immediate(Size::Byte)),
+        S::AndiToSr => format!("ANDI {},SR", ext.immediate(Size::Word)),
+        S::OriToCcr => format!("ORI {},CCR", ext.immediate(Size::Byte)),
+        S::OriToSr => format!("ORI {},SR", ext.immediate(Size::Word)),
+        S::EoriToCcr => format!("EORI {},CCR", ext.immediate(Size::Byte)),
+        S::EoriToSr => format!("EORI {},SR", ext.immediate(Size::Word)),
+        S::Illegal => "ILLEGAL".to_string(),
+        S::LineA { opcode }
+        | S::LineF { opcode }
+        | S::Moves { opcode }
+        | S::Unimplemented { opcode } => {
+            format!("DC.W ${:04X}", opcode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Memory;
+
+    #[test]
+    fn test_disassemble_range_advances_by_instruction_length() {
+        let mut memory = Memory::new(0x10000);
+        let program: [u16; 13] = [
+            0x7005, // MOVEQ #5,D0
+            0x33FC, 0x1234, 0x00FF, 0x8000, // MOVE.W #$1234,($00FF8000).L
+            0x4E71, // NOP
+            0x48E7, 0xC002, // MOVEM.L D0-D1/A6,-(A7)
+            0x51C8, 0xFFF2, // DBF D0,$001004
+            0x41FA, 0x0010, // LEA $001026(PC),A0
+            0x4E75, // RTS
+        ];
+        for (i, word) in program.iter().enumerate() {
+            memory.write_word(0x1000 + i as u32 * 2, *word);
+        }
+
+        let lines = disassemble_range(&mut memory, 0x1000, 7);
+        let expected = [
+            (0x1000, "MOVEQ #5,D0", 2),
+            (0x1002, "MOVE.W #$1234,($00FF8000).L", 8),
+            (0x100A, "NOP", 2),
+            (0x100C, "MOVEM.L D0-D1/A6,-(A7)", 4),
+            (0x1010, "DBF D0,$001004", 4),
+            (0x1014, "LEA $001026(PC),A0", 4),
+            (0x1018, "RTS", 2),
+        ];
+        for (line, (addr, text, length)) in lines.iter().zip(expected) {
+            assert_eq!(*line, (addr, text.to_string(), length));
+        }
+    }
+
+    #[test]
+    fn test_disassemble_branches_and_unknown_words() {
+        let mut memory = Memory::new(0x10000);
+        memory.write_word(0x2000, 0x6700); // BEQ.W
+        memory.write_word(0x2002, 0x0100);
+        memory.write_word(0x2004, 0x60FE); // BRA.S to itself
+        memory.write_word(0x2006, 0xA123); // line A
+
+        let lines = disassemble_range(&mut memory, 0x2000, 3);
+        assert_eq!(lines[0], (0x2000, "BEQ $002102".to_string(), 4));
+        assert_eq!(lines[1], (0x2004, "BRA $002004".to_string(), 2));
+        assert_eq!(lines[2], (0x2006, "DC.W $A123".to_string(), 2));
+    }
+}
@@ -152,6 +152,21 @@ impl AddressingMode {
         self.is_alterable() && !matches!(self, AddressingMode::AddressRegister(_))
     }
 
+    /// Returns true if this mode is "Control": a memory location with no
+    /// register side effects, as required by JMP, JSR, LEA and PEA
+    pub fn is_control(&self) -> bool {
+        matches!(
+            self,
+            AddressingMode::AddressIndirect(_)
+                | AddressingMode::AddressDisplacement(_)
+                | AddressingMode::AddressIndex(_)
+                | AddressingMode::AbsoluteShort
+                | AddressingMode::AbsoluteLong
+                | AddressingMode::PcDisplacement
+                | AddressingMode::PcIndex
+        )
+    }
+
     /// Returns the number of extension words needed for this addressing mode
     pub fn extension_words(&self, size: Size) -> u32 {
         match self {
@@ -717,9 +732,15 @@ pub enum SystemInstruction {
     MoveFromSr {
         dst: AddressingMode,
     },
+    MoveFromCcr {
+        dst: AddressingMode,
+    },
     MoveToCcr {
         src: AddressingMode,
     },
+    Moves {
+        opcode: u16,
+    },
     AndiToCcr,
     AndiToSr,
     OriToCcr,
@@ -741,20 +762,9 @@ pub enum SystemInstruction {
 impl SystemInstruction {
     pub fn extension_words(&self) -> u32 {
         match self {
-            SystemInstruction::Bra { displacement } | SystemInstruction::Bsr { displacement } => {
-                if *displacement == 0 {
-                    1
-                } else {
-                    0
-                }
-            }
-            SystemInstruction::Bcc { displacement, .. } => {
-                if *displacement == 0 {
-                    1
-                } else {
-                    0
-                }
-            }
+            SystemInstruction::Bra { displacement }
+            | SystemInstruction::Bsr { displacement }
+            | SystemInstruction::Bcc { displacement, .. } => u32::from(*displacement == 0),
             SystemInstruction::Scc { dst, .. } => dst.extension_words(Size::Byte),
             SystemInstruction::DBcc { .. }
             | SystemInstruction::Link { .. }
@@ -765,7 +775,9 @@ impl SystemInstruction {
             SystemInstruction::MoveToSr { src } | SystemInstruction::MoveToCcr { src } => {
                 src.extension_words(Size::Word)
             }
-            SystemInstruction::MoveFromSr { dst } => dst.extension_words(Size::Word),
+            SystemInstruction::MoveFromSr { dst } | SystemInstruction::MoveFromCcr { dst } => {
+                dst.extension_words(Size::Word)
+            }
             SystemInstruction::AndiToCcr
             | SystemInstruction::AndiToSr
             | SystemInstruction::OriToCcr
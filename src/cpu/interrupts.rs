@@ -0,0 +1,90 @@
+//! 68000 Interrupt Priority Resolution
+//!
+//! On the Mega Drive the VDP drives the 68000's IPL lines for its three
+//! interrupt sources. Each source raises or drops its level here; the CPU
+//! samples the highest pending level before every instruction and
+//! acknowledges the one it takes, leaving any lower sources pending.
+
+use serde::{Deserialize, Serialize};
+
+/// Interrupt sources wired to the 68000 on the Mega Drive, by IPL level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptSource {
+    /// Controller TH pin via the VDP (level 2)
+    External = 2,
+    /// VDP horizontal interrupt (level 4)
+    HBlank = 4,
+    /// VDP vertical interrupt (level 6)
+    VBlank = 6,
+}
+
+impl InterruptSource {
+    pub const ALL: [InterruptSource; 3] = [Self::VBlank, Self::HBlank, Self::External];
+
+    pub const fn level(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_level(level: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.level() == level)
+    }
+}
+
+/// Pending interrupt levels 1-7 as a bitmask (bit N = level N)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InterruptController {
+    pending: u8,
+}
+
+impl InterruptController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assert `level`; out-of-range levels are ignored
+    pub fn raise(&mut self, level: u8) {
+        if (1..=7).contains(&level) {
+            self.pending |= 1 << level;
+        }
+    }
+
+    /// Drop `level` without it having been serviced
+    pub fn clear(&mut self, level: u8) {
+        if (1..=7).contains(&level) {
+            self.pending &= !(1 << level);
+        }
+    }
+
+    /// Drive a source's line to match the device's pending flag
+    pub fn set_line(&mut self, source: InterruptSource, asserted: bool) {
+        if asserted {
+            self.raise(source.level());
+        } else {
+            self.clear(source.level());
+        }
+    }
+
+    pub fn is_pending(&self, level: u8) -> bool {
+        level <= 7 && self.pending & (1 << level) != 0
+    }
+
+    /// Highest pending level, or 0 if none
+    pub fn highest(&self) -> u8 {
+        match self.pending & 0xFE {
+            0 => 0,
+            mask => 7 - mask.leading_zeros() as u8,
+        }
+    }
+
+    /// Clear the serviced level and return the source it belongs to, so the
+    /// caller can acknowledge the device as well
+    pub fn acknowledge(&mut self, level: u8) -> Option<InterruptSource> {
+        self.clear(level);
+        InterruptSource::from_level(level)
+    }
+
+    pub fn clear_all(&mut self) {
+        self.pending = 0;
+    }
+}
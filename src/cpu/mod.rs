@@ -1,9 +1,12 @@
 use crate::memory::MemoryInterface;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub mod addressing;
 pub mod decoder;
+pub mod disasm;
 pub mod instructions;
+pub mod interrupts;
 pub mod ops;
 
 pub use addressing::EffectiveAddress;
@@ -12,9 +15,89 @@ use instructions::{
     ArithmeticInstruction, BitSource, BitsInstruction, DataInstruction, DecodeCacheEntry,
     Instruction, SystemInstruction,
 };
+pub use interrupts::{InterruptController, InterruptSource};
 
 const CACHE_ROM_LIMIT: u32 = 0x400000; // 4MB ROM
 const CACHE_MASK: u32 = 0x1FFFFF; // 2M entries
+/// Work RAM and its mirrors
+const CACHE_RAM_START: u32 = 0xE00000;
+const CACHE_RAM_END: u32 = 0xFFFFFF;
+const CACHE_RAM_MASK: u32 = 0x7FFF; // 64KB of opcode words
+
+/// Why the CPU stopped fetching instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HaltReason {
+    /// STOP instruction; the next accepted interrupt resumes execution
+    Stopped,
+    /// An exception was raised while another was being processed. Only a
+    /// reset recovers.
+    DoubleFault,
+    /// Halted from outside the CPU (debugger, test harness, /HALT line)
+    ExternalHalt,
+}
+
+/// CPU variant, for the few instructions whose behaviour differs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CpuModel {
+    /// Original 68000 as fitted to the Mega Drive: MOVE from SR is
+    /// unprivileged and MOVE from CCR doesn't exist
+    #[default]
+    M68000,
+    /// 68010: MOVE from SR is privileged and MOVE from CCR is added
+    M68010,
+}
+
+/// Part of the 68k address space an opcode was fetched from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FetchRegion {
+    /// Cartridge space, 0x000000-0x3FFFFF, including any SRAM window
+    Rom,
+    /// Z80 RAM and the sound chips seen through the Z80 window
+    Z80Area,
+    Io,
+    Vdp,
+    WorkRam,
+    /// Anything else, such as the unused expansion area
+    Unmapped,
+}
+
+impl FetchRegion {
+    pub fn of(address: u32) -> Self {
+        match address & 0xFFFFFF {
+            0x000000..=0x3FFFFF => FetchRegion::Rom,
+            0xA00000..=0xA0FFFF => FetchRegion::Z80Area,
+            0xA10000..=0xA1FFFF => FetchRegion::Io,
+            0xC00000..=0xDFFFFF => FetchRegion::Vdp,
+            0xE00000..=0xFFFFFF => FetchRegion::WorkRam,
+            _ => FetchRegion::Unmapped,
+        }
+    }
+}
+
+/// 68000 exception groups, highest priority first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExceptionGroup {
+    /// Reset, bus error and address error. These abort the current instruction.
+    Group0,
+    /// Trace, interrupt, illegal instruction and privilege violation
+    Group1,
+    /// Traps raised by instruction execution (TRAP, TRAPV, CHK, divide by zero)
+    Group2,
+}
+
+impl ExceptionGroup {
+    pub fn of(vector: u32) -> Self {
+        match vector {
+            0..=3 => ExceptionGroup::Group0,
+            5..=7 | 32..=47 => ExceptionGroup::Group2,
+            _ => ExceptionGroup::Group1,
+        }
+    }
+}
+
+/// Observer called with each decoded instruction and its address, just
+/// before it executes
+pub type ExecuteHook = Box<dyn FnMut(&Instruction, u32)>;
 
 #[derive(Serialize, Deserialize)]
 pub struct Cpu {
@@ -24,14 +107,58 @@ pub struct Cpu {
     pub sr: u16,
     pub usp: u32,
     pub ssp: u32,
-    pub halted: bool,
+    #[serde(default)]
+    pub halt_reason: Option<HaltReason>,
     pub pending_interrupt: u8,
-    pub interrupt_pending_mask: u8,
+    /// Asserted IPL levels; `pending_interrupt` caches the highest
+    #[serde(alias = "interrupt_pending_mask")]
+    pub interrupts: InterruptController,
     pub pending_exception: bool,
+    /// Highest priority exception group raised during the current step
+    #[serde(skip)]
+    pub step_exception: Option<ExceptionGroup>,
     pub last_interrupt_level: u8,
     pub cycles: u64,
     #[serde(skip)]
     pub decode_cache: Box<[DecodeCacheEntry]>,
+    /// Decodes of opcodes fetched from work RAM, when enabled with
+    /// `set_ram_decode_cache`. The CPU's own writes invalidate the opcodes
+    /// they overwrite; anything else writing code into RAM must call
+    /// `invalidate_cache`.
+    #[serde(skip)]
+    ram_decode_cache: Option<Box<[DecodeCacheEntry]>>,
+    /// Opcodes decoded rather than served from a cache
+    #[serde(skip)]
+    pub decode_count: u64,
+    /// Take the illegal instruction exception (vector 4) on opcodes the
+    /// decoder doesn't implement. When cleared they execute as a 4-cycle NOP.
+    #[serde(default = "default_trap_unimplemented")]
+    pub trap_unimplemented: bool,
+    #[serde(default)]
+    pub model: CpuModel,
+    /// Model the prefetch queue: the opcode after each instruction is read
+    /// before the instruction runs, so an instruction that rewrites the one
+    /// following it still executes the old opcode, as on hardware
+    #[serde(default)]
+    pub emulate_prefetch: bool,
+    /// Address and opcode of the next sequential instruction, latched while
+    /// `emulate_prefetch` is set
+    #[serde(skip)]
+    prefetch: Option<(u32, u16)>,
+    /// Count of each unimplemented opcode encountered, keyed by opcode
+    #[serde(skip)]
+    unimplemented_opcodes: BTreeMap<u16, u64>,
+    /// Instructions executed from each region, while enabled with
+    /// `set_region_trace`
+    #[serde(skip)]
+    region_trace: Option<BTreeMap<FetchRegion, u64>>,
+    /// Optional per-instruction observer, e.g. for coverage-guided fuzzing
+    #[serde(skip)]
+    execute_hook: Option<ExecuteHook>,
+}
+
+fn default_trap_unimplemented() -> bool {
+    true
 }
 
 pub mod flags {
@@ -52,7 +179,7 @@ pub struct CpuState {
     pub a: [u32; 8],
     pub pc: u32,
     pub sr: u16,
-    pub halted: bool,
+    pub halt_reason: Option<HaltReason>,
     pub pending_interrupt: u8,
 }
 
@@ -60,6 +187,8 @@ impl Cpu {
     pub fn new<M: MemoryInterface>(memory: &mut M) -> Self {
         let ssp = memory.read_long(0);
         let pc = memory.read_long(4);
+        // Vector fetches belong to the reset sequence, not the first instruction
+        memory.take_wait_cycles();
         let cache_size = (CACHE_MASK + 1) as usize;
         let cache = vec![DecodeCacheEntry::default(); cache_size].into_boxed_slice();
 
@@ -70,13 +199,23 @@ impl Cpu {
             sr: 0x2700,
             usp: 0,
             ssp,
-            halted: false,
+            halt_reason: None,
             pending_interrupt: 0,
-            interrupt_pending_mask: 0,
+            interrupts: InterruptController::new(),
             pending_exception: false,
+            step_exception: None,
             last_interrupt_level: 0,
             cycles: 0,
             decode_cache: cache,
+            ram_decode_cache: None,
+            decode_count: 0,
+            trap_unimplemented: true,
+            model: CpuModel::default(),
+            emulate_prefetch: false,
+            prefetch: None,
+            unimplemented_opcodes: BTreeMap::new(),
+            region_trace: None,
+            execute_hook: None,
         };
         cpu.a[7] = ssp;
         cpu
@@ -85,13 +224,16 @@ impl Cpu {
     pub fn reset<M: MemoryInterface>(&mut self, memory: &mut M) {
         self.ssp = memory.read_long(0);
         self.pc = memory.read_long(4);
+        memory.take_wait_cycles();
         self.sr = 0x2700;
         self.a[7] = self.ssp;
-        self.halted = false;
+        self.halt_reason = None;
         self.pending_interrupt = 0;
-        self.interrupt_pending_mask = 0;
+        self.interrupts.clear_all();
         self.pending_exception = false;
+        self.step_exception = None;
         self.last_interrupt_level = 0;
+        self.prefetch = None;
         self.invalidate_cache();
     }
 
@@ -122,6 +264,7 @@ impl Cpu {
                 self.process_exception(3, memory);
                 return;
             }
+            self.invalidate_written_code(addr, size.bytes());
         }
         addressing::write_ea(ea, size, value, &mut self.d, &mut self.a, memory)
     }
@@ -162,38 +305,154 @@ impl Cpu {
             let _cycles = self.process_exception(vector, memory);
             self.sr = (self.sr & !flags::INTERRUPT_MASK) | ((level as u16) << 8);
             self.acknowledge_interrupt(level);
-            self.halted = false;
+            self.halt_reason = None;
             self.last_interrupt_level = level;
             return 44;
         }
         0
     }
 
+    pub fn halted(&self) -> bool {
+        self.halt_reason.is_some()
+    }
+
+    pub fn halt(&mut self, reason: HaltReason) {
+        self.halt_reason = Some(reason);
+    }
+
+    pub fn resume(&mut self) {
+        self.halt_reason = None;
+    }
+
+    /// Unimplemented opcodes executed so far with their counts, sorted by opcode
+    pub fn unimplemented_report(&self) -> Vec<(u16, u64)> {
+        self.unimplemented_opcodes
+            .iter()
+            .map(|(&opcode, &count)| (opcode, count))
+            .collect()
+    }
+
+    pub fn clear_unimplemented_report(&mut self) {
+        self.unimplemented_opcodes.clear();
+    }
+
+    /// Count executed instructions by the region their opcode came from,
+    /// e.g. to spot a game copying code into work RAM and running it there.
+    /// Turning the trace on or off discards the counts so far.
+    pub fn set_region_trace(&mut self, enabled: bool) {
+        self.region_trace = enabled.then(BTreeMap::new);
+    }
+
+    /// Instructions executed from each region since the trace was enabled,
+    /// sorted by region. Empty when the trace is off.
+    pub fn region_trace_report(&self) -> Vec<(FetchRegion, u64)> {
+        self.region_trace
+            .iter()
+            .flatten()
+            .map(|(&region, &count)| (region, count))
+            .collect()
+    }
+
+    /// Install a hook that sees every instruction before it executes, along
+    /// with its PC. Replaces any previous hook. Interrupt entry isn't an
+    /// instruction and isn't reported.
+    pub fn on_execute<F: FnMut(&Instruction, u32) + 'static>(&mut self, hook: F) {
+        self.execute_hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_execute_hook(&mut self) {
+        self.execute_hook = None;
+    }
+
+    fn exec_unimplemented<M: MemoryInterface>(&mut self, opcode: u16, memory: &mut M) -> u32 {
+        let count = self.unimplemented_opcodes.entry(opcode).or_insert(0);
+        if *count == 0 {
+            log::warn!(
+                "Unimplemented opcode {:04X} at PC={:06X}",
+                opcode,
+                self.pc.wrapping_sub(2)
+            );
+        }
+        *count += 1;
+        if self.trap_unimplemented {
+            self.process_exception(4, memory) // Illegal instruction
+        } else {
+            4
+        }
+    }
+
+    /// Drop every cached decode. Needed after code the CPU runs is
+    /// rewritten by anything but the 68k itself, such as DMA or the Z80
+    /// writing work RAM through its bank window.
     pub fn invalidate_cache(&mut self) {
         self.decode_cache.fill(DecodeCacheEntry::default());
+        if let Some(cache) = self.ram_decode_cache.as_mut() {
+            cache.fill(DecodeCacheEntry::default());
+        }
     }
 
-    pub fn request_interrupt(&mut self, level: u8) {
-        if level > 0 && level <= 7 {
-            self.interrupt_pending_mask |= 1 << level;
-            self.update_pending_interrupt();
+    /// Cache decodes of code running from work RAM too. Off by default.
+    ///
+    /// Only the 68k's own writes, stack pushes included, drop stale
+    /// entries. The Z80 writing 68k RAM through its bank window doesn't
+    /// reach the CPU, so code that runs RAM the Z80 has rewritten must call
+    /// `invalidate_cache` first.
+    pub fn set_ram_decode_cache(&mut self, enabled: bool) {
+        self.ram_decode_cache = enabled.then(|| {
+            vec![DecodeCacheEntry::default(); (CACHE_RAM_MASK + 1) as usize].into_boxed_slice()
+        });
+    }
+
+    /// Cache slot that would hold the decode of the opcode at `pc`
+    fn cache_entry_mut(&mut self, pc: u32) -> Option<&mut DecodeCacheEntry> {
+        // Only 24 address lines, so sign-extended addresses alias low memory
+        let pc = pc & 0xFF_FFFF;
+        if pc < CACHE_ROM_LIMIT {
+            self.decode_cache.get_mut(((pc >> 1) & CACHE_MASK) as usize)
+        } else if (CACHE_RAM_START..=CACHE_RAM_END).contains(&pc) {
+            let cache = self.ram_decode_cache.as_mut()?;
+            cache.get_mut(((pc >> 1) & CACHE_RAM_MASK) as usize)
+        } else {
+            None
         }
     }
 
-    pub fn cancel_interrupt(&mut self, level: u8) {
-        if level > 0 && level <= 7 {
-            self.interrupt_pending_mask &= !(1 << level);
-            self.update_pending_interrupt();
+    /// Drop cached decodes of any opcode word in `len` bytes from `addr`.
+    /// Only opcodes are cached; extension words are read as they execute.
+    fn invalidate_written_code(&mut self, addr: u32, len: u32) {
+        let addr = addr & 0xFF_FFFF;
+        let mut word = addr & !1;
+        while word < addr.wrapping_add(len) {
+            if let Some(entry) = self.cache_entry_mut(word) {
+                *entry = DecodeCacheEntry::default();
+            }
+            word = word.wrapping_add(2);
         }
     }
 
+    pub fn request_interrupt(&mut self, level: u8) {
+        self.interrupts.raise(level);
+        self.update_pending_interrupt();
+    }
+
+    pub fn cancel_interrupt(&mut self, level: u8) {
+        self.interrupts.clear(level);
+        self.update_pending_interrupt();
+    }
+
+    /// Drive a source's IPL line to match the device's pending flag
+    pub fn set_interrupt_line(&mut self, source: InterruptSource, asserted: bool) {
+        self.interrupts.set_line(source, asserted);
+        self.update_pending_interrupt();
+    }
+
     pub fn get_state(&self) -> CpuState {
         CpuState {
             d: self.d,
             a: self.a,
             pc: self.pc,
             sr: self.sr,
-            halted: self.halted,
+            halt_reason: self.halt_reason,
             pending_interrupt: self.pending_interrupt,
         }
     }
@@ -203,31 +462,21 @@ impl Cpu {
         self.a = state.a;
         self.pc = state.pc;
         self.sr = state.sr;
-        self.halted = state.halted;
+        self.halt_reason = state.halt_reason;
         self.pending_interrupt = state.pending_interrupt;
     }
 
-    /// Update pending_interrupt to highest priority level from bitmask
+    /// Update pending_interrupt to the highest level the controller holds
     fn update_pending_interrupt(&mut self) {
-        // Find highest set bit in mask
-        for level in (1..=7).rev() {
-            if (self.interrupt_pending_mask & (1 << level)) != 0 {
-                self.pending_interrupt = level;
-                return;
-            }
-        }
-        self.pending_interrupt = 0;
+        self.pending_interrupt = self.interrupts.highest();
     }
 
-    /// Acknowledge an interrupt (called after processing)
-    pub fn acknowledge_interrupt(&mut self, level: u8) {
-        if level > 7 {
-            return;
-        }
-        // Clear the bit for this interrupt level
-        self.interrupt_pending_mask &= !(1 << level);
-        // Update to next highest priority
+    /// Acknowledge an interrupt (called after processing), returning the
+    /// source that was serviced
+    pub fn acknowledge_interrupt(&mut self, level: u8) -> Option<InterruptSource> {
+        let source = self.interrupts.acknowledge(level);
         self.update_pending_interrupt();
+        source
     }
 
     pub fn read_word<M: MemoryInterface>(&mut self, addr: u32, memory: &mut M) -> u16 {
@@ -251,6 +500,7 @@ impl Cpu {
             self.process_exception(3, memory);
             return;
         }
+        self.invalidate_written_code(addr, 2);
         memory.write_word(addr, val);
     }
 
@@ -259,10 +509,12 @@ impl Cpu {
             self.process_exception(3, memory);
             return;
         }
+        self.invalidate_written_code(addr, 4);
         memory.write_long(addr, val);
     }
 
     pub fn write_byte<M: MemoryInterface>(&mut self, addr: u32, val: u8, memory: &mut M) {
+        self.invalidate_written_code(addr, 1);
         memory.write_byte(addr, val);
     }
 
@@ -365,11 +617,13 @@ impl Cpu {
 
     pub fn push_long<M: MemoryInterface>(&mut self, val: u32, memory: &mut M) {
         self.a[7] = self.a[7].wrapping_sub(4);
+        self.invalidate_written_code(self.a[7], 4);
         memory.write_long(self.a[7], val);
     }
 
     pub fn push_word<M: MemoryInterface>(&mut self, val: u16, memory: &mut M) {
         self.a[7] = self.a[7].wrapping_sub(2);
+        self.invalidate_written_code(self.a[7], 2);
         memory.write_word(self.a[7], val);
     }
 
@@ -386,12 +640,14 @@ impl Cpu {
     }
 
     pub fn process_exception<M: MemoryInterface>(&mut self, vector: u32, memory: &mut M) -> u32 {
-        if self.pending_exception {
-            eprintln!("Double fault detected at PC={:X}. Halting.", self.pc);
-            self.halted = true;
+        let group = ExceptionGroup::of(vector);
+        // A group 0 exception aborts the instruction; anything else it would
+        // have raised never happens
+        if self.step_exception == Some(ExceptionGroup::Group0) {
             return 0;
         }
         self.pending_exception = true;
+        self.step_exception = Some(self.step_exception.map_or(group, |g| g.min(group)));
         let old_sr = self.sr;
         self.set_flag(flags::SUPERVISOR, true);
         self.set_flag(flags::TRACE, false);
@@ -401,6 +657,14 @@ impl Cpu {
             self.a[7] = self.ssp;
         }
 
+        // Stacking to an odd SSP is an address error during exception
+        // processing, which halts the 68000
+        if !self.a[7].is_multiple_of(2) {
+            log::error!("Double fault detected at PC={:X}. Halting.", self.pc);
+            self.halt(HaltReason::DoubleFault);
+            return 0;
+        }
+
         self.push_long(self.pc, memory);
         self.push_word(old_sr, memory);
 
@@ -427,58 +691,96 @@ impl Cpu {
 
     pub fn step_instruction<M: MemoryInterface>(&mut self, memory: &mut M) -> u32 {
         self.pending_exception = false;
+        self.step_exception = None;
         self.last_interrupt_level = 0;
 
+        // Only a STOP can be woken by an interrupt
+        if self.halted() && self.halt_reason != Some(HaltReason::Stopped) {
+            return 4;
+        }
+
         let int_cycles = self.check_interrupts(memory);
         if int_cycles > 0 {
+            let int_cycles = int_cycles + memory.take_wait_cycles();
             self.cycles += int_cycles as u64;
             return int_cycles;
         }
 
-        if self.halted {
+        if self.halted() {
             return 4;
         }
 
+        let tracing = (self.sr & flags::TRACE) != 0;
+        let instruction_pc = self.pc;
         let instruction = match self.fetch_next_instruction(memory) {
             Some(instr) => instr,
             None => {
-                self.cycles += 34;
-                return 34;
+                let cycles = 34 + memory.take_wait_cycles();
+                self.cycles += cycles as u64;
+                return cycles;
             }
         };
+        if let Some(hook) = self.execute_hook.as_mut() {
+            hook(&instruction, instruction_pc);
+        }
+        if let Some(trace) = self.region_trace.as_mut() {
+            *trace.entry(FetchRegion::of(instruction_pc)).or_insert(0) += 1;
+        }
+        if self.emulate_prefetch {
+            let next = instruction_pc.wrapping_add(instruction.length_words() * 2);
+            self.prefetch = Some((next, memory.read_word(next)));
+        }
 
-        let cycles = self.execute(instruction, memory);
+        let mut cycles = self.execute(instruction, memory);
+        if tracing {
+            cycles += self.take_trace(memory);
+        }
+        cycles += memory.take_wait_cycles();
         self.cycles += cycles as u64;
         cycles
     }
 
+    /// Trace exception after a traced instruction completes. Group 0 and
+    /// group 1 exceptions mean the instruction never completed, so there is
+    /// nothing to trace; group 2 traps are traced after their own stacking.
+    /// A pending interrupt is taken at the start of the next step, before
+    /// the trace handler's first instruction, as on hardware.
+    fn take_trace<M: MemoryInterface>(&mut self, memory: &mut M) -> u32 {
+        if matches!(
+            self.step_exception,
+            Some(ExceptionGroup::Group0 | ExceptionGroup::Group1)
+        ) || self.halt_reason == Some(HaltReason::DoubleFault)
+        {
+            return 0;
+        }
+        // A traced STOP resumes through the trace handler
+        if self.halt_reason == Some(HaltReason::Stopped) {
+            self.halt_reason = None;
+        }
+        self.process_exception(9, memory)
+    }
+
     fn fetch_next_instruction<M: MemoryInterface>(
         &mut self,
         memory: &mut M,
     ) -> Option<Instruction> {
         let pc = self.pc;
 
-        if pc < CACHE_ROM_LIMIT {
-            let cache_index = ((pc >> 1) & CACHE_MASK) as usize;
-
-            if let Some(entry) = self.decode_cache.get(cache_index).copied() {
-                if entry.pc == pc {
-                    self.pc = pc.wrapping_add(2);
-                    return Some(entry.instruction);
-                }
-
-                let opcode = self.read_instruction_word(pc, memory);
-                if self.pending_exception {
-                    return None;
-                }
-
-                self.pc = self.pc.wrapping_add(2);
-                let instruction = decode(opcode);
+        // The queue only holds the opcode if execution ran on sequentially;
+        // a branch or exception refetches from memory
+        if let Some((addr, opcode)) = self.prefetch.take() {
+            if addr == pc {
+                self.pc = pc.wrapping_add(2);
+                self.decode_count += 1;
+                return Some(decode(opcode));
+            }
+        }
 
-                if let Some(entry_mut) = self.decode_cache.get_mut(cache_index) {
-                    *entry_mut = DecodeCacheEntry { pc, instruction };
-                }
-                return Some(instruction);
+        if let Some(entry) = self.cache_entry_mut(pc).copied() {
+            if entry.pc == pc {
+                memory.note_access(pc);
+                self.pc = pc.wrapping_add(2);
+                return Some(entry.instruction);
             }
         }
 
@@ -488,7 +790,12 @@ impl Cpu {
         }
 
         self.pc = self.pc.wrapping_add(2);
-        Some(decode(opcode))
+        self.decode_count += 1;
+        let instruction = decode(opcode);
+        if let Some(entry) = self.cache_entry_mut(pc) {
+            *entry = DecodeCacheEntry { pc, instruction };
+        }
+        Some(instruction)
     }
 
     fn read_instruction_word<M: MemoryInterface>(&mut self, addr: u32, memory: &mut M) -> u16 {
@@ -729,9 +1036,20 @@ impl Cpu {
                 SystemInstruction::MoveFromSr { dst } => {
                     ops::system::exec_move_from_sr(self, dst, memory)
                 }
+                SystemInstruction::MoveFromCcr { dst } => {
+                    ops::system::exec_move_from_ccr(self, dst, memory)
+                }
                 SystemInstruction::MoveToCcr { src } => {
                     ops::system::exec_move_to_ccr(self, src, memory)
                 }
+                // Added by the 68010, which doesn't emulate it yet
+                SystemInstruction::Moves { opcode } => {
+                    if self.model == CpuModel::M68000 {
+                        self.process_exception(4, memory)
+                    } else {
+                        self.exec_unimplemented(opcode, memory)
+                    }
+                }
                 SystemInstruction::AndiToCcr => ops::system::exec_andi_to_ccr(self, memory),
                 SystemInstruction::AndiToSr => ops::system::exec_andi_to_sr(self, memory),
                 SystemInstruction::OriToCcr => ops::system::exec_ori_to_ccr(self, memory),
@@ -741,8 +1059,8 @@ impl Cpu {
                 SystemInstruction::Illegal => self.process_exception(4, memory),
                 SystemInstruction::LineA { opcode: _ } => self.process_exception(10, memory),
                 SystemInstruction::LineF { opcode: _ } => self.process_exception(11, memory),
-                SystemInstruction::Unimplemented { opcode: _ } => {
-                    self.process_exception(4, memory) // Illegal instruction
+                SystemInstruction::Unimplemented { opcode } => {
+                    self.exec_unimplemented(opcode, memory)
                 }
             },
         }
@@ -809,8 +1127,12 @@ mod tests_m68k_exhaustive;
 #[cfg(test)]
 mod tests_m68k_extended;
 #[cfg(test)]
+mod tests_m68k_flag_properties;
+#[cfg(test)]
 mod tests_m68k_movep;
 #[cfg(test)]
+mod tests_m68k_prefetch;
+#[cfg(test)]
 mod tests_m68k_shift;
 #[cfg(test)]
 mod tests_m68k_torture;
@@ -818,3 +1140,5 @@ mod tests_m68k_torture;
 mod tests_performance;
 #[cfg(test)]
 mod tests_security;
+#[cfg(test)]
+mod tests_unimplemented;
@@ -437,6 +437,19 @@ pub fn exec_cmpm<M: MemoryInterface>(
     }
 }
 
+/// Execution time of CLR, NEG, NEGX and NOT. A data register costs 4
+/// cycles (6 for long); memory costs 8 (12 for long) plus the effective
+/// address time, which for a long operand is 4 more than `calculate_ea`'s
+/// byte/word figure.
+pub fn single_operand_cycles(size: Size, dst: &AddressingMode, ea_cycles: u32) -> u32 {
+    match (size, dst) {
+        (Size::Long, AddressingMode::DataRegister(_)) => 6,
+        (_, AddressingMode::DataRegister(_)) => 4,
+        (Size::Long, _) => 12 + ea_cycles + 4,
+        _ => 8 + ea_cycles,
+    }
+}
+
 pub fn exec_neg<M: MemoryInterface>(
     cpu: &mut Cpu,
     size: Size,
@@ -454,7 +467,7 @@ pub fn exec_neg<M: MemoryInterface>(
     cpu.set_flag(flags::EXTEND, carry);
     cpu.set_flag(flags::OVERFLOW, overflow);
 
-    4 + cycles
+    single_operand_cycles(size, &dst, cycles)
 }
 
 pub fn exec_negx<M: MemoryInterface>(
@@ -478,7 +491,7 @@ pub fn exec_negx<M: MemoryInterface>(
     cpu.set_flag(flags::EXTEND, carry);
     cpu.set_flag(flags::OVERFLOW, overflow);
 
-    4 + cycles
+    single_operand_cycles(size, &dst, cycles)
 }
 
 pub fn exec_mulu<M: MemoryInterface>(
@@ -607,7 +620,7 @@ pub fn exec_clr<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    4 + cycles
+    single_operand_cycles(size, &dst, cycles)
 }
 
 pub fn exec_tst<M: MemoryInterface>(
@@ -623,7 +636,12 @@ pub fn exec_tst<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    4 + cycles
+    // TST only reads, so registers take 4 cycles whatever the size
+    match (size, src) {
+        (_, AddressingMode::DataRegister(_)) => 4,
+        (Size::Long, _) => 8 + cycles,
+        _ => 4 + cycles,
+    }
 }
 
 pub fn exec_abcd<M: MemoryInterface>(
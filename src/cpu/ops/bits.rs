@@ -1,9 +1,28 @@
 use crate::cpu::addressing::{calculate_ea, read_ea};
 use crate::cpu::decoder::{AddressingMode, BitSource, ShiftCount, Size};
 use crate::cpu::flags;
+use crate::cpu::ops::arithmetic::single_operand_cycles;
 use crate::cpu::Cpu;
 use crate::memory::MemoryInterface;
 
+/// AND/OR/EOR timing from the 68000 tables. `ea_cycles` is the word-sized
+/// time `calculate_ea` returns for the memory or immediate operand; long
+/// memory operands need one more bus cycle on top.
+fn logical_cycles(size: Size, src: &AddressingMode, dst: &AddressingMode, ea_cycles: u32) -> u32 {
+    let long = size == Size::Long;
+    match dst {
+        // <ea>,Dn
+        AddressingMode::DataRegister(_) => match (long, src) {
+            (false, _) => 4 + ea_cycles,
+            (true, AddressingMode::DataRegister(_) | AddressingMode::Immediate) => 8 + ea_cycles,
+            (true, _) => 6 + ea_cycles + 4,
+        },
+        // Dn,<ea>: read-modify-write of memory
+        _ if long => 12 + ea_cycles + 4,
+        _ => 8 + ea_cycles,
+    }
+}
+
 pub fn exec_and<M: MemoryInterface>(
     cpu: &mut Cpu,
     size: Size,
@@ -12,14 +31,10 @@ pub fn exec_and<M: MemoryInterface>(
     _direction: bool,
     memory: &mut M,
 ) -> u32 {
-    let mut cycles = 4u32;
-
     let (src_ea, src_cycles) = calculate_ea(src, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += src_cycles;
     let src_val = cpu.cpu_read_ea(src_ea, size, memory);
 
     let (dst_ea, dst_cycles) = calculate_ea(dst, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += dst_cycles;
     let dst_val = cpu.cpu_read_ea(dst_ea, size, memory);
 
     let result = src_val & dst_val;
@@ -30,7 +45,7 @@ pub fn exec_and<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    cycles
+    logical_cycles(size, &src, &dst, src_cycles + dst_cycles)
 }
 
 pub fn exec_andi<M: MemoryInterface>(
@@ -67,14 +82,10 @@ pub fn exec_or<M: MemoryInterface>(
     _direction: bool,
     memory: &mut M,
 ) -> u32 {
-    let mut cycles = 4u32;
-
     let (src_ea, src_cycles) = calculate_ea(src, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += src_cycles;
     let src_val = cpu.cpu_read_ea(src_ea, size, memory);
 
     let (dst_ea, dst_cycles) = calculate_ea(dst, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += dst_cycles;
     let dst_val = cpu.cpu_read_ea(dst_ea, size, memory);
 
     let result = src_val | dst_val;
@@ -85,7 +96,7 @@ pub fn exec_or<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    cycles
+    logical_cycles(size, &src, &dst, src_cycles + dst_cycles)
 }
 
 pub fn exec_ori<M: MemoryInterface>(
@@ -138,7 +149,7 @@ pub fn exec_eor<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    4 + cycles
+    logical_cycles(size, &AddressingMode::DataRegister(src_reg), &dst, cycles)
 }
 
 pub fn exec_eori<M: MemoryInterface>(
@@ -184,7 +195,7 @@ pub fn exec_not<M: MemoryInterface>(
     cpu.set_flag(flags::OVERFLOW, false);
     cpu.set_flag(flags::CARRY, false);
 
-    4 + cycles
+    single_operand_cycles(size, &dst, cycles)
 }
 
 fn calculate_shift_left(
@@ -692,7 +703,7 @@ mod tests {
         assert_eq!(cpu.d[1], 0xFFFFFFFF);
         assert!(cpu.get_flag(flags::NEGATIVE));
         assert!(!cpu.get_flag(flags::ZERO));
-        assert_eq!(cycles, 4);
+        assert_eq!(cycles, 8); // Long register forms take 8
     }
 
     #[test]
@@ -718,7 +729,7 @@ mod tests {
         assert!(cpu.get_flag(flags::ZERO));
         assert!(!cpu.get_flag(flags::CARRY));
         assert!(!cpu.get_flag(flags::OVERFLOW));
-        assert_eq!(cycles, 4);
+        assert_eq!(cycles, 8);
     }
 
     #[test]
@@ -741,7 +752,7 @@ mod tests {
         assert_eq!(memory.read_byte(0x2000), 0xFF);
         assert!(cpu.get_flag(flags::NEGATIVE));
         assert!(!cpu.get_flag(flags::ZERO));
-        assert_eq!(cycles, 8); // 4 (base) + 0 (DataReg) + 4 (AddrIndirect)
+        assert_eq!(cycles, 12); // 8 (Dn,<ea>) + 4 (AddrIndirect)
     }
 
     #[test]
@@ -1185,7 +1196,7 @@ mod tests {
         assert_eq!(cpu.d[1], 0x55555555);
         assert!(!cpu.get_flag(flags::NEGATIVE));
         assert!(!cpu.get_flag(flags::ZERO));
-        assert_eq!(cycles, 4);
+        assert_eq!(cycles, 8); // Long register forms take 8
     }
 
     #[test]
@@ -1208,7 +1219,7 @@ mod tests {
         assert!(!cpu.get_flag(flags::ZERO));
         assert!(!cpu.get_flag(flags::CARRY));
         assert!(!cpu.get_flag(flags::OVERFLOW));
-        assert_eq!(cycles, 8); // 4 + 4 cycles
+        assert_eq!(cycles, 12); // 8 (Dn,<ea>) + 4 (AddrIndirect)
     }
 
     #[test]
@@ -3,6 +3,82 @@ use crate::cpu::decoder::{AddressingMode, Size};
 use crate::cpu::Cpu;
 use crate::memory::MemoryInterface;
 
+/// MOVE.B/MOVE.W execution times from the 68000 user's manual, indexed by
+/// `[source][destination]`. Source rows: Dn, An, (An), (An)+, -(An), d16(An),
+/// d8(An,Xn), xxx.W, xxx.L, d16(PC), d8(PC,Xn), #imm. Destination columns:
+/// Dn, (An), (An)+, -(An), d16(An), d8(An,Xn), xxx.W, xxx.L.
+const MOVE_CYCLES_BW: [[u8; 8]; 12] = [
+    [4, 8, 8, 8, 12, 14, 12, 16],
+    [4, 8, 8, 8, 12, 14, 12, 16],
+    [8, 12, 12, 12, 16, 18, 16, 20],
+    [8, 12, 12, 12, 16, 18, 16, 20],
+    [10, 14, 14, 14, 18, 20, 18, 22],
+    [12, 16, 16, 16, 20, 22, 20, 24],
+    [14, 18, 18, 18, 22, 24, 22, 26],
+    [12, 16, 16, 16, 20, 22, 20, 24],
+    [16, 20, 20, 20, 24, 26, 24, 28],
+    [12, 16, 16, 16, 20, 22, 20, 24],
+    [14, 18, 18, 18, 22, 24, 22, 26],
+    [8, 12, 12, 12, 16, 18, 16, 20],
+];
+
+/// MOVE.L execution times, same layout as [`MOVE_CYCLES_BW`]
+const MOVE_CYCLES_L: [[u8; 8]; 12] = [
+    [4, 12, 12, 12, 16, 18, 16, 20],
+    [4, 12, 12, 12, 16, 18, 16, 20],
+    [12, 20, 20, 20, 24, 26, 24, 28],
+    [12, 20, 20, 20, 24, 26, 24, 28],
+    [14, 22, 22, 22, 26, 28, 26, 30],
+    [16, 24, 24, 24, 28, 30, 28, 32],
+    [18, 26, 26, 26, 30, 32, 30, 34],
+    [16, 24, 24, 24, 28, 30, 28, 32],
+    [20, 28, 28, 28, 32, 34, 32, 36],
+    [16, 24, 24, 24, 28, 30, 28, 32],
+    [18, 26, 26, 26, 30, 32, 30, 34],
+    [12, 20, 20, 20, 24, 26, 24, 28],
+];
+
+fn move_src_index(mode: AddressingMode) -> usize {
+    match mode {
+        AddressingMode::DataRegister(_) => 0,
+        AddressingMode::AddressRegister(_) => 1,
+        AddressingMode::AddressIndirect(_) => 2,
+        AddressingMode::AddressPostIncrement(_) => 3,
+        AddressingMode::AddressPreDecrement(_) => 4,
+        AddressingMode::AddressDisplacement(_) => 5,
+        AddressingMode::AddressIndex(_) => 6,
+        AddressingMode::AbsoluteShort => 7,
+        AddressingMode::AbsoluteLong => 8,
+        AddressingMode::PcDisplacement => 9,
+        AddressingMode::PcIndex => 10,
+        AddressingMode::Immediate => 11,
+    }
+}
+
+fn move_dst_index(mode: AddressingMode) -> usize {
+    match mode {
+        AddressingMode::AddressIndirect(_) => 1,
+        AddressingMode::AddressPostIncrement(_) => 2,
+        AddressingMode::AddressPreDecrement(_) => 3,
+        AddressingMode::AddressDisplacement(_) => 4,
+        AddressingMode::AddressIndex(_) => 5,
+        AddressingMode::AbsoluteShort => 6,
+        AddressingMode::AbsoluteLong => 7,
+        // Dn, plus register-direct forms that decode to MOVEA instead
+        _ => 0,
+    }
+}
+
+/// Total MOVE execution time, including operand fetch and write
+pub fn move_cycles(size: Size, src: AddressingMode, dst: AddressingMode) -> u32 {
+    let table = if size == Size::Long {
+        &MOVE_CYCLES_L
+    } else {
+        &MOVE_CYCLES_BW
+    };
+    table[move_src_index(src)][move_dst_index(dst)] as u32
+}
+
 pub fn exec_move<M: MemoryInterface>(
     cpu: &mut Cpu,
     size: Size,
@@ -10,16 +86,10 @@ pub fn exec_move<M: MemoryInterface>(
     dst: AddressingMode,
     memory: &mut M,
 ) -> u32 {
-    let mut cycles = 4u32;
-
-    let (src_ea, src_cycles) = calculate_ea(src, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += src_cycles;
-
+    let (src_ea, _) = calculate_ea(src, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
     let val = cpu.cpu_read_ea(src_ea, size, memory);
 
-    let (dst_ea, dst_cycles) = calculate_ea(dst, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
-    cycles += dst_cycles;
-
+    let (dst_ea, _) = calculate_ea(dst, size, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
     cpu.cpu_write_ea(dst_ea, size, val, memory);
 
     // Update flags
@@ -27,12 +97,7 @@ pub fn exec_move<M: MemoryInterface>(
     cpu.set_flag(crate::cpu::flags::CARRY, false);
     cpu.set_flag(crate::cpu::flags::OVERFLOW, false);
 
-    // MOVE to register is faster than MOVE to memory
-    if matches!(dst, AddressingMode::DataRegister(_)) {
-        cycles
-    } else {
-        cycles + 4
-    }
+    move_cycles(size, src, dst)
 }
 
 pub fn exec_movea<M: MemoryInterface>(
@@ -1,6 +1,6 @@
 use crate::cpu::addressing::{calculate_ea, EffectiveAddress};
 use crate::cpu::decoder::{AddressingMode, Condition, Size};
-use crate::cpu::Cpu;
+use crate::cpu::{flags, Cpu, CpuModel};
 use crate::memory::MemoryInterface;
 
 pub fn exec_bra<M: MemoryInterface>(cpu: &mut Cpu, displacement: i16, memory: &mut M) -> u32 {
@@ -186,10 +186,8 @@ pub fn exec_stop<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32 {
     let imm = memory.read_word(cpu.pc);
     cpu.pc = cpu.pc.wrapping_add(2);
     cpu.set_sr(imm);
-    cpu.halted = true; // STOP stops the processor until interrupt/reset.
-                       // In emulator, we might just set a flag.
-                       // For now, halted = true is close, but interrupts should wake it.
-                       // We'll leave it as halted.
+    // STOP stops the processor until an interrupt or reset
+    cpu.halt(crate::cpu::HaltReason::Stopped);
     4
 }
 
@@ -223,8 +221,9 @@ pub fn exec_rtr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32 {
     let ccr = cpu.pop_word(memory);
     let new_pc = cpu.pop_long(memory);
 
-    // Only restore lower 5 bits (CCR portion)
-    cpu.sr = (cpu.sr & 0xFF00) | (ccr & 0x00FF);
+    // Only the CCR is restored. The 68000 CCR is just XNZVC, so bits 5-7
+    // of the stacked byte are dropped and the system byte is untouched.
+    cpu.sr = (cpu.sr & 0xFF00) | (ccr & 0x001F);
     cpu.pc = new_pc;
 
     20
@@ -255,6 +254,9 @@ pub fn exec_move_from_sr<M: MemoryInterface>(
     memory: &mut M,
 ) -> u32 {
     // On 68000, this is not privileged. On 68010+, it is.
+    if cpu.model == CpuModel::M68010 && (cpu.sr & flags::SUPERVISOR) == 0 {
+        return cpu.process_exception(8, memory);
+    }
     let mut cycles = 6u32;
     let (dst_ea, dst_cycles) =
         calculate_ea(dst, Size::Word, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
@@ -264,6 +266,24 @@ pub fn exec_move_from_sr<M: MemoryInterface>(
     cycles
 }
 
+pub fn exec_move_from_ccr<M: MemoryInterface>(
+    cpu: &mut Cpu,
+    dst: AddressingMode,
+    memory: &mut M,
+) -> u32 {
+    // Added by the 68010; a 68000 takes the illegal instruction exception
+    if cpu.model == CpuModel::M68000 {
+        return cpu.process_exception(4, memory);
+    }
+    let mut cycles = 6u32;
+    let (dst_ea, dst_cycles) =
+        calculate_ea(dst, Size::Word, &mut cpu.d, &mut cpu.a, &mut cpu.pc, memory);
+    cycles += dst_cycles;
+
+    cpu.cpu_write_ea(dst_ea, Size::Word, (cpu.sr & 0x00FF) as u32, memory);
+    cycles
+}
+
 pub fn exec_move_to_ccr<M: MemoryInterface>(
     cpu: &mut Cpu,
     src: AddressingMode,
@@ -275,14 +295,17 @@ pub fn exec_move_to_ccr<M: MemoryInterface>(
     cycles += src_cycles;
 
     let val = cpu.cpu_read_ea(src_ea, Size::Word, memory) as u16;
-    cpu.sr = (cpu.sr & 0xFF00) | (val & 0x00FF);
+    cpu.sr = (cpu.sr & 0xFF00) | (val & 0x001F);
     cycles
 }
 
+// All six immediate-to-CCR/SR forms take 20 cycles. The SR forms go through
+// `set_sr`, so changing the S bit swaps A7 between the two stack pointers.
+
 pub fn exec_andi_to_ccr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32 {
     let imm = memory.read_word(cpu.pc) & 0x00FF;
     cpu.pc = cpu.pc.wrapping_add(2);
-    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr & imm) & 0x00FF);
+    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr & imm) & 0x001F);
     20
 }
 
@@ -299,7 +322,7 @@ pub fn exec_andi_to_sr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32
 pub fn exec_ori_to_ccr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32 {
     let imm = memory.read_word(cpu.pc) & 0x00FF;
     cpu.pc = cpu.pc.wrapping_add(2);
-    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr | imm) & 0x00FF);
+    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr | imm) & 0x001F);
     20
 }
 
@@ -316,7 +339,7 @@ pub fn exec_ori_to_sr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32
 pub fn exec_eori_to_ccr<M: MemoryInterface>(cpu: &mut Cpu, memory: &mut M) -> u32 {
     let imm = memory.read_word(cpu.pc) & 0x00FF;
     cpu.pc = cpu.pc.wrapping_add(2);
-    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr ^ imm) & 0x00FF);
+    cpu.sr = (cpu.sr & 0xFF00) | ((cpu.sr ^ imm) & 0x001F);
     20
 }
 
@@ -662,7 +685,7 @@ mod tests {
         let cycles = exec_stop(&mut cpu, &mut memory);
 
         assert_eq!(cycles, 4);
-        assert!(cpu.halted);
+        assert_eq!(cpu.halt_reason, Some(crate::cpu::HaltReason::Stopped));
         assert_eq!(cpu.pc, 0x1002);
         assert_eq!(cpu.sr & flags::SUPERVISOR, flags::SUPERVISOR);
         assert_eq!(cpu.sr & flags::INTERRUPT_MASK, flags::INTERRUPT_MASK);
@@ -1,4 +1,5 @@
-use crate::cpu::{flags, Cpu};
+use crate::cpu::instructions::{Instruction, SystemInstruction};
+use crate::cpu::{decode, flags, Cpu};
 use crate::memory::{Memory, MemoryInterface};
 
 pub fn create_test_cpu() -> (Cpu, Memory) {
@@ -25,3 +26,55 @@ pub fn write_op(memory: &mut Memory, opcodes: &[u16]) {
         addr += 2;
     }
 }
+
+/// Opcode families by the top nibble of the opcode word
+pub const OPCODE_FAMILIES: [&str; 16] = [
+    "bit/movep/immediate",
+    "move.b",
+    "move.l",
+    "move.w",
+    "miscellaneous",
+    "addq/subq/scc/dbcc",
+    "bcc/bsr/bra",
+    "moveq",
+    "or/div/sbcd",
+    "sub/subx",
+    "line-a",
+    "cmp/eor",
+    "and/mul/abcd/exg",
+    "add/addx",
+    "shift/rotate",
+    "line-f",
+];
+
+/// How the opcodes of one family decode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FamilyCoverage {
+    /// Decoded to a real instruction
+    pub implemented: u32,
+    /// Decoded to `Unimplemented`
+    pub unimplemented: u32,
+    /// Decoded to `Illegal`, `LineA` or `LineF`
+    pub illegal: u32,
+}
+
+/// Run all 65536 opcodes through the decoder, grouped by family
+pub fn opcode_coverage() -> [FamilyCoverage; 16] {
+    let mut families = [FamilyCoverage::default(); 16];
+    for opcode in 0..=u16::MAX {
+        let family = &mut families[(opcode >> 12) as usize];
+        match decode(opcode) {
+            // MOVES only runs on the 68010, where it isn't emulated yet
+            Instruction::System(
+                SystemInstruction::Unimplemented { .. } | SystemInstruction::Moves { .. },
+            ) => family.unimplemented += 1,
+            Instruction::System(
+                SystemInstruction::Illegal
+                | SystemInstruction::LineA { .. }
+                | SystemInstruction::LineF { .. },
+            ) => family.illegal += 1,
+            _ => family.implemented += 1,
+        }
+    }
+    families
+}
@@ -56,3 +56,122 @@ fn test_smc_overwrite_instruction() {
     cpu.step_instruction(&mut memory);
     assert_eq!(cpu.d[0], 1);
 }
+
+#[test]
+fn test_ram_decode_cache_serves_loops_and_drops_rewritten_opcodes() {
+    use crate::cpu::Cpu;
+    use crate::memory::bus::Bus;
+
+    // 0xFF1000: MOVEQ #9,D1 ; ADDQ.W #1,D0 ; DBRA D1,*-2 ; NOP
+    // 0xFF100A: MOVE.W #$5440,($FF1002).L, patching in ADDQ.W #2,D0
+    let program: [u16; 9] = [
+        0x7209, 0x5240, 0x51C9, 0xFFFC, 0x4E71, 0x33FC, 0x5440, 0x00FF, 0x1002,
+    ];
+    let run_loop = |cached: bool| {
+        let mut bus = Bus::new();
+        for (i, &word) in program.iter().enumerate() {
+            bus.write_word(0xFF1000 + i as u32 * 2, word);
+        }
+        let mut cpu = Cpu::new(&mut bus);
+        cpu.set_ram_decode_cache(cached);
+        cpu.pc = 0xFF1000;
+        // MOVEQ, ten ADDQ/DBRA pairs, NOP
+        for _ in 0..22 {
+            cpu.step_instruction(&mut bus);
+        }
+        assert_eq!((cpu.d[0], cpu.pc), (10, 0xFF100A));
+        (cpu, bus)
+    };
+
+    let (uncached, _) = run_loop(false);
+    assert_eq!(uncached.decode_count, 22);
+    let (mut cpu, mut bus) = run_loop(true);
+    assert_eq!(cpu.decode_count, 4, "each opcode decoded once");
+
+    // Patch the ADDQ, then run it again from the cache's point of view
+    cpu.step_instruction(&mut bus);
+    let decodes = cpu.decode_count;
+    cpu.pc = 0xFF1002;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.d[0], 12, "the patched ADDQ #2 ran");
+    assert_eq!(cpu.decode_count, decodes + 1, "re-decoded after the write");
+    cpu.pc = 0xFF1002;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.d[0], 14);
+    assert_eq!(cpu.decode_count, decodes + 1, "and cached again");
+}
+
+#[test]
+fn test_ram_decode_cache_drops_opcodes_overwritten_by_stack_pushes() {
+    use crate::cpu::Cpu;
+    use crate::memory::bus::Bus;
+
+    // 0x200: JSR ($FF1000).L ; 0x206: LINK A6,#0
+    let mut rom = vec![0u8; 0x400];
+    rom[0x200..0x20A]
+        .copy_from_slice(&[0x4E, 0xB9, 0x00, 0xFF, 0x10, 0x00, 0x4E, 0x56, 0x00, 0x00]);
+    let mut bus = Bus::new();
+    bus.load_rom(&rom);
+    let mut cpu = Cpu::new(&mut bus);
+    cpu.set_ram_decode_cache(true);
+
+    // Cache MOVEQ #1,D0 at 0xFF1000
+    let run_ram_opcode = |cpu: &mut Cpu, bus: &mut Bus| {
+        cpu.d[0] = 0;
+        cpu.pc = 0xFF1000;
+        cpu.step_instruction(bus);
+        cpu.d[0]
+    };
+    bus.write_word(0xFF1000, 0x7001);
+    assert_eq!(run_ram_opcode(&mut cpu, &mut bus), 1);
+
+    // The JSR's return address 0x00000206 lands on the cached opcode,
+    // leaving ORI.B #6,D0
+    cpu.a[7] = 0xFF1004;
+    cpu.pc = 0x200;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.pc, 0xFF1000);
+    assert_eq!(run_ram_opcode(&mut cpu, &mut bus), 6, "JSR push re-decoded");
+
+    // LINK pushes A6 the same way, leaving MOVEQ #2,D0
+    // (a bus write behind the CPU's back needs an explicit invalidate)
+    bus.write_word(0xFF1000, 0x7001);
+    cpu.invalidate_cache();
+    assert_eq!(run_ram_opcode(&mut cpu, &mut bus), 1);
+    cpu.a[6] = 0x7002_4E71;
+    cpu.a[7] = 0xFF1004;
+    cpu.pc = 0x206;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.a[7], 0xFF1000);
+    assert_eq!(
+        run_ram_opcode(&mut cpu, &mut bus),
+        2,
+        "LINK push re-decoded"
+    );
+}
+
+#[test]
+fn test_ram_decode_cache_drops_opcodes_written_through_absolute_short() {
+    use crate::cpu::Cpu;
+    use crate::memory::bus::Bus;
+
+    let mut bus = Bus::new();
+    // 0xFFF000: MOVEQ #1,D0
+    // 0xFF2000: MOVE.W #$7002,($F000).W, which addresses 0xFFFFF000
+    bus.write_word(0xFFF000, 0x7001);
+    for (i, word) in [0x31FC, 0x7002, 0xF000].into_iter().enumerate() {
+        bus.write_word(0xFF2000 + i as u32 * 2, word);
+    }
+    let mut cpu = Cpu::new(&mut bus);
+    cpu.set_ram_decode_cache(true);
+    cpu.pc = 0xFFF000;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.d[0], 1);
+
+    cpu.pc = 0xFF2000;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(bus.read_word(0xFFF000), 0x7002);
+    cpu.pc = 0xFFF000;
+    cpu.step_instruction(&mut bus);
+    assert_eq!(cpu.d[0], 2, "the patched MOVEQ ran");
+}
@@ -4,8 +4,8 @@
 
 #![cfg(test)]
 
-use crate::cpu::flags;
 use crate::cpu::test_utils::{create_cpu, write_op};
+use crate::cpu::{flags, InterruptController, InterruptSource};
 use crate::memory::MemoryInterface;
 
 #[test]
@@ -97,7 +97,7 @@ fn test_nmi_level_7() {
 fn test_interrupt_clears_halted() {
     let (mut cpu, mut memory) = create_cpu();
 
-    cpu.halted = true;
+    cpu.halt(crate::cpu::HaltReason::Stopped);
     cpu.sr &= !flags::INTERRUPT_MASK;
 
     cpu.request_interrupt(1);
@@ -108,7 +108,7 @@ fn test_interrupt_clears_halted() {
     let cycles = cpu.step_instruction(&mut memory);
 
     assert_eq!(cycles, 44);
-    assert!(!cpu.halted);
+    assert!(!cpu.halted());
 }
 
 #[test]
@@ -154,3 +154,43 @@ fn test_multiple_interrupts_priority() {
     assert_eq!(cycles, 44);
     assert_eq!(cpu.pc, 0x6000);
 }
+
+#[test]
+fn test_controller_presents_vint_before_hint() {
+    let mut ic = InterruptController::new();
+    ic.set_line(InterruptSource::HBlank, true);
+    ic.set_line(InterruptSource::VBlank, true);
+    assert_eq!(ic.highest(), 6);
+
+    assert_eq!(ic.acknowledge(6), Some(InterruptSource::VBlank));
+    assert_eq!(ic.highest(), 4);
+    assert!(!ic.is_pending(6));
+
+    assert_eq!(ic.acknowledge(4), Some(InterruptSource::HBlank));
+    assert_eq!(ic.highest(), 0);
+}
+
+#[test]
+fn test_cpu_services_vint_then_hint() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.sr &= !flags::INTERRUPT_MASK;
+    memory.write_long(0x78, 0x5000); // Level 6
+    memory.write_long(0x70, 0x6000); // Level 4
+    memory.write_word(0x5000, 0x4E73); // RTE
+    write_op(&mut memory, &[0x4E71]);
+
+    cpu.set_interrupt_line(InterruptSource::HBlank, true);
+    cpu.set_interrupt_line(InterruptSource::VBlank, true);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x5000);
+    assert_eq!(cpu.last_interrupt_level, 6);
+    // The serviced source is cleared; the lower one is still waiting
+    assert_eq!(cpu.pending_interrupt, 4);
+
+    // RTE restores mask 0, then HINT is taken
+    cpu.step_instruction(&mut memory);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x6000);
+    assert_eq!(cpu.last_interrupt_level, 4);
+    assert_eq!(cpu.pending_interrupt, 0);
+}
@@ -5,7 +5,9 @@
 
 #![cfg(test)]
 
+use crate::cpu::decode;
 use crate::cpu::flags;
+use crate::cpu::instructions::{ArithmeticInstruction, Instruction, SystemInstruction};
 use crate::cpu::test_utils::{create_cpu, write_op};
 use crate::memory::MemoryInterface;
 
@@ -89,6 +91,40 @@ fn test_add_zero_byte() {
     assert!(!cpu.get_flag(flags::NEGATIVE));
 }
 
+#[test]
+fn test_add_b_immediate_uses_low_byte_of_word() {
+    let (mut cpu, mut memory) = create_cpu();
+    // ADD.B #$7F,D0 - the high byte of the extension word is ignored
+    write_op(&mut memory, &[0xD03C, 0xAA7F, 0x4E71]);
+    cpu.d[0] = 0x1234_5601;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[0], 0x1234_5680);
+    assert!(cpu.get_flag(flags::OVERFLOW));
+    assert!(cpu.get_flag(flags::NEGATIVE));
+    // One extension word, not one byte
+    assert_eq!(cpu.pc, 0x1004);
+}
+
+#[test]
+fn test_byte_immediate_forms_advance_one_word() {
+    let (mut cpu, mut memory) = create_cpu();
+    // ANDI.B #$0F,D1; ORI.B #$30,D1; CMP.B #$3A,D1
+    write_op(
+        &mut memory,
+        &[0x0201, 0xFF0F, 0x0001, 0xFF30, 0xB23C, 0xFF3A],
+    );
+    cpu.d[1] = 0xFFFF_FFFA;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[1], 0xFFFF_FF0A);
+    assert_eq!(cpu.pc, 0x1004);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[1], 0xFFFF_FF3A);
+    assert_eq!(cpu.pc, 0x1008);
+    cpu.step_instruction(&mut memory);
+    assert!(cpu.get_flag(flags::ZERO));
+    assert_eq!(cpu.pc, 0x100C);
+}
+
 // ============================================================================
 // ADDI Tests
 // ============================================================================
@@ -182,6 +218,45 @@ fn test_adda_sign_extend() {
     assert_eq!(cpu.a[0], 0x0FFF); // 0x1000 + (-1) = 0x0FFF
 }
 
+#[test]
+fn test_adda_w_immediate_minus_one_borrows_across_word() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0xD0FC, 0xFFFF]); // ADDA.W #-1, A0
+    cpu.a[0] = 0x0001_0000;
+    cpu.sr |= 0x001F;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.a[0], 0x0000_FFFF);
+    assert_eq!(cpu.sr & 0x001F, 0x001F, "ADDA leaves the CCR alone");
+}
+
+#[test]
+fn test_suba_w_immediate_borrows_across_word() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x90FC, 0x0001]); // SUBA.W #1, A0
+    cpu.a[0] = 0x0001_0000;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.a[0], 0x0000_FFFF);
+    assert_eq!(cpu.sr & 0x001F, 0, "SUBA leaves the CCR alone");
+}
+
+#[test]
+fn test_add_sub_byte_from_address_register_is_illegal() {
+    // ADD.B A0,D0 / SUB.B A0,D0: there is no byte ADDA/SUBA
+    for opcode in [0xD008, 0x9008] {
+        assert_eq!(
+            decode(opcode),
+            Instruction::System(SystemInstruction::Illegal),
+            "{:04X}",
+            opcode
+        );
+    }
+    // Word reads of An are fine
+    assert!(matches!(
+        decode(0xD048),
+        Instruction::Arithmetic(ArithmeticInstruction::Add { .. })
+    ));
+}
+
 // ============================================================================
 // ADDX Tests
 // ============================================================================
@@ -848,3 +923,131 @@ fn test_subx_memory() {
     assert_eq!(cpu.a[0], 0x1FFF);
     assert_eq!(cpu.a[1], 0x2FFF);
 }
+
+// ============================================================================
+// X Flag Preservation Tests
+// ============================================================================
+
+/// Compare, test, move and logical ops update N/Z/V/C but never X, so a
+/// carry set up for a following ADDX/SUBX chain has to survive them
+const X_PRESERVING_OPS: [(u16, &str); 9] = [
+    (0xB200, "CMP.B D0,D1"),
+    (0xC240, "AND.W D0,D1"),
+    (0x8200, "OR.B D0,D1"),
+    (0xB141, "EOR.W D0,D1"),
+    (0x4A81, "TST.L D1"),
+    (0x3200, "MOVE.W D0,D1"),
+    (0x4241, "CLR.W D1"),
+    (0x4601, "NOT.B D1"),
+    (0xC2C0, "MULU.W D0,D1"),
+];
+
+#[test]
+fn test_cmp_and_logical_ops_leave_x_set() {
+    for (opcode, name) in X_PRESERVING_OPS {
+        let (mut cpu, mut memory) = create_cpu();
+        write_op(&mut memory, &[opcode]);
+        cpu.d[0] = 0x0000_00F0;
+        cpu.d[1] = 0x0000_000F;
+        cpu.set_flag(flags::EXTEND, true);
+        cpu.step_instruction(&mut memory);
+        assert!(cpu.get_flag(flags::EXTEND), "{} cleared X", name);
+    }
+}
+
+#[test]
+fn test_cmp_and_logical_ops_leave_x_clear() {
+    for (opcode, name) in X_PRESERVING_OPS {
+        let (mut cpu, mut memory) = create_cpu();
+        write_op(&mut memory, &[opcode]);
+        // D1 < D0 so CMP borrows and sets C
+        cpu.d[0] = 0x0000_00F0;
+        cpu.d[1] = 0x0000_000F;
+        cpu.set_flag(flags::EXTEND, false);
+        cpu.step_instruction(&mut memory);
+        assert!(!cpu.get_flag(flags::EXTEND), "{} set X", name);
+    }
+}
+
+#[test]
+fn test_cmp_borrow_then_addx_uses_earlier_x() {
+    let (mut cpu, mut memory) = create_cpu();
+    // ADD.B D0,D1 (carry out) ; CMP.B D2,D3 (no borrow) ; ADDX.B D4,D5
+    write_op(&mut memory, &[0xD200, 0xB602, 0xDB04]);
+    cpu.d[0] = 0x80;
+    cpu.d[1] = 0x80;
+    cpu.d[2] = 0x01;
+    cpu.d[3] = 0x02;
+    cpu.d[4] = 0x10;
+    cpu.d[5] = 0x20;
+    cpu.step_instruction(&mut memory);
+    assert!(cpu.get_flag(flags::EXTEND));
+    cpu.step_instruction(&mut memory);
+    assert!(!cpu.get_flag(flags::CARRY), "CMP cleared C");
+    assert!(cpu.get_flag(flags::EXTEND), "but not X");
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[5] & 0xFF, 0x31, "ADDX added the carry from the ADD");
+}
+
+// ============================================================================
+// Single-operand Cycle Tests
+// ============================================================================
+
+#[test]
+fn test_clr_neg_not_tst_cycles() {
+    // (opcode words, mnemonic, cycles from the 68000 user's manual)
+    let cases: &[(&[u16], &str, u32)] = &[
+        (&[0x4200], "CLR.B D0", 4),
+        (&[0x4280], "CLR.L D0", 6),
+        (&[0x4250], "CLR.W (A0)", 12),
+        (&[0x4290], "CLR.L (A0)", 20),
+        (&[0x42A0], "CLR.L -(A0)", 22),
+        (&[0x4268, 0x0010], "CLR.W $10(A0)", 16),
+        (&[0x42B9, 0x0000, 0x5000], "CLR.L ($5000).L", 28),
+        (&[0x4401], "NEG.B D1", 4),
+        (&[0x4481], "NEG.L D1", 6),
+        (&[0x4458], "NEG.W (A0)+", 12),
+        (&[0x44A0], "NEG.L -(A0)", 22),
+        (&[0x4090], "NEGX.L (A0)", 20),
+        (&[0x4642], "NOT.W D2", 4),
+        (&[0x4628, 0x0010], "NOT.B $10(A0)", 16),
+        (&[0x46B8, 0x5000], "NOT.L ($5000).W", 24),
+        (&[0x4A80], "TST.L D0", 4),
+        (&[0x4A50], "TST.W (A0)", 8),
+        (&[0x4A90], "TST.L (A0)", 12),
+        (&[0x4A30, 0x0000], "TST.B 0(A0,D0.W)", 14),
+    ];
+    for (opcode, name, expected) in cases {
+        let (mut cpu, mut memory) = create_cpu();
+        write_op(&mut memory, opcode);
+        cpu.a[0] = 0x4000;
+        cpu.d[0] = 0;
+        let cycles = cpu.step_instruction(&mut memory);
+        assert_eq!(cycles, *expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_and_or_eor_cycles() {
+    // (opcode words, mnemonic, cycles from the 68000 user's manual)
+    let cases: &[(&[u16], &str, u32)] = &[
+        (&[0xC190], "AND.L D0,(A0)", 20),
+        (&[0xB150], "EOR.W D0,(A0)", 12),
+        (&[0xC250], "AND.W (A0),D1", 8),
+        (&[0xC081], "AND.L D1,D0", 8),
+        (&[0xC0BC, 0x1234, 0x5678], "AND.L #$12345678,D0", 16),
+        (&[0x8090], "OR.L (A0),D0", 14),
+        (&[0x8118], "OR.B D0,(A0)+", 12),
+        (&[0x81A8, 0x0010], "OR.L D0,$10(A0)", 24),
+        (&[0xB380], "EOR.L D1,D0", 8),
+        (&[0xB340], "EOR.W D1,D0", 4),
+        (&[0xB1A0], "EOR.L D0,-(A0)", 22),
+    ];
+    for (opcode, name, expected) in cases {
+        let (mut cpu, mut memory) = create_cpu();
+        write_op(&mut memory, opcode);
+        cpu.a[0] = 0x4000;
+        let cycles = cpu.step_instruction(&mut memory);
+        assert_eq!(cycles, *expected, "{}", name);
+    }
+}
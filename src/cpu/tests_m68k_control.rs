@@ -5,7 +5,8 @@
 
 #![cfg(test)]
 
-use crate::cpu::Cpu;
+use crate::cpu::instructions::{Instruction, SystemInstruction};
+use crate::cpu::{decode, Cpu, CpuModel, HaltReason};
 use crate::memory::Memory;
 
 use crate::cpu::flags;
@@ -108,6 +109,32 @@ fn test_bra_word_displacement() {
     assert_eq!(cpu.pc, 0x1102); // 0x1000 + 2 + 256
 }
 
+#[test]
+fn test_short_branches_take_ff_as_minus_one() {
+    // 0xFF is the 68020's 32-bit form; the 68000 has no such form, so the
+    // words that follow are never read as a displacement
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x0C, 0x4000);
+    write_op(&mut memory, &[0x60FF, 0x0000, 0x0100][..]); // BRA.S -1
+    assert_eq!(cpu.step_instruction(&mut memory), 10);
+    assert_eq!(cpu.pc, 0x1001, "0x1002 - 1");
+    // The odd target faults on the next fetch
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x4000);
+
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x61FF, 0x0000, 0x0100][..]); // BSR.S -1
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1001);
+    assert_eq!(memory.read_long(cpu.a[7]), 0x1002, "returns past one word");
+
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x67FF, 0x0000, 0x0100][..]); // BEQ.S -1
+    cpu.set_flag(flags::ZERO, false);
+    assert_eq!(cpu.step_instruction(&mut memory), 8);
+    assert_eq!(cpu.pc, 0x1002, "not taken: no extension words skipped");
+}
+
 // ============================================================================
 // Bcc Tests - All 16 Conditions
 // ============================================================================
@@ -287,6 +314,97 @@ fn test_dbeq_condition_true() {
     assert_eq!(cpu.pc, 0x1004); // Falls through
 }
 
+#[test]
+fn test_dbne_exits_when_condition_turns_true() {
+    let (mut cpu, mut memory) = create_cpu();
+    // loop: MOVE.B (A0)+,D1 ; DBNE D0,loop
+    write_op(&mut memory, &[0x1218, 0x56C8, 0xFFFC][..]);
+    for (i, byte) in [0, 0, 0, 7, 0].into_iter().enumerate() {
+        memory.write_byte(0x2000 + i as u32, byte);
+    }
+    cpu.a[0] = 0x2000;
+    cpu.d[0] = 10;
+
+    let mut cycles = 0;
+    while cpu.pc != 0x1006 {
+        cpu.step_instruction(&mut memory);
+        cycles = cpu.step_instruction(&mut memory);
+    }
+    // Three zero bytes each cost a decrement; the fourth ends the loop
+    assert_eq!(cpu.d[0], 7, "no decrement on the exiting pass");
+    assert_eq!(cpu.a[0], 0x2004);
+    assert_eq!(cpu.d[1] & 0xFF, 7);
+    assert_eq!(cycles, 12);
+}
+
+#[test]
+fn test_dbt_never_loops() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x50C8, 0xFFFE][..]); // DBT D0,*
+    cpu.d[0] = 5;
+    let cycles = cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1004);
+    assert_eq!(cpu.d[0], 5);
+    assert_eq!(cycles, 12);
+}
+
+#[test]
+fn test_dbcc_all_conditions() {
+    // Reference truth table over N, Z, V, C for each condition code
+    fn holds(cond: u16, ccr: u16) -> bool {
+        let (n, z, v, c) = (ccr & 8 != 0, ccr & 4 != 0, ccr & 2 != 0, ccr & 1 != 0);
+        match cond {
+            0x0 => true,
+            0x1 => false,
+            0x2 => !c && !z,
+            0x3 => c || z,
+            0x4 => !c,
+            0x5 => c,
+            0x6 => !z,
+            0x7 => z,
+            0x8 => !v,
+            0x9 => v,
+            0xA => !n,
+            0xB => n,
+            0xC => n == v,
+            0xD => n != v,
+            0xE => !z && n == v,
+            _ => z || n != v,
+        }
+    }
+
+    let (mut cpu, mut memory) = create_cpu();
+    // DBcc D0,*+$12 for each condition, 4 bytes apart
+    let program: Vec<u16> = (0..16)
+        .flat_map(|cond| [0x50C8 | cond << 8, 0x0010])
+        .collect();
+    write_op(&mut memory, &program);
+
+    for cond in 0..16u16 {
+        let at = 0x1000 + cond as u32 * 4;
+        for ccr in 0..16u16 {
+            for counter in [5u32, 0] {
+                cpu.pc = at;
+                cpu.sr = (cpu.sr & 0xFF00) | ccr;
+                cpu.d[0] = 0xABCD_0000 | counter;
+                let cycles = cpu.step_instruction(&mut memory);
+                let label = format!("cond {cond:X} ccr {ccr:04b} counter {counter}");
+
+                if holds(cond, ccr) {
+                    assert_eq!((cpu.pc, cycles), (at + 4, 12), "{label}");
+                    assert_eq!(cpu.d[0], 0xABCD_0000 | counter, "{label}");
+                } else if counter == 0 {
+                    assert_eq!((cpu.pc, cycles), (at + 4, 14), "{label}");
+                    assert_eq!(cpu.d[0], 0xABCD_FFFF, "{label}");
+                } else {
+                    assert_eq!((cpu.pc, cycles), (at + 2 + 0x10, 10), "{label}");
+                    assert_eq!(cpu.d[0], 0xABCD_0000 | (counter - 1), "{label}");
+                }
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Scc Tests
 // ============================================================================
@@ -586,6 +704,45 @@ fn test_jmp_indirect() {
     assert_eq!(cpu.pc, 0x3000);
 }
 
+#[test]
+fn test_jmp_pc_index_target() {
+    let (mut cpu, mut memory) = create_cpu();
+    // JMP 6(PC,D1.W): base is the extension word address
+    write_op(&mut memory, &[0x4EFB, 0x1006][..]);
+    cpu.d[1] = 0xFFFF_0010;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1002 + 6 + 0x10);
+}
+
+#[test]
+fn test_jmp_invalid_modes_decode_illegal() {
+    // JMP D0, JMP A0, JMP (A0)+, JMP -(A0), JMP #imm
+    for opcode in [0x4EC0, 0x4EC8, 0x4ED8, 0x4EE0, 0x4EFC] {
+        assert!(
+            matches!(
+                decode(opcode),
+                Instruction::System(SystemInstruction::Illegal)
+            ),
+            "{:04X}",
+            opcode
+        );
+    }
+    // JSR D0
+    assert!(matches!(
+        decode(0x4E80),
+        Instruction::System(SystemInstruction::Illegal)
+    ));
+
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x10, 0x4000);
+    write_op(&mut memory, &[0x4EC0][..]);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(
+        cpu.pc, 0x4000,
+        "JMP D0 takes the illegal instruction vector"
+    );
+}
+
 // ============================================================================
 // JSR/RTS Tests
 // ============================================================================
@@ -607,6 +764,21 @@ fn test_jsr_rts_roundtrip() {
     assert_eq!(cpu.a[7], 0x8000);
 }
 
+#[test]
+fn test_jsr_pc_displacement() {
+    let (mut cpu, mut memory) = create_cpu();
+    // JSR $0100(PC)
+    write_op(&mut memory, &[0x4EBA, 0x0100][..]);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1002 + 0x100);
+    assert_eq!(cpu.a[7], 0x7FFC);
+    assert_eq!(
+        memory.read_long(0x7FFC),
+        0x1004,
+        "Return past the extension"
+    );
+}
+
 #[test]
 fn test_bsr_rts() {
     let (mut cpu, mut memory) = create_cpu();
@@ -765,7 +937,7 @@ fn test_stop_privilege_violation() {
     // Exception should set supervisor bit
     assert!(cpu.sr & flags::SUPERVISOR != 0);
     // Should NOT be halted
-    assert!(!cpu.halted);
+    assert!(!cpu.halted());
 }
 
 #[test]
@@ -778,7 +950,170 @@ fn test_stop_supervisor_behavior() {
     cpu.step_instruction(&mut memory);
 
     assert_eq!(cpu.sr, 0x2200);
-    assert!(cpu.halted);
+    assert!(cpu.halted());
+}
+
+// ============================================================================
+// Halt Reason Tests
+// ============================================================================
+
+#[test]
+fn test_halt_reason_stop_wakes_on_interrupt() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x4E72, 0x2000][..]);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.halt_reason, Some(HaltReason::Stopped));
+
+    memory.write_long(0x64, 0x4000);
+    cpu.request_interrupt(1);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.halt_reason, None);
+    assert_eq!(cpu.pc, 0x4000);
+}
+
+#[test]
+fn test_halt_reason_double_fault() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x0C, 0x4000);
+
+    // Address error while stacking the exception frame
+    cpu.a[7] = 0x7FFF;
+    cpu.process_exception(3, &mut memory);
+    assert_eq!(cpu.halt_reason, Some(HaltReason::DoubleFault));
+
+    // Interrupts don't recover a double fault, only reset does
+    cpu.request_interrupt(7);
+    assert_eq!(cpu.step_instruction(&mut memory), 4);
+    assert_eq!(cpu.halt_reason, Some(HaltReason::DoubleFault));
+    cpu.reset(&mut memory);
+    assert!(!cpu.halted());
+}
+
+#[test]
+fn test_address_error_during_exception_processing_double_faults() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x0C, 0x4000);
+    // MOVE.W (A0),D0 with an odd A0 raises an address error; the odd SSP
+    // then faults again while the frame is stacked
+    write_op(&mut memory, &[0x3010][..]);
+    cpu.a[0] = 0x2001;
+    cpu.a[7] = 0x7FFF;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.halt_reason, Some(HaltReason::DoubleFault));
+}
+
+#[test]
+fn test_group0_abort_suppresses_later_exceptions() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x0C, 0x4000);
+    cpu.process_exception(3, &mut memory);
+    let sp = cpu.a[7];
+    // The aborted instruction can't raise anything further this step
+    cpu.process_exception(8, &mut memory);
+    assert_eq!(cpu.a[7], sp);
+    assert_eq!(cpu.pc, 0x4000);
+    assert!(!cpu.halted());
+}
+
+#[test]
+fn test_trace_taken_before_coinciding_interrupt() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x24, 0x5000); // Trace (vector 9)
+    memory.write_long(0x70, 0x6000); // Level 4 autovector
+    memory.write_word(0x5000, 0x4E71);
+    memory.write_word(0x6000, 0x4E71);
+
+    // MOVE #$A000,SR: traced, and lowers the mask under a pending level 4
+    write_op(&mut memory, &[0x46FC, 0xA000][..]);
+    cpu.sr = 0xA700;
+    cpu.request_interrupt(4);
+    let sp = cpu.a[7];
+
+    // End of the instruction: trace is processed first
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x5000);
+    assert_eq!(cpu.a[7], sp - 6);
+    assert_eq!(memory.read_long(sp - 4), 0x1004);
+    assert_ne!(memory.read_word(sp - 6) & flags::TRACE, 0);
+    assert!(!cpu.get_flag(flags::TRACE));
+
+    // ...then the interrupt, before the trace handler runs
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x6000);
+    assert_eq!(cpu.a[7], sp - 12);
+    assert_eq!(memory.read_long(sp - 10), 0x5000);
+    assert_eq!(cpu.sr & flags::INTERRUPT_MASK, 0x0400);
+}
+
+#[test]
+fn test_trace_follows_trap() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x24, 0x5000); // Trace
+    memory.write_long(0x80, 0x6000); // TRAP #0
+    write_op(&mut memory, &[0x4E40][..]);
+    cpu.sr = 0xA700;
+    let sp = cpu.a[7];
+
+    cpu.step_instruction(&mut memory);
+    // Group 2 trap stacked first, then the trace frame on top of it
+    assert_eq!(cpu.pc, 0x5000);
+    assert_eq!(cpu.a[7], sp - 12);
+    assert_eq!(memory.read_long(sp - 10), 0x6000);
+    assert!(!cpu.halted());
+}
+
+#[test]
+fn test_traced_stop_takes_trace_instead_of_stopping() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x24, 0x5000); // Trace
+    memory.write_word(0x5000, 0x4E73); // RTE
+
+    // STOP #$2000 (clears T), then NOP
+    write_op(&mut memory, &[0x4E72, 0x2000, 0x4E71][..]);
+    cpu.sr = 0xA700;
+    let sp = cpu.a[7];
+
+    cpu.step_instruction(&mut memory);
+    assert!(!cpu.halted(), "A traced STOP doesn't stop");
+    assert_eq!(cpu.pc, 0x5000);
+    assert_eq!(cpu.a[7], sp - 6);
+    // The frame holds the SR that STOP loaded and the address after STOP
+    assert_eq!(memory.read_word(sp - 6), 0x2000);
+    assert_eq!(memory.read_long(sp - 4), 0x1004);
+
+    // RTE restores STOP's SR, so the NOP runs untraced
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1004);
+    assert_eq!(cpu.sr, 0x2000);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1006);
+    assert_eq!(cpu.a[7], sp);
+}
+
+#[test]
+fn test_trace_not_taken_for_illegal_instruction() {
+    let (mut cpu, mut memory) = create_cpu();
+    memory.write_long(0x10, 0x4000); // Illegal
+    memory.write_long(0x24, 0x5000); // Trace
+    write_op(&mut memory, &[0x4AFC][..]);
+    cpu.sr = 0xA700;
+
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x4000);
+}
+
+#[test]
+fn test_halt_reason_external_halt() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x4E71][..]); // NOP
+    cpu.halt(HaltReason::ExternalHalt);
+    assert!(cpu.halted());
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1000);
+
+    cpu.resume();
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1002);
 }
 
 // ============================================================================
@@ -975,6 +1310,20 @@ fn test_rtr_preserves_system_byte() {
     assert_eq!(cpu.a[7], initial_sp + 6);
 }
 
+#[test]
+fn test_rtr_ignores_unimplemented_ccr_bits() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &[0x4E77][..]);
+
+    cpu.sr = 0x2700;
+    push_rtr_frame(&mut cpu, &mut memory, 0x2000, 0xFFF5);
+
+    cpu.step_instruction(&mut memory);
+
+    assert_eq!(cpu.pc, 0x2000);
+    assert_eq!(cpu.sr, 0x2715, "Bits 5-7 don't exist in the CCR");
+}
+
 // ============================================================================
 // MOVE USP Tests
 // ============================================================================
@@ -1018,3 +1367,180 @@ fn test_move_usp_user_mode_traps() {
     assert_eq!(cpu.pc, 0x4000);
     assert!(cpu.sr & flags::SUPERVISOR != 0);
 }
+
+// ============================================================================
+// MOVE from SR / CCR Model Tests
+// ============================================================================
+
+fn user_mode_cpu(model: CpuModel, opcode: u16) -> (Cpu, Memory) {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.model = model;
+    write_op(&mut memory, &[opcode][..]);
+    memory.write_long(0x10, 0x4000); // Illegal instruction
+    memory.write_long(0x20, 0x5000); // Privilege violation
+    cpu.ssp = 0x8000;
+    cpu.usp = 0xA000;
+    cpu.a[7] = cpu.usp;
+    cpu.sr = 0x0015; // User mode, X/Z/C set
+    (cpu, memory)
+}
+
+#[test]
+fn test_move_from_sr_unprivileged_on_68000() {
+    // MOVE SR,D0
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68000, 0x40C0);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1002);
+    assert_eq!(cpu.d[0] & 0xFFFF, 0x0015);
+    assert!(cpu.sr & flags::SUPERVISOR == 0);
+}
+
+#[test]
+fn test_move_from_sr_privileged_on_68010() {
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68010, 0x40C0);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x5000);
+    assert_eq!(cpu.d[0], 0);
+    assert!(cpu.sr & flags::SUPERVISOR != 0);
+}
+
+#[test]
+fn test_move_from_ccr_depends_on_model() {
+    // MOVE CCR,D0 is illegal on a 68000...
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68000, 0x42C0);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x4000);
+
+    // ...and an unprivileged CCR read on a 68010
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68010, 0x42C0);
+    cpu.d[0] = 0xFFFF_FFFF;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1002);
+    assert_eq!(cpu.d[0], 0xFFFF_0015);
+}
+
+#[test]
+fn test_move_from_ccr_rejects_non_data_alterable_destinations() {
+    // MOVE CCR,A0 isn't an encoding even on a 68010
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68010, 0x42C8);
+    cpu.a[0] = 0x1234_5678;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x4000);
+    assert_eq!(cpu.a[0], 0x1234_5678);
+
+    // Nor are PC-relative or immediate destinations, for CCR or SR
+    for opcode in [0x42FA, 0x42FC, 0x40C8, 0x40FB] {
+        assert!(
+            !matches!(
+                decode(opcode),
+                Instruction::System(
+                    SystemInstruction::MoveFromCcr { .. } | SystemInstruction::MoveFromSr { .. }
+                )
+            ),
+            "{:04X}",
+            opcode
+        );
+    }
+}
+
+#[test]
+fn test_moves_depends_on_model() {
+    // MOVES.B (A0),D0 / MOVES.W D1,(A1) / MOVES.L (A2)+,A3
+    for opcode in [0x0E10, 0x0E51, 0x0E9A] {
+        assert!(
+            matches!(
+                decode(opcode),
+                Instruction::System(SystemInstruction::Moves { .. })
+            ),
+            "{:04X}",
+            opcode
+        );
+    }
+
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68000, 0x0E10);
+    cpu.sr |= flags::SUPERVISOR;
+    cpu.a[7] = cpu.ssp;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x4000, "vector 4, not a NOP");
+    assert!(cpu.unimplemented_report().is_empty());
+
+    // The 68010 has MOVES, which isn't emulated yet, so it is reported
+    let (mut cpu, mut memory) = user_mode_cpu(CpuModel::M68010, 0x0E10);
+    cpu.sr |= flags::SUPERVISOR;
+    cpu.a[7] = cpu.ssp;
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.unimplemented_report(), vec![(0x0E10, 1)]);
+}
+
+// ============================================================================
+// Execution Hook and Trace Tests
+// ============================================================================
+
+#[test]
+fn test_execute_hook_sees_instruction_sequence() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let (mut cpu, mut memory) = create_cpu();
+    // MOVEQ #1,D0; JSR $2000.W; NOP, with RTS at $2000
+    write_op(&mut memory, &[0x7001, 0x4EB8, 0x2000, 0x4E71][..]);
+    memory.write_word(0x2000, 0x4E75);
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = seen.clone();
+    cpu.on_execute(move |instr, pc| log.borrow_mut().push((pc, *instr)));
+    for _ in 0..4 {
+        cpu.step_instruction(&mut memory);
+    }
+
+    {
+        let seen = seen.borrow();
+        let pcs: Vec<u32> = seen.iter().map(|&(pc, _)| pc).collect();
+        assert_eq!(pcs, [0x1000, 0x1002, 0x2000, 0x1006]);
+        assert!(matches!(
+            seen[1].1,
+            Instruction::System(SystemInstruction::Jsr { .. })
+        ));
+        assert!(matches!(
+            seen[2].1,
+            Instruction::System(SystemInstruction::Rts)
+        ));
+    }
+
+    cpu.clear_execute_hook();
+    assert_eq!(Rc::strong_count(&seen), 1, "Hook is dropped");
+}
+
+#[test]
+fn test_region_trace_splits_rom_and_ram_execution() {
+    use crate::cpu::FetchRegion;
+    use crate::memory::bus::Bus;
+
+    // ROM at $200: MOVEQ #1,D0 ; JSR $FF0000.L ; NOP
+    // RAM at $FF0000: ADDQ.W #1,D0 ; ADDQ.W #1,D0 ; RTS
+    let mut rom = vec![0u8; 0x400];
+    rom[0..8].copy_from_slice(&[0x00, 0xFF, 0x80, 0x00, 0x00, 0x00, 0x02, 0x00]);
+    rom[0x200..0x20A]
+        .copy_from_slice(&[0x70, 0x01, 0x4E, 0xB9, 0x00, 0xFF, 0x00, 0x00, 0x4E, 0x71]);
+    let mut bus = Bus::new();
+    bus.load_rom(&rom);
+    for (i, word) in [0x5240, 0x5240, 0x4E75].into_iter().enumerate() {
+        bus.write_word(0xFF0000 + i as u32 * 2, word);
+    }
+    let mut cpu = Cpu::new(&mut bus);
+    assert!(cpu.region_trace_report().is_empty(), "off by default");
+
+    cpu.set_region_trace(true);
+    for _ in 0..6 {
+        cpu.step_instruction(&mut bus);
+    }
+    assert_eq!(cpu.pc, 0x20A);
+    assert_eq!(cpu.d[0], 3);
+    assert_eq!(
+        cpu.region_trace_report(),
+        vec![(FetchRegion::Rom, 3), (FetchRegion::WorkRam, 3)]
+    );
+
+    cpu.set_region_trace(false);
+    assert!(cpu.region_trace_report().is_empty());
+}
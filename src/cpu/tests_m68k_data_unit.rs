@@ -218,3 +218,90 @@ fn test_move_immediate() {
     // Note: create_cpu sets PC to 0x1000
     assert_eq!(cpu.pc, 0x1002);
 }
+
+#[test]
+fn test_move_cycles_register_to_register() {
+    let (mut cpu, mut memory) = create_cpu();
+    for size in [Size::Byte, Size::Word, Size::Long] {
+        let cycles = exec_move(
+            &mut cpu,
+            size,
+            AddressingMode::DataRegister(0),
+            AddressingMode::DataRegister(1),
+            &mut memory,
+        );
+        assert_eq!(cycles, 4, "MOVE.{:?} D0,D1", size);
+    }
+}
+
+#[test]
+fn test_move_cycles_immediate_to_memory() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.a[0] = 0x5000;
+
+    // MOVE.W #$1234,(A0)
+    memory.write_word(cpu.pc, 0x1234);
+    let cycles = exec_move(
+        &mut cpu,
+        Size::Word,
+        AddressingMode::Immediate,
+        AddressingMode::AddressIndirect(0),
+        &mut memory,
+    );
+    assert_eq!(cycles, 12);
+
+    // MOVE.L #$11223344,($00006000).L
+    let pc = cpu.pc;
+    memory.write_long(pc, 0x11223344);
+    memory.write_long(pc + 4, 0x00006000);
+    let cycles = exec_move(
+        &mut cpu,
+        Size::Long,
+        AddressingMode::Immediate,
+        AddressingMode::AbsoluteLong,
+        &mut memory,
+    );
+    assert_eq!(cycles, 28);
+    assert_eq!(memory.read_long(0x6000), 0x11223344);
+}
+
+#[test]
+fn test_move_cycles_memory_to_memory_long() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.a[0] = 0x5000;
+    cpu.a[1] = 0x6000;
+    memory.write_long(0x5000, 0xCAFEBABE);
+
+    // MOVE.L (A0)+,(A1)+
+    let cycles = exec_move(
+        &mut cpu,
+        Size::Long,
+        AddressingMode::AddressPostIncrement(0),
+        AddressingMode::AddressPostIncrement(1),
+        &mut memory,
+    );
+    assert_eq!(cycles, 20);
+    assert_eq!(memory.read_long(0x6000), 0xCAFEBABE);
+
+    // MOVE.L -(A0),-(A1): predecrement costs 2 extra on the source only
+    let cycles = exec_move(
+        &mut cpu,
+        Size::Long,
+        AddressingMode::AddressPreDecrement(0),
+        AddressingMode::AddressPreDecrement(1),
+        &mut memory,
+    );
+    assert_eq!(cycles, 22);
+
+    // MOVE.L $10(A0),(xxx).L is the costliest common form
+    memory.write_word(cpu.pc, 0x0010);
+    memory.write_long(cpu.pc + 2, 0x00007000);
+    let cycles = exec_move(
+        &mut cpu,
+        Size::Long,
+        AddressingMode::AddressDisplacement(0),
+        AddressingMode::AbsoluteLong,
+        &mut memory,
+    );
+    assert_eq!(cycles, 32);
+}
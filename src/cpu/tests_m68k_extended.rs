@@ -284,6 +284,24 @@ fn test_move_to_ccr() {
     assert_eq!(cpu.sr, 0x271F);
 }
 
+#[test]
+fn test_move_and_ori_to_ccr_drop_unimplemented_bits() {
+    let (mut cpu, mut memory) = create_test_cpu();
+    cpu.sr = 0x2700;
+
+    // MOVE #$FF, CCR ; ORI #$E0, CCR
+    memory.write_word(0x100, 0x44FC);
+    memory.write_word(0x102, 0x00FF);
+    memory.write_word(0x104, 0x003C);
+    memory.write_word(0x106, 0x00E0);
+
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.sr, 0x271F, "Bits 5-7 don't exist in the CCR");
+
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.sr, 0x271F);
+}
+
 #[test]
 fn test_andi_to_ccr() {
     let (mut cpu, mut memory) = create_test_cpu();
@@ -298,6 +316,56 @@ fn test_andi_to_ccr() {
     assert_eq!(cpu.sr & 0x00FF, 0x0010);
 }
 
+#[test]
+fn test_immediate_to_sr_and_ccr_cycles() {
+    // The 68000 manual lists 20(3/0) for all six forms, CCR included
+    for (opcode, imm, name) in [
+        (0x023C, 0x001F, "ANDI #$1F,CCR"),
+        (0x003C, 0x0001, "ORI #$01,CCR"),
+        (0x0A3C, 0x0001, "EORI #$01,CCR"),
+        (0x027C, 0xFFFF, "ANDI #$FFFF,SR"),
+        (0x007C, 0x0700, "ORI #$0700,SR"),
+        (0x0A7C, 0x0001, "EORI #$0001,SR"),
+    ] {
+        let (mut cpu, mut memory) = create_test_cpu();
+        cpu.sr = 0x2700;
+        memory.write_word(0x100, opcode);
+        memory.write_word(0x102, imm);
+
+        assert_eq!(cpu.step_instruction(&mut memory), 20, "{}", name);
+        assert_eq!(cpu.pc, 0x104, "{}", name);
+    }
+}
+
+#[test]
+fn test_andi_to_sr_leaving_supervisor_swaps_to_user_stack() {
+    let (mut cpu, mut memory) = create_test_cpu();
+    cpu.sr = 0x2700;
+    cpu.a[7] = 0x1000;
+    cpu.usp = 0x3000;
+
+    // ANDI #$DFFF,SR ; ORI #$2000,SR (privileged from user mode)
+    memory.write_word(0x100, 0x027C);
+    memory.write_word(0x102, 0xDFFF);
+    memory.write_word(0x104, 0x007C);
+    memory.write_word(0x106, 0x2000);
+    memory.write_long(0x20, 0x200); // Privilege violation vector
+
+    assert_eq!(cpu.step_instruction(&mut memory), 20);
+    assert_eq!(cpu.sr, 0x0700);
+    assert_eq!(cpu.a[7], 0x3000, "A7 is now the user stack");
+    assert_eq!(cpu.ssp, 0x1000, "supervisor stack saved");
+
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x200, "ORI to SR traps in user mode");
+    assert_eq!(cpu.usp, 0x3000);
+    assert_eq!(
+        cpu.a[7],
+        0x1000 - 6,
+        "exception frame on the supervisor stack"
+    );
+}
+
 // ============ Property Tests ============
 
 proptest! {
@@ -0,0 +1,130 @@
+//! Property tests for ADD/SUB flag generation
+//!
+//! Compares `add_with_flags`/`sub_with_flags`, and the CCR left by ADD and
+//! SUB, against a reference computed in 64-bit arithmetic, for random
+//! operands biased towards the sign and carry boundaries of each size.
+
+#![cfg(test)]
+
+use crate::cpu::flags;
+use crate::cpu::instructions::Size;
+use crate::cpu::test_utils::{create_cpu, write_op};
+use proptest::prelude::*;
+use std::cell::RefCell;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+}
+
+/// Result plus N, Z, V, C, computed from the unsigned and signed values
+fn reference(op: Op, dst: u32, src: u32, size: Size) -> (u32, bool, bool, bool, bool) {
+    let mask = size.mask() as u64;
+    let bits = size.bits();
+    let d = dst as u64 & mask;
+    let s = src as u64 & mask;
+    let signed = |v: u64| ((v << (64 - bits)) as i64) >> (64 - bits);
+    let (wide, signed_wide) = match op {
+        Op::Add => (d + s, signed(d) + signed(s)),
+        Op::Sub => (d.wrapping_sub(s), signed(d) - signed(s)),
+    };
+    let res = wide & mask;
+    let min = -(1i64 << (bits - 1));
+    let max = (1i64 << (bits - 1)) - 1;
+    let carry = match op {
+        Op::Add => wide > mask,
+        Op::Sub => s > d,
+    };
+    let overflow = signed_wide < min || signed_wide > max;
+    let negative = res >> (bits - 1) & 1 != 0;
+    (res as u32, negative, res == 0, overflow, carry)
+}
+
+fn size() -> impl Strategy<Value = Size> {
+    prop_oneof![Just(Size::Byte), Just(Size::Word), Just(Size::Long)]
+}
+
+/// Random values mixed with the sign, zero and all-ones edges of each size
+fn operand() -> impl Strategy<Value = u32> {
+    prop_oneof![
+        3 => any::<u32>(),
+        1 => prop::sample::select(vec![
+            0, 1, 0x7F, 0x80, 0xFF, 0x7FFF, 0x8000, 0xFFFF, 0x7FFF_FFFF, 0x8000_0000, 0xFFFF_FFFF,
+        ]),
+    ]
+}
+
+fn opcode(op: Op, size: Size) -> u16 {
+    let base = match op {
+        Op::Add => 0xD001, // ADD.B D1,D0
+        Op::Sub => 0x9001, // SUB.B D1,D0
+    };
+    let size_bits = match size {
+        Size::Byte => 0,
+        Size::Word => 1,
+        Size::Long => 2,
+    };
+    base | (size_bits << 6)
+}
+
+// A CPU is expensive to build (its decode cache is large), so each test
+// builds one and reuses it for every case.
+
+#[test]
+fn prop_add_with_flags_matches_reference() {
+    let (cpu, _) = create_cpu();
+    proptest!(ProptestConfig::with_cases(6000), |(size in size(), src in operand(), dst in operand())| {
+        let (res, _, _, v, c) = reference(Op::Add, dst, src, size);
+        prop_assert_eq!(cpu.add_with_flags(src, dst, size), (res, c, v));
+    });
+}
+
+#[test]
+fn prop_sub_with_flags_matches_reference() {
+    let (cpu, _) = create_cpu();
+    proptest!(ProptestConfig::with_cases(6000), |(size in size(), src in operand(), dst in operand())| {
+        let (res, _, _, v, c) = reference(Op::Sub, dst, src, size);
+        prop_assert_eq!(cpu.sub_with_flags(dst, src, size), (res, c, v));
+    });
+}
+
+#[test]
+fn prop_add_sub_ccr_matches_reference() {
+    let (cpu, mut memory) = create_cpu();
+    // One copy of each opcode, so the decode cache stays valid across cases
+    let ops = [Op::Add, Op::Sub];
+    let sizes = [Size::Byte, Size::Word, Size::Long];
+    let program: Vec<u16> = ops
+        .iter()
+        .flat_map(|&op| sizes.iter().map(move |&size| opcode(op, size)))
+        .collect();
+    write_op(&mut memory, &program);
+    // The runner only takes `Fn` closures
+    let machine = RefCell::new((cpu, memory));
+
+    proptest!(ProptestConfig::with_cases(6000), |(
+        is_sub in any::<bool>(),
+        size_idx in 0usize..3,
+        src in operand(),
+        dst in operand(),
+        x_in in any::<bool>(),
+    )| {
+        let op = ops[is_sub as usize];
+        let size = sizes[size_idx];
+        let (cpu, memory) = &mut *machine.borrow_mut();
+        cpu.pc = 0x1000 + (is_sub as u32 * 3 + size_idx as u32) * 2;
+        cpu.d[0] = dst;
+        cpu.d[1] = src;
+        cpu.set_flag(flags::EXTEND, x_in);
+        cpu.step_instruction(memory);
+
+        let (res, n, z, v, c) = reference(op, dst, src, size);
+        prop_assert_eq!(cpu.d[0], size.apply(dst, res));
+        prop_assert_eq!(cpu.get_flag(flags::NEGATIVE), n);
+        prop_assert_eq!(cpu.get_flag(flags::ZERO), z);
+        prop_assert_eq!(cpu.get_flag(flags::OVERFLOW), v);
+        prop_assert_eq!(cpu.get_flag(flags::CARRY), c);
+        prop_assert_eq!(cpu.get_flag(flags::EXTEND), c, "X follows C");
+    });
+}
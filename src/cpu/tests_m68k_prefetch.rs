@@ -0,0 +1,66 @@
+//! Prefetch queue tests
+//!
+//! Self-modifying code that rewrites the instruction immediately after the
+//! writer: the 68000 has already fetched that opcode, so the old one runs.
+
+#![cfg(test)]
+
+use crate::cpu::test_utils::{create_cpu, write_op};
+use crate::memory::MemoryInterface;
+
+/// MOVE.W #$4E71,($1006).W ; MOVEQ #1,D0 ; MOVEQ #2,D1
+/// The MOVE overwrites the MOVEQ #1 that follows it with a NOP.
+const REWRITE_NEXT: [u16; 5] = [0x31FC, 0x4E71, 0x1006, 0x7001, 0x7202];
+
+#[test]
+fn test_rewritten_next_opcode_runs_stale_with_prefetch() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.emulate_prefetch = true;
+    write_op(&mut memory, &REWRITE_NEXT);
+
+    cpu.step_instruction(&mut memory);
+    assert_eq!(memory.read_word(0x1006), 0x4E71, "the write landed");
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[0], 1, "the prefetched MOVEQ ran, not the new NOP");
+    assert_eq!(cpu.pc, 0x1008);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[1], 2);
+}
+
+#[test]
+fn test_rewritten_next_opcode_runs_new_without_prefetch() {
+    let (mut cpu, mut memory) = create_cpu();
+    write_op(&mut memory, &REWRITE_NEXT);
+
+    cpu.step_instruction(&mut memory);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[0], 0, "the NOP ran");
+    assert_eq!(cpu.pc, 0x1008);
+}
+
+#[test]
+fn test_prefetch_is_discarded_by_a_branch() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.emulate_prefetch = true;
+    // BRA.S *+4 ; MOVEQ #1,D1 ; MOVEQ #2,D2
+    write_op(&mut memory, &[0x6002, 0x7201, 0x7402]);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.pc, 0x1004);
+    cpu.step_instruction(&mut memory);
+    assert_eq!(cpu.d[1], 0, "the skipped MOVEQ in the queue didn't run");
+    assert_eq!(cpu.d[2], 2);
+}
+
+#[test]
+fn test_later_words_are_fetched_after_the_write() {
+    let (mut cpu, mut memory) = create_cpu();
+    cpu.emulate_prefetch = true;
+    // MOVE.W #$7203,($1008).W ; NOP ; MOVEQ #1,D0
+    // Only the opcode straight after the writer is held in the queue
+    write_op(&mut memory, &[0x31FC, 0x7203, 0x1008, 0x4E71, 0x7001]);
+    for _ in 0..3 {
+        cpu.step_instruction(&mut memory);
+    }
+    assert_eq!(cpu.d[0], 0);
+    assert_eq!(cpu.d[1], 3, "the rewritten MOVEQ #3,D1 ran");
+}
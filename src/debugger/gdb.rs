@@ -224,14 +224,14 @@ impl GdbServer {
             Ok((stream, addr)) => {
                 // Security check: Only allow loopback connections
                 if !addr.ip().is_loopback() {
-                    eprintln!(
+                    log::warn!(
                         "⚠️  SECURITY ALERT: Rejected GDB connection from non-loopback address: {}",
                         addr
                     );
                     return false;
                 }
 
-                eprintln!("ℹ️  Accepted GDB connection from {}", addr);
+                log::info!("ℹ️  Accepted GDB connection from {}", addr);
                 stream.set_nonblocking(true).ok();
                 self.client = Some(stream);
                 true
@@ -311,7 +311,7 @@ impl GdbServer {
                     // but stay in sync with the protocol by continuing to read until the checksum.
                     if data.len() >= MAX_PACKET_SIZE {
                         if !oversized {
-                            eprintln!("⚠️  SECURITY ALERT: GDB packet exceeded maximum size of {}. Refusing.", MAX_PACKET_SIZE);
+                            log::warn!("⚠️  SECURITY ALERT: GDB packet exceeded maximum size of {}. Refusing.", MAX_PACKET_SIZE);
                             oversized = true;
                         }
                     } else {
@@ -686,7 +686,7 @@ impl GdbServer {
         };
 
         if self.breakpoints.len() >= MAX_BREAKPOINTS && !self.breakpoints.contains(&addr) {
-            eprintln!(
+            log::warn!(
                 "⚠️  SECURITY ALERT: Maximum number of breakpoints ({}) reached.",
                 MAX_BREAKPOINTS
             );
@@ -806,7 +806,7 @@ impl GdbServer {
                 self.auth_failed_attempts += 1;
                 if self.auth_failed_attempts >= MAX_AUTH_ATTEMPTS {
                     self.auth_lockout_until = Some(Instant::now() + AUTH_LOCKOUT_DURATION);
-                    eprintln!(
+                    log::warn!(
                         "⚠️  SECURITY ALERT: GDB authentication lockout active for {}s",
                         AUTH_LOCKOUT_DURATION.as_secs()
                     );
@@ -838,7 +838,7 @@ impl GdbServer {
         // Unknown monitor command.
         // We return "OK" to prevent the client from treating this as an error,
         // effectively ignoring unknown commands.
-        eprintln!("ℹ️  Ignored unknown monitor command: {}", cmd);
+        log::info!("ℹ️  Ignored unknown monitor command: {}", cmd);
         "OK".to_string()
     }
 
@@ -3,6 +3,7 @@
 //! Provides cross-platform windowing, input handling, and rendering
 //! for the Genesis emulator using pure Rust libraries.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 #[cfg(any(feature = "gui", feature = "test_headless"))]
 use winit::keyboard::{Key, KeyCode};
@@ -18,6 +19,120 @@ pub enum InputMapping {
     Ergonomic,
 }
 
+/// Button names accepted by `ControllerState::set_button`
+const BUTTON_NAMES: [&str; 12] = [
+    "up", "down", "left", "right", "a", "b", "c", "start", "x", "y", "z", "mode",
+];
+
+/// Physical keys of the `Original` layout, by `KeyCode` name
+const ORIGINAL_KEYS: [(&str, &str); 12] = [
+    ("ArrowUp", "up"),
+    ("ArrowDown", "down"),
+    ("ArrowLeft", "left"),
+    ("ArrowRight", "right"),
+    ("KeyZ", "a"),
+    ("KeyX", "b"),
+    ("KeyC", "c"),
+    ("Enter", "start"),
+    ("KeyA", "x"),
+    ("KeyS", "y"),
+    ("KeyD", "z"),
+    ("KeyQ", "mode"),
+];
+
+/// Physical keys of the `Ergonomic` layout, by `KeyCode` name
+const ERGONOMIC_KEYS: [(&str, &str); 19] = [
+    ("KeyW", "up"),
+    ("ArrowUp", "up"),
+    ("KeyS", "down"),
+    ("ArrowDown", "down"),
+    ("KeyA", "left"),
+    ("ArrowLeft", "left"),
+    ("KeyD", "right"),
+    ("ArrowRight", "right"),
+    ("KeyJ", "a"),
+    ("KeyK", "b"),
+    ("KeyL", "c"),
+    ("KeyU", "x"),
+    ("KeyI", "y"),
+    ("KeyO", "z"),
+    ("Enter", "start"),
+    ("Space", "mode"),
+    ("KeyZ", "a"),
+    ("KeyX", "b"),
+    ("KeyC", "c"),
+];
+
+/// The key table of a built-in layout
+fn layout_keys(mapping: InputMapping) -> &'static [(&'static str, &'static str)] {
+    match mapping {
+        InputMapping::Original => &ORIGINAL_KEYS,
+        InputMapping::Ergonomic => &ERGONOMIC_KEYS,
+    }
+}
+
+/// Player 1 key bindings that don't depend on a windowing library, for
+/// headless use and custom frontends. Keys are named as winit's physical
+/// `KeyCode` variants ("KeyZ", "ArrowUp", "Enter"); `keycode_name` gives
+/// the name of a winit key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMap {
+    bindings: BTreeMap<String, String>,
+}
+
+impl KeyMap {
+    /// A map with no keys bound
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One of the built-in layouts, as a starting point for remapping
+    pub fn from_mapping(mapping: InputMapping) -> Self {
+        let mut map = Self::new();
+        for &(key, button) in layout_keys(mapping) {
+            map.bindings.insert(key.to_string(), button.to_string());
+        }
+        map
+    }
+
+    /// Bind `key` to `button`, replacing any earlier binding of the key
+    pub fn bind(&mut self, key: &str, button: &str) -> Result<(), String> {
+        let button = button.to_lowercase();
+        if !BUTTON_NAMES.contains(&button.as_str()) {
+            return Err(format!("Unknown button '{}'", button));
+        }
+        self.bindings.insert(key.to_string(), button);
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, key: &str) {
+        self.bindings.remove(key);
+    }
+
+    /// Button `key` is bound to
+    pub fn button(&self, key: &str) -> Option<&str> {
+        self.bindings.get(key).map(String::as_str)
+    }
+
+    /// Apply a key press or release to `pad`. Returns false if the key
+    /// isn't bound.
+    pub fn apply(&self, key: &str, pressed: bool, pad: &mut crate::io::ControllerState) -> bool {
+        match self.button(key) {
+            Some(button) => {
+                pad.set_button(button, pressed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Name of a physical key as used by `KeyMap`
+#[cfg(any(feature = "gui", feature = "test_headless"))]
+pub fn keycode_name(keycode: KeyCode) -> String {
+    format!("{:?}", keycode)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PsgToneInfo {
     pub frequency: u16,
@@ -58,55 +173,14 @@ pub struct DebugInfo {
 
 use serde::{Deserialize, Serialize};
 
-/// Key mapping for player 1 (Physical KeyCode)
+/// Key mapping for player 1 (Physical KeyCode), from the layout tables
 #[cfg(any(feature = "gui", feature = "test_headless"))]
 pub fn keycode_to_button(keycode: KeyCode, mapping: InputMapping) -> Option<(&'static str, bool)> {
-    match mapping {
-        InputMapping::Original => match keycode {
-            // Player 1 - Arrow keys + ZXC/Enter
-            KeyCode::ArrowUp => Some(("up", true)),
-            KeyCode::ArrowDown => Some(("down", true)),
-            KeyCode::ArrowLeft => Some(("left", true)),
-            KeyCode::ArrowRight => Some(("right", true)),
-            KeyCode::KeyZ => Some(("a", true)),
-            KeyCode::KeyX => Some(("b", true)),
-            KeyCode::KeyC => Some(("c", true)),
-            KeyCode::Enter => Some(("start", true)),
-            // 6-button extension
-            KeyCode::KeyA => Some(("x", true)),
-            KeyCode::KeyS => Some(("y", true)),
-            KeyCode::KeyD => Some(("z", true)),
-            KeyCode::KeyQ => Some(("mode", true)),
-            _ => None,
-        },
-        InputMapping::Ergonomic => match keycode {
-            // Player 1 - D-pad (WASD physical or Arrow keys)
-            KeyCode::KeyW | KeyCode::ArrowUp => Some(("up", true)),
-            KeyCode::KeyS | KeyCode::ArrowDown => Some(("down", true)),
-            KeyCode::KeyA | KeyCode::ArrowLeft => Some(("left", true)),
-            KeyCode::KeyD | KeyCode::ArrowRight => Some(("right", true)),
-
-            // Face Buttons (Bottom Row: J, K, L -> A, B, C)
-            KeyCode::KeyJ => Some(("a", true)),
-            KeyCode::KeyK => Some(("b", true)),
-            KeyCode::KeyL => Some(("c", true)),
-
-            // Face Buttons (Top Row: U, I, O -> X, Y, Z)
-            KeyCode::KeyU => Some(("x", true)),
-            KeyCode::KeyI => Some(("y", true)),
-            KeyCode::KeyO => Some(("z", true)),
-
-            // System Buttons
-            KeyCode::Enter => Some(("start", true)),
-            KeyCode::Space => Some(("mode", true)),
-
-            // Legacy/Alternative Mapping (ZX for A/B)
-            KeyCode::KeyZ => Some(("a", true)),
-            KeyCode::KeyX => Some(("b", true)),
-            KeyCode::KeyC => Some(("c", true)),
-            _ => None,
-        },
-    }
+    let name = keycode_name(keycode);
+    layout_keys(mapping)
+        .iter()
+        .find(|&&(key, _)| key == name)
+        .map(|&(_, button)| (button, true))
 }
 
 /// Key mapping for player 1 (Logical Key)
@@ -162,6 +236,59 @@ pub fn rgb565_to_rgba8(framebuffer_565: &[u16], output: &mut [u8]) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_custom_key_map_drives_buttons() {
+        let mut map = KeyMap::new();
+        map.bind("KeyF", "Start").unwrap();
+        map.bind("Numpad0", "a").unwrap();
+        assert!(map.bind("KeyG", "turbo").is_err());
+        assert_eq!(map.button("KeyF"), Some("start"));
+
+        let mut pad = crate::io::ControllerState::default();
+        assert!(map.apply("KeyF", true, &mut pad));
+        assert!(pad.start);
+        assert!(map.apply("Numpad0", true, &mut pad));
+        assert!(pad.a);
+        assert!(!map.apply("KeyZ", true, &mut pad), "unbound key");
+        assert!(map.apply("KeyF", false, &mut pad));
+        assert!(!pad.start);
+
+        // Rebinding moves the key; maps round-trip through JSON
+        map.bind("KeyF", "mode").unwrap();
+        map.unbind("Numpad0");
+        let json = serde_json::to_string(&map).unwrap();
+        let restored: KeyMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, map);
+        assert_eq!(restored.button("KeyF"), Some("mode"));
+        assert_eq!(restored.button("Numpad0"), None);
+    }
+
+    #[cfg(any(feature = "gui", feature = "test_headless"))]
+    #[test]
+    fn test_key_map_presets_match_keycode_tables() {
+        for mapping in [InputMapping::Original, InputMapping::Ergonomic] {
+            let map = KeyMap::from_mapping(mapping);
+            for keycode in [
+                KeyCode::ArrowUp,
+                KeyCode::KeyW,
+                KeyCode::KeyZ,
+                KeyCode::KeyA,
+                KeyCode::KeyQ,
+                KeyCode::Enter,
+                KeyCode::Space,
+                KeyCode::KeyP,
+            ] {
+                assert_eq!(
+                    map.button(&keycode_name(keycode)),
+                    keycode_to_button(keycode, mapping).map(|(button, _)| button),
+                    "{:?} {:?}",
+                    mapping,
+                    keycode
+                );
+            }
+        }
+    }
+
     #[cfg(any(feature = "gui", feature = "test_headless"))]
     #[test]
     fn test_keycode_mapping() {
@@ -212,7 +212,8 @@ pub struct DebugInfo {
     pub m68k_sr: u16,
     pub m68k_usp: u32,
     pub m68k_ssp: u32,
-    pub m68k_disasm: [(u32, crate::cpu::instructions::Instruction); 10],
+    pub m68k_halt_reason: Option<crate::cpu::HaltReason>,
+    pub m68k_disasm: Vec<(u32, String, u32)>,
     pub z80_pc: u16,
     pub z80_a: u8,
     pub z80_f: u8,
@@ -240,6 +241,7 @@ pub struct DebugInfo {
     pub cram_raw: [u16; 64],
     pub vram: [u8; 0x10000],
     pub vsram: [u8; 80],
+    pub sprites: Vec<crate::vdp::SpriteAttributes>,
     pub wram: [u8; 0x10000],
     pub z80_ram: [u8; 0x2000],
     pub ym2612_regs: [[u8; 256]; 2],
@@ -884,6 +886,9 @@ impl Framework {
                     ui.separator();
                     self.label_fmt(ui, format_args!("USP: {:08X}", debug_info.m68k_usp));
                     self.label_fmt(ui, format_args!("SSP: {:08X}", debug_info.m68k_ssp));
+                    if let Some(reason) = debug_info.m68k_halt_reason {
+                        self.label_fmt(ui, format_args!("Halted: {:?}", reason));
+                    }
                 });
             if !open {
                 self.gui_state.set_window_open("M68k Status", false);
@@ -995,19 +1000,16 @@ impl Framework {
                     egui::ScrollArea::vertical()
                         .id_source("m68k_disasm")
                         .show(ui, |ui| {
-                            for (addr, instr) in &debug_info.m68k_disasm {
+                            for (addr, text, _) in &debug_info.m68k_disasm {
                                 let is_current = *addr == debug_info.m68k_pc;
                                 if is_current {
                                     self.colored_label_fmt(
                                         ui,
                                         egui::Color32::YELLOW,
-                                        format_args!("-> {:06X}: {:?}", addr, instr),
+                                        format_args!("-> {:06X}: {}", addr, text),
                                     );
                                 } else {
-                                    self.label_fmt(
-                                        ui,
-                                        format_args!("   {:06X}: {:?}", addr, instr),
-                                    );
+                                    self.label_fmt(ui, format_args!("   {:06X}: {}", addr, text));
                                 }
                             }
                         });
@@ -1144,18 +1146,6 @@ impl Framework {
             egui::Window::new("Sprite Viewer")
                 .open(&mut open)
                 .show(&ctx, |ui| {
-                    let sat_base = ((debug_info.vdp_registers[5] as usize) & 0x7F) << 9;
-                    let h40 = (debug_info.vdp_registers[12] & 0x81) == 0x81;
-                    let max_sprites = if h40 { 80 } else { 64 };
-
-                    let iter = crate::vdp::SpriteIterator {
-                        vram: &debug_info.vram[..],
-                        next_idx: 0,
-                        count: 0,
-                        max_sprites,
-                        sat_base,
-                    };
-
                     egui::ScrollArea::vertical().show(ui, |ui| {
                         egui::Grid::new("sprite_grid").striped(true).show(ui, |ui| {
                             ui.label("Idx");
@@ -1168,7 +1158,7 @@ impl Framework {
                             ui.label("Link");
                             ui.end_row();
 
-                            for attr in iter {
+                            for attr in &debug_info.sprites {
                                 self.label_fmt(ui, format_args!("{}", attr.index));
                                 self.label_fmt(ui, format_args!("{},{}", attr.h_pos, attr.v_pos));
                                 self.label_fmt(ui, format_args!("{}x{}", attr.h_size, attr.v_size));
@@ -1835,20 +1825,9 @@ fn collect_debug_info(
     let mut bus = emulator.bus.borrow_mut();
     if force_red {
         bus.vdp.framebuffer.fill(0xF800); // Red in RGB565
+        bus.vdp.present();
     }
-    let mut m68k_disasm = [(
-        0u32,
-        crate::cpu::instructions::Instruction::System(
-            crate::cpu::instructions::SystemInstruction::Unimplemented { opcode: 0 },
-        ),
-    ); 10];
-    let mut addr = emulator.cpu.pc;
-    for item in &mut m68k_disasm {
-        let opcode = bus.read_word(addr);
-        let instr = crate::cpu::decode(opcode);
-        *item = (addr, instr);
-        addr += instr.length_words() * 2;
-    }
+    let m68k_disasm = crate::cpu::disasm::disassemble_range(&mut *bus, emulator.cpu.pc, 10);
 
     let mut z80_disasm = [(0u16, 0u8); 10];
     let mut addr = emulator.z80.pc;
@@ -1875,6 +1854,7 @@ fn collect_debug_info(
         m68k_sr: emulator.cpu.sr,
         m68k_usp: emulator.cpu.usp,
         m68k_ssp: emulator.cpu.ssp,
+        m68k_halt_reason: emulator.cpu.halt_reason,
         m68k_disasm,
         z80_pc: emulator.z80.pc,
         z80_a: emulator.z80.a,
@@ -1903,6 +1883,7 @@ fn collect_debug_info(
         cram_raw,
         vram: bus.vdp.vram,
         vsram: bus.vdp.vsram,
+        sprites: bus.vdp.sprite_attributes().to_vec(),
         wram,
         z80_ram,
         ym2612_regs: bus.apu.fm.registers,
@@ -1916,7 +1897,7 @@ fn collect_debug_info(
         has_rom: !bus.rom.is_empty(),
         current_rom_path: emulator.current_rom_path.clone(),
     };
-    frontend::rgb565_to_rgba8(&bus.vdp.framebuffer, pixels_frame);
+    frontend::rgb565_to_rgba8(bus.vdp.front_buffer(), pixels_frame);
     info
 }
 
@@ -1941,6 +1922,10 @@ fn handle_keyboard_input(
             framework.handle_exit(emulator, record_path);
             return true;
         }
+        if let Some(key_map) = &emulator.key_map {
+            key_map.apply(&frontend::keycode_name(keycode), pressed, &mut input.p1);
+            return false;
+        }
         if let Some((button, _)) = frontend::keycode_to_button(keycode, emulator.input_mapping) {
             input.p1.set_button(button, pressed);
             handled = true;
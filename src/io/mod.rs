@@ -28,6 +28,12 @@ use crate::debugger::Debuggable;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// 6-button pad handshake timeout in 68k cycles.
+///
+/// The pad's internal counter resets when TH hasn't fallen for ~1.5ms;
+/// at the NTSC 68000 clock (MCLK/7 = 7.67 MHz) that is 11,505 cycles.
+pub const TH_TIMEOUT_CYCLES: u32 = 11_505;
+
 /// Button state for a Genesis controller
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct ControllerState {
@@ -89,6 +95,24 @@ impl ControllerState {
         *self = Self::default();
     }
 
+    /// This state with every button that is pressed in `mask` released
+    pub fn without(&self, mask: &ControllerState) -> Self {
+        Self {
+            up: self.up && !mask.up,
+            down: self.down && !mask.down,
+            left: self.left && !mask.left,
+            right: self.right && !mask.right,
+            a: self.a && !mask.a,
+            b: self.b && !mask.b,
+            c: self.c && !mask.c,
+            start: self.start && !mask.start,
+            x: self.x && !mask.x,
+            y: self.y && !mask.y,
+            z: self.z && !mask.z,
+            mode: self.mode && !mask.mode,
+        }
+    }
+
     /// Convert state to button string format: UDLRABCSXYZM
     pub fn to_button_string(&self) -> String {
         let mut s = String::with_capacity(12);
@@ -126,6 +150,52 @@ pub enum ControllerType {
     SixButton,
 }
 
+/// Where the 6-button handshake answers, counted in TH falling edges
+/// since the pad's counter last reset (the counter wraps at 8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SixButtonTiming {
+    /// TH=0 cycle that returns the pad ID
+    pub id_cycle: u8,
+    /// TH=0 cycle that returns X, Y, Z and Mode
+    pub extra_cycle: u8,
+}
+
+impl SixButtonTiming {
+    /// The sequence official pads use
+    pub const STANDARD: Self = Self {
+        id_cycle: 3,
+        extra_cycle: 5,
+    };
+
+    /// The standard sequence answered `pulses` TH pulses later, or `None`
+    /// if that would push the extra cycle past the counter's wrap
+    pub const fn delayed(pulses: u8) -> Option<Self> {
+        match Self::STANDARD.extra_cycle.checked_add(pulses) {
+            Some(extra_cycle) if extra_cycle < 8 => Some(Self {
+                id_cycle: Self::STANDARD.id_cycle + pulses,
+                extra_cycle,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Default for SixButtonTiming {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Rapid fire: while held, each turbo button alternates between pressed
+/// and released `rate_hz` times a second, stepped once per frame
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TurboConfig {
+    /// Buttons with turbo enabled, marked as pressed
+    pub buttons: ControllerState,
+    /// Presses per second; zero disables turbo
+    pub rate_hz: u32,
+}
+
 /// A controller port
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ControllerPort {
@@ -139,8 +209,26 @@ pub struct ControllerPort {
     pub th_state: bool,
     /// Counter for 6-button protocol
     pub(crate) th_counter: u8,
-    /// Timer for 6-button reset (cycles since last TH transition)
+    /// Timer for 6-button reset (68k cycles since last TH falling edge)
     pub(crate) th_timer: u32,
+    /// Handshake cycles a 6-button pad answers on
+    #[serde(default)]
+    pub six_button_timing: SixButtonTiming,
+    /// Rapid-fire settings for this port
+    #[serde(default)]
+    pub turbo: TurboConfig,
+    /// Frames since turbo started counting, and whether the current frame
+    /// falls in the released half of the turbo cycle
+    #[serde(skip)]
+    turbo_frame: u32,
+    #[serde(skip)]
+    turbo_released: bool,
+    /// Data port reads since the last `reset_poll_count`
+    #[serde(skip)]
+    polls: u32,
+    /// What the TH-low reads so far identify the pad as
+    #[serde(skip)]
+    detected: Option<ControllerType>,
 }
 
 impl ControllerPort {
@@ -153,15 +241,25 @@ impl ControllerPort {
             th_state: true,
             th_counter: 0,
             th_timer: 0,
+            six_button_timing: SixButtonTiming::STANDARD,
+            turbo: TurboConfig::default(),
+            turbo_frame: 0,
+            turbo_released: false,
+            polls: 0,
+            detected: None,
         }
     }
 
-    /// Reset the port
-    pub fn reset(&mut self) {
-        self.control = 0x00;
-        self.th_state = true;
+    /// Reset the port. A hard reset also clears the control register and TH
+    /// output; a soft reset only drops the 6-button handshake state.
+    pub fn reset(&mut self, hard: bool) {
+        if hard {
+            self.control = 0x00;
+            self.th_state = true;
+        }
         self.th_counter = 0;
         self.th_timer = 0;
+        self.detected = None;
     }
 
     /// Read from the data port
@@ -169,39 +267,98 @@ impl ControllerPort {
     /// The returned value depends on:
     /// - TH state (directly or via control register)
     /// - 6-button counter
-    pub fn read_data(&self) -> u8 {
-        match self.controller_type {
+    pub fn read_data(&mut self) -> u8 {
+        let data = match self.controller_type {
             ControllerType::None => 0x7F, // No controller = all inputs high
 
             ControllerType::ThreeButton => self.read_3button(),
 
             ControllerType::SixButton => self.read_6button(),
+        };
+        if !self.th_state {
+            self.observe_th_low(data);
+        }
+        data
+    }
+
+    /// Classify a TH=0 read the way pad detection routines do. A pad forces
+    /// bits 2-3 low on every TH=0 read, and a 6-button pad answers its ID
+    /// cycle with all of D3-D0 low; an open port reads all ones. The
+    /// 6-button ID is sticky until reset.
+    fn observe_th_low(&mut self, data: u8) {
+        if data & 0x0F == 0 {
+            self.detected = Some(ControllerType::SixButton);
+        } else if self.detected != Some(ControllerType::SixButton) {
+            self.detected = Some(if data & 0x0C == 0 {
+                ControllerType::ThreeButton
+            } else {
+                ControllerType::None
+            });
+        }
+    }
+
+    /// What a game probing this port would conclude from the handshake so
+    /// far, or `None` before any TH=0 read
+    pub fn detected_type(&self) -> Option<ControllerType> {
+        self.detected
+    }
+
+    /// Number of times the data port has been polled since the last reset
+    pub fn poll_count(&self) -> u32 {
+        self.polls
+    }
+
+    pub fn count_poll(&mut self) {
+        self.polls = self.polls.saturating_add(1);
+    }
+
+    pub fn reset_poll_count(&mut self) {
+        self.polls = 0;
+    }
+
+    /// Step turbo by one frame, for a display running at `frames_per_second`
+    pub fn advance_turbo_frame(&mut self, frames_per_second: u32) {
+        self.turbo_frame = self.turbo_frame.wrapping_add(1);
+        let half_cycles = self.turbo_frame as u64 * 2 * self.turbo.rate_hz as u64;
+        self.turbo_released = self.turbo.rate_hz != 0
+            && frames_per_second != 0
+            && (half_cycles / frames_per_second as u64) % 2 == 1;
+    }
+
+    /// Buttons as the console sees them: the held state, minus turbo
+    /// buttons during the released half of the turbo cycle
+    pub fn reported_state(&self) -> ControllerState {
+        if self.turbo_released {
+            self.state.without(&self.turbo.buttons)
+        } else {
+            self.state
         }
     }
 
     /// Read 3-button controller data
     fn read_3button(&self) -> u8 {
+        let state = self.reported_state();
         // Genesis controllers are active-low: pressed = 0, released = 1
         if self.th_state {
             // TH=1: Return Up, Down, Left, Right, B, C (and TH=1)
             let mut data = 0x7F; // All released + TH high
 
-            if self.state.up {
+            if state.up {
                 data &= !0x01;
             }
-            if self.state.down {
+            if state.down {
                 data &= !0x02;
             }
-            if self.state.left {
+            if state.left {
                 data &= !0x04;
             }
-            if self.state.right {
+            if state.right {
                 data &= !0x08;
             }
-            if self.state.b {
+            if state.b {
                 data &= !0x10;
             }
-            if self.state.c {
+            if state.c {
                 data &= !0x20;
             }
 
@@ -210,17 +367,17 @@ impl ControllerPort {
             // TH=0: Return Up, Down, 0, 0, A, Start (TH=0)
             let mut data = 0x33; // Up, Down, A, Start released; bits 2-3 low
 
-            if self.state.up {
+            if state.up {
                 data &= !0x01;
             }
-            if self.state.down {
+            if state.down {
                 data &= !0x02;
             }
             // Bits 2-3 are always 0 when TH=0 (used to detect controller type)
-            if self.state.a {
+            if state.a {
                 data &= !0x10;
             }
-            if self.state.start {
+            if state.start {
                 data &= !0x20;
             }
 
@@ -235,45 +392,39 @@ impl ControllerPort {
             return self.read_3button();
         }
 
+        let timing = self.six_button_timing;
         match self.th_counter {
-            3 => self.read_cycle3(),
-            5 => self.read_extra_buttons(),
-            // Cycles 0, 1, 2, 4 and others use standard 3-button logic
+            c if c == timing.id_cycle => self.read_cycle3(),
+            c if c == timing.extra_cycle => self.read_extra_buttons(),
+            // Every other cycle uses standard 3-button logic
             _ => self.read_3button(),
         }
     }
 
     /// Read data for cycle 3 (controller identification)
     fn read_cycle3(&self) -> u8 {
-        // Fourth cycle: TH=0 returns controller ID in low nibble
-        // Note: Original implementation returns Active High (1=Pressed) for Up/Down
-        // and sets bits 2-3 to 1. This behavior is preserved here.
-        let mut data = 0x0C; // Bits 2 and 3 set
-        if self.state.up {
-            data |= 0x01;
-        }
-        if self.state.down {
-            data |= 0x02;
-        }
-        data
+        // Fourth cycle: TH=0 drives D3-D0 low in place of Up/Down, while A
+        // and Start still read as on any TH=0 cycle
+        self.read_3button() & 0x30
     }
 
     /// Read data for cycle 5 (extra buttons X, Y, Z, Mode)
     fn read_extra_buttons(&self) -> u8 {
+        let state = self.reported_state();
         // Sixth cycle: TH=0 returns X, Y, Z, Mode
         // Note: Original implementation returns Active High (1=Pressed) for these buttons.
         // This behavior is preserved here.
         let mut data = 0x70; // High nibble bits 4-6 set
-        if self.state.z {
+        if state.z {
             data |= 0x01;
         }
-        if self.state.y {
+        if state.y {
             data |= 0x02;
         }
-        if self.state.x {
+        if state.x {
             data |= 0x04;
         }
-        if self.state.mode {
+        if state.mode {
             data |= 0x08;
         }
         data
@@ -295,12 +446,29 @@ impl ControllerPort {
         self.th_state = new_th;
     }
 
-    /// Update timing (call each CPU cycle or frame)
+    /// Drive TH from the peripheral side, as a light gun does.
+    ///
+    /// Only takes effect while TH is configured as an input. Returns true
+    /// when a falling edge should assert HL, which needs the port's TH
+    /// interrupt enable (control bit 7).
+    pub fn drive_th(&mut self, level: bool) -> bool {
+        if (self.control & 0x40) != 0 {
+            return false;
+        }
+        let falling = self.th_state && !level;
+        self.th_state = level;
+        falling && (self.control & 0x80) != 0
+    }
+
+    /// Advance the handshake timer by `cycles` 68k cycles.
+    ///
+    /// Called by the bus as emulated time passes, so the counter also resets
+    /// after a long gap between reads with no TH activity at all.
     pub fn update(&mut self, cycles: u32) {
         if self.controller_type == ControllerType::SixButton {
-            self.th_timer += cycles;
+            self.th_timer = self.th_timer.saturating_add(cycles);
             // Reset counter if TH hasn't been toggled recently
-            if self.th_timer > 1500 {
+            if self.th_timer > TH_TIMEOUT_CYCLES {
                 self.th_counter = 0;
             }
         }
@@ -338,23 +506,28 @@ impl Io {
         }
     }
 
-    /// Reset all ports
-    pub fn reset(&mut self) {
-        self.port1.reset();
-        self.port2.reset();
-        self.expansion.reset();
+    /// Reset all ports. `hard` is a power cycle; otherwise this is the
+    /// console reset button, which leaves the ports configured as the game
+    /// set them. Connected controller types never change.
+    pub fn reset(&mut self, hard: bool) {
+        self.port1.reset(hard);
+        self.port2.reset(hard);
+        self.expansion.reset(hard);
     }
 
     /// Read from an I/O address
-    pub fn read(&self, address: u32) -> u8 {
+    ///
+    /// Registers sit on the low byte lane, so the even address of each pair
+    /// reads back the same value as the odd one.
+    pub fn read(&mut self, address: u32) -> u8 {
         match address & 0x1F {
-            0x01 => self.version,
-            0x03 => self.port1.read_data(),
-            0x05 => self.port2.read_data(),
-            0x07 => self.expansion.read_data(),
-            0x09 => self.port1.control,
-            0x0B => self.port2.control,
-            0x0D => self.expansion.control,
+            0x00 | 0x01 => self.version,
+            0x02 | 0x03 => self.port1.read_data(),
+            0x04 | 0x05 => self.port2.read_data(),
+            0x06 | 0x07 => self.expansion.read_data(),
+            0x08 | 0x09 => self.port1.control,
+            0x0A | 0x0B => self.port2.control,
+            0x0C | 0x0D => self.expansion.control,
             _ => 0xFF,
         }
     }
@@ -377,10 +550,47 @@ impl Io {
         match port {
             1 => self.port1.controller_type = controller_type,
             2 => self.port2.controller_type = controller_type,
+            3 => self.expansion.controller_type = controller_type,
+            _ => {}
+        }
+    }
+
+    /// Set the 6-button handshake timing for a port
+    pub fn set_six_button_timing(&mut self, port: u8, timing: SixButtonTiming) {
+        match port {
+            1 => self.port1.six_button_timing = timing,
+            2 => self.port2.six_button_timing = timing,
+            3 => self.expansion.six_button_timing = timing,
+            _ => {}
+        }
+    }
+
+    /// Set the rapid-fire configuration for a port
+    pub fn set_turbo(&mut self, port: u8, turbo: TurboConfig) {
+        match port {
+            1 => self.port1.turbo = turbo,
+            2 => self.port2.turbo = turbo,
+            3 => self.expansion.turbo = turbo,
             _ => {}
         }
     }
 
+    /// Step every port's turbo cycle, called once per frame
+    pub fn advance_turbo_frame(&mut self, frames_per_second: u32) {
+        self.port1.advance_turbo_frame(frames_per_second);
+        self.port2.advance_turbo_frame(frames_per_second);
+        self.expansion.advance_turbo_frame(frames_per_second);
+    }
+
+    /// Drive TH on a port from the peripheral side; true when HL is asserted
+    pub fn drive_th(&mut self, port: u8, level: bool) -> bool {
+        match port {
+            1 => self.port1.drive_th(level),
+            2 => self.port2.drive_th(level),
+            _ => false,
+        }
+    }
+
     /// Get mutable reference to controller state for a port
     pub fn controller(&mut self, port: u8) -> Option<&mut ControllerState> {
         match port {
@@ -390,10 +600,49 @@ impl Io {
         }
     }
 
-    /// Update timing for all ports
+    /// Data port reads on a port since the last `reset_poll_counts`. A frame
+    /// that ends with zero polls on an active port is a lag frame.
+    pub fn poll_count(&self, port: u8) -> u32 {
+        match port {
+            1 => self.port1.poll_count(),
+            2 => self.port2.poll_count(),
+            3 => self.expansion.poll_count(),
+            _ => 0,
+        }
+    }
+
+    /// Count one CPU read of `len` bytes at `address` as a poll of each data
+    /// port it touches. The bus calls this once per access, so a word or
+    /// long read of a port is one poll, and debugger peeks don't count.
+    pub fn count_polls(&mut self, address: u32, len: u32) {
+        let mut touched = [false; 3];
+        for byte in address..address.wrapping_add(len) {
+            if let 0x02..=0x07 = byte & 0x1F {
+                touched[(((byte & 0x1F) - 2) / 2) as usize] = true;
+            }
+        }
+        for (port, touched) in [&mut self.port1, &mut self.port2, &mut self.expansion]
+            .into_iter()
+            .zip(touched)
+        {
+            if touched {
+                port.count_poll();
+            }
+        }
+    }
+
+    /// Start a new polling window, called at the top of every frame
+    pub fn reset_poll_counts(&mut self) {
+        self.port1.reset_poll_count();
+        self.port2.reset_poll_count();
+        self.expansion.reset_poll_count();
+    }
+
+    /// Update timing for all ports (`cycles` in 68k cycles)
     pub fn update(&mut self, cycles: u32) {
         self.port1.update(cycles);
         self.port2.update(cycles);
+        self.expansion.update(cycles);
     }
 
     pub fn serialize(&self) -> serde_json::Value {
@@ -508,7 +757,7 @@ mod tests {
 
     #[test]
     fn test_io_version() {
-        let io = Io::new();
+        let mut io = Io::new();
         assert_eq!(io.read(0xA10001), 0xA0); // Overseas version
     }
 
@@ -569,11 +818,8 @@ mod tests {
 
         // Pulse 3 (Fall -> Cnt=3) ** ID Check **
         port.write_data(0x00);
-        // Logic: Bits 2-3 set (0x0C).
-        // Up=Pressed (Active High) -> Bit 0 set.
-        // Down=Released (Active High) -> Bit 1 clear.
-        // Expected: 0x0C | 0x01 = 0x0D.
-        assert_eq!(port.read_data(), 0x0D, "Pulse 3 Fall (ID Check)");
+        // Logic: D3-D0 low whatever the D-pad, A/Start released (0x30).
+        assert_eq!(port.read_data(), 0x30, "Pulse 3 Fall (ID Check)");
 
         // Pulse 3 (Rise)
         port.write_data(0x40);
@@ -597,6 +843,122 @@ mod tests {
         assert_eq!(port.read_data(), 0x71, "Pulse 5 Fall (Extra Buttons)");
     }
 
+    /// Pulse TH `pulses` times, returning each TH=0 read
+    fn th_low_reads(port: &mut ControllerPort, pulses: usize) -> Vec<u8> {
+        (0..pulses)
+            .map(|_| {
+                port.write_data(0x00);
+                let data = port.read_data();
+                port.write_data(0x40);
+                data
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_6button_extra_pulse_timing() {
+        let mut port = ControllerPort::new(ControllerType::SixButton);
+        port.state.x = true;
+        port.state.mode = true;
+
+        // Standard pads answer on pulses 3 and 5, so a sixth pulse is plain
+        let reads = th_low_reads(&mut port, 6);
+        assert_eq!(reads, [0x33, 0x33, 0x30, 0x33, 0x7C, 0x33]);
+
+        // A game that sends one extra pulse still finds the ID and buttons
+        // on a pad that answers one cycle later
+        let mut port = ControllerPort::new(ControllerType::SixButton);
+        port.state.x = true;
+        port.state.mode = true;
+        port.six_button_timing = SixButtonTiming::delayed(1).unwrap();
+        let reads = th_low_reads(&mut port, 6);
+        assert_eq!(reads, [0x33, 0x33, 0x33, 0x30, 0x33, 0x7C]);
+        assert_eq!(port.detected_type(), Some(ControllerType::SixButton));
+
+        // Timing is pad configuration, so a reset keeps it
+        port.reset(true);
+        assert_eq!(port.six_button_timing, SixButtonTiming::delayed(1).unwrap());
+    }
+
+    #[test]
+    fn test_io_sets_six_button_timing_per_port() {
+        let mut io = Io::new();
+        let late = SixButtonTiming::delayed(1).unwrap();
+        io.set_six_button_timing(2, late);
+        io.set_six_button_timing(3, late);
+        assert_eq!(io.port1.six_button_timing, SixButtonTiming::STANDARD);
+        assert_eq!(io.port2.six_button_timing, late);
+        assert_eq!(io.expansion.six_button_timing, late);
+    }
+
+    #[test]
+    fn test_six_button_timing_delay_stops_before_the_counter_wraps() {
+        assert_eq!(SixButtonTiming::delayed(0), Some(SixButtonTiming::STANDARD));
+        assert_eq!(
+            SixButtonTiming::delayed(2),
+            Some(SixButtonTiming {
+                id_cycle: 5,
+                extra_cycle: 7
+            })
+        );
+        assert_eq!(SixButtonTiming::delayed(3), None);
+        assert_eq!(SixButtonTiming::delayed(u8::MAX), None);
+    }
+
+    #[test]
+    fn test_turbo_a_alternates_at_configured_rate() {
+        let mut io = Io::new();
+        let mut buttons = ControllerState::new();
+        buttons.a = true;
+        // 15 presses a second at 60 fps: two frames down, two frames up
+        io.set_turbo(
+            1,
+            TurboConfig {
+                buttons,
+                rate_hz: 15,
+            },
+        );
+        io.port1.state.a = true;
+        io.port1.state.b = true;
+        io.write(0x03, 0x00); // TH low: A on bit 4
+
+        let mut a_reads = Vec::new();
+        for _ in 0..8 {
+            a_reads.push(io.read(0x03) & 0x10 == 0);
+            io.advance_turbo_frame(60);
+        }
+        assert_eq!(
+            a_reads,
+            [true, true, false, false, true, true, false, false]
+        );
+
+        io.write(0x03, 0x40);
+        assert_eq!(io.read(0x03) & 0x10, 0, "B has no turbo and stays held");
+    }
+
+    #[test]
+    fn test_turbo_only_applies_while_held() {
+        let mut port = ControllerPort::new(ControllerType::ThreeButton);
+        port.turbo = TurboConfig {
+            buttons: ControllerState {
+                c: true,
+                ..ControllerState::new()
+            },
+            rate_hz: 30,
+        };
+        for _ in 0..4 {
+            port.advance_turbo_frame(60);
+            assert!(!port.reported_state().c);
+        }
+        // Zero rate disables turbo, leaving the held button pressed
+        port.state.c = true;
+        port.turbo.rate_hz = 0;
+        for _ in 0..4 {
+            port.advance_turbo_frame(60);
+            assert!(port.reported_state().c);
+        }
+    }
+
     #[test]
     fn test_6button_timeout() {
         let mut port = ControllerPort::new(ControllerType::SixButton);
@@ -610,21 +972,21 @@ mod tests {
         assert_eq!(port.th_counter, 1);
         assert_eq!(port.th_timer, 0);
 
-        // Update with cycles <= 1500
+        // Update with cycles <= TH_TIMEOUT_CYCLES
         port.update(1000);
         assert_eq!(port.th_counter, 1);
         assert_eq!(port.th_timer, 1000);
 
-        // Multiple small updates summing to 1500
-        port.update(250);
-        port.update(250);
+        // Multiple small updates summing to TH_TIMEOUT_CYCLES
+        port.update(TH_TIMEOUT_CYCLES / 2 - 1000);
+        port.update(TH_TIMEOUT_CYCLES - TH_TIMEOUT_CYCLES / 2);
         assert_eq!(port.th_counter, 1);
-        assert_eq!(port.th_timer, 1500);
+        assert_eq!(port.th_timer, TH_TIMEOUT_CYCLES);
 
-        // Timeout: th_timer > 1500
+        // Timeout: th_timer > TH_TIMEOUT_CYCLES
         port.update(1);
         assert_eq!(port.th_counter, 0);
-        assert_eq!(port.th_timer, 1501);
+        assert_eq!(port.th_timer, TH_TIMEOUT_CYCLES + 1);
 
         // Verify write_data resets th_timer on falling edge
         port.write_data(0x40); // TH high
@@ -644,7 +1006,7 @@ mod tests {
         assert_eq!(port.th_timer, 0);
 
         // Verify timeout even if TH is high
-        port.update(1501);
+        port.update(TH_TIMEOUT_CYCLES + 1);
         assert_eq!(port.th_counter, 0);
     }
 
@@ -674,13 +1036,18 @@ mod tests {
         assert_eq!(io.port1.controller_type, ControllerType::SixButton);
         assert_eq!(io.port2.controller_type, ControllerType::None);
 
-        // Try invalid port (should do nothing)
-        io.set_controller_type(0, ControllerType::SixButton);
+        // Port 3 is the expansion port
         io.set_controller_type(3, ControllerType::SixButton);
+        assert_eq!(io.expansion.controller_type, ControllerType::SixButton);
+
+        // Try invalid port (should do nothing)
+        io.set_controller_type(0, ControllerType::ThreeButton);
+        io.set_controller_type(4, ControllerType::ThreeButton);
 
         // Verify no changes from invalid ports
         assert_eq!(io.port1.controller_type, ControllerType::SixButton);
         assert_eq!(io.port2.controller_type, ControllerType::None);
+        assert_eq!(io.expansion.controller_type, ControllerType::SixButton);
     }
 
     #[test]
@@ -692,20 +1059,19 @@ mod tests {
         assert_eq!(port.th_counter, 1);
         assert_eq!(port.th_timer, 0);
 
-        // Case 1: Exact boundary (1500 cycles)
-        // Add 1500 cycles (exactly the threshold)
-        port.update(1500);
-        assert_eq!(port.th_timer, 1500);
+        // Case 1: Exact boundary (exactly the threshold)
+        port.update(TH_TIMEOUT_CYCLES);
+        assert_eq!(port.th_timer, TH_TIMEOUT_CYCLES);
         assert_eq!(
             port.th_counter, 1,
-            "Counter should NOT reset at exactly 1500 cycles"
+            "Counter should NOT reset at exactly the timeout"
         );
 
-        // Case 2: Cross boundary (1501 cycles)
+        // Case 2: Cross boundary
         // Add 1 more cycle to exceed threshold
         port.update(1);
-        assert_eq!(port.th_timer, 1501);
-        assert_eq!(port.th_counter, 0, "Counter SHOULD reset at 1501 cycles");
+        assert_eq!(port.th_timer, TH_TIMEOUT_CYCLES + 1);
+        assert_eq!(port.th_counter, 0, "Counter SHOULD reset past the timeout");
 
         // Reset
         port.write_data(0x40); // TH high
@@ -713,15 +1079,24 @@ mod tests {
         assert_eq!(port.th_counter, 1);
         assert_eq!(port.th_timer, 0);
 
-        // Case 3: Large update (2000 cycles at once)
-        port.update(2000);
-        assert_eq!(port.th_timer, 2000);
+        // Case 3: Large update (well past the timeout at once)
+        port.update(TH_TIMEOUT_CYCLES * 2);
+        assert_eq!(port.th_timer, TH_TIMEOUT_CYCLES * 2);
         assert_eq!(
             port.th_counter, 0,
             "Counter SHOULD reset with single large update"
         );
     }
 
+    #[test]
+    fn test_6button_timer_saturates() {
+        let mut port = ControllerPort::new(ControllerType::SixButton);
+        port.th_timer = u32::MAX - 10;
+        port.update(100);
+        assert_eq!(port.th_timer, u32::MAX);
+        assert_eq!(port.th_counter, 0);
+    }
+
     #[test]
     fn test_non_6button_no_timeout() {
         let mut port = ControllerPort::new(ControllerType::ThreeButton);
@@ -780,6 +1155,73 @@ mod tests {
         assert_eq!(state.to_button_string(), "U...A..S....");
     }
 
+    #[test]
+    fn test_io_counts_data_port_polls() {
+        let mut io = Io::new();
+        io.count_polls(0xA10003, 1);
+        io.count_polls(0xA10002, 2); // One word read is one poll
+        io.count_polls(0xA10005, 1);
+        io.count_polls(0xA10009, 1); // Control register reads aren't polls
+        assert_eq!(io.poll_count(1), 2);
+        assert_eq!(io.poll_count(2), 1);
+        assert_eq!(io.poll_count(3), 0);
+        // A long read spans ports 1 and 2
+        io.count_polls(0xA10002, 4);
+        assert_eq!((io.poll_count(1), io.poll_count(2)), (3, 2));
+        // Reading the port itself doesn't count; the bus does
+        io.read(0xA10003);
+        assert_eq!(io.poll_count(1), 3);
+
+        io.reset_poll_counts();
+        assert_eq!(io.poll_count(1), 0);
+        assert_eq!(io.poll_count(2), 0);
+    }
+
+    /// Pulse TH through the I/O registers the way a detection routine does,
+    /// reading the port while TH is low
+    fn run_detection_handshake(io: &mut Io, port: u8) {
+        let data = 0xA10001 + port as u32 * 2;
+        io.write(data + 6, 0x40); // TH as output
+        for _ in 0..4 {
+            io.write(data, 0x40);
+            io.read(data);
+            io.write(data, 0x00);
+            io.read(data);
+        }
+    }
+
+    #[test]
+    fn test_6button_detected_on_every_port() {
+        for port in 1..=3 {
+            let mut io = Io::new();
+            io.set_controller_type(port, ControllerType::SixButton);
+            run_detection_handshake(&mut io, port);
+            let detected = match port {
+                1 => io.port1.detected_type(),
+                2 => io.port2.detected_type(),
+                _ => io.expansion.detected_type(),
+            };
+            assert_eq!(detected, Some(ControllerType::SixButton), "port {}", port);
+        }
+    }
+
+    #[test]
+    fn test_detected_type_for_3button_and_empty_ports() {
+        let mut io = Io::new();
+        io.set_controller_type(2, ControllerType::SixButton);
+        assert_eq!(io.port1.detected_type(), None, "No handshake yet");
+
+        run_detection_handshake(&mut io, 1);
+        run_detection_handshake(&mut io, 2);
+        run_detection_handshake(&mut io, 3);
+        assert_eq!(io.port1.detected_type(), Some(ControllerType::ThreeButton));
+        assert_eq!(io.port2.detected_type(), Some(ControllerType::SixButton));
+        assert_eq!(io.expansion.detected_type(), Some(ControllerType::None));
+
+        io.reset(false);
+        assert_eq!(io.port2.detected_type(), None);
+    }
+
     #[test]
     fn test_io_serialize() {
         let io = Io::new();
@@ -805,8 +1247,8 @@ mod tests {
         io.write(0xA10003, 0x00);
         assert_eq!(io.port1.th_counter, 1);
 
-        // Update IO with cycles > 1500
-        io.update(1501);
+        // Update IO with cycles > TH_TIMEOUT_CYCLES
+        io.update(TH_TIMEOUT_CYCLES + 1);
 
         // Should be reset
         assert_eq!(io.port1.th_counter, 0);
@@ -931,4 +1373,36 @@ mod tests {
         assert!(!state.mode);
         assert_eq!(state.to_button_string(), "U...........");
     }
+
+    #[test]
+    fn test_soft_reset_keeps_configuration() {
+        let mut io = Io::new();
+        io.set_controller_type(1, ControllerType::SixButton);
+        io.write(0xA10009, 0x40);
+        io.write(0xA10003, 0x40);
+        io.write(0xA10003, 0x00);
+        assert_eq!(io.port1.th_counter, 1);
+
+        io.reset(false);
+        assert_eq!(io.port1.controller_type, ControllerType::SixButton);
+        assert_eq!(io.port1.control, 0x40);
+        assert!(!io.port1.th_state);
+        assert_eq!(io.port1.th_counter, 0);
+    }
+
+    #[test]
+    fn test_hard_reset_clears_configuration() {
+        let mut io = Io::new();
+        io.set_controller_type(1, ControllerType::SixButton);
+        io.write(0xA10009, 0x40);
+        io.write(0xA10003, 0x40);
+        io.write(0xA10003, 0x00);
+
+        io.reset(true);
+        assert_eq!(io.port1.control, 0x00);
+        assert!(io.port1.th_state);
+        assert_eq!(io.port1.th_counter, 0);
+        // The pad is still plugged in
+        assert_eq!(io.port1.controller_type, ControllerType::SixButton);
+    }
 }
@@ -5,6 +5,7 @@
 
 pub mod apu;
 pub mod audio;
+pub mod cheats;
 pub mod cpu;
 pub mod debugger;
 pub mod frontend;
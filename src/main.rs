@@ -9,6 +9,7 @@ macro_rules! println_safe {
 }
 pub mod apu;
 pub mod audio;
+pub mod cheats;
 pub mod cpu;
 pub mod debugger;
 pub mod frontend;
@@ -17,6 +18,8 @@ pub mod gui;
 pub mod input;
 pub mod io;
 pub mod memory;
+#[cfg(test)]
+mod testing;
 #[cfg(all(feature = "gui", test))]
 pub mod tests_gui;
 pub mod vdp;
@@ -31,17 +34,19 @@ const MAX_STATE_SIZE: u64 = 25 * 1024 * 1024;
 /// Maximum SRAM size in bytes (2MB) to prevent OOM/DoS
 const MAX_SRAM_SIZE: u64 = 2 * 1024 * 1024;
 
-use crate::vdp::RenderOps;
 use apu::Apu;
-use cpu::Cpu;
+use cheats::CheatList;
+use cpu::{Cpu, InterruptSource};
 use debugger::{GdbMemory, GdbRegisters, GdbServer, StopReason};
-use frontend::InputMapping;
+use frontend::{InputMapping, KeyMap};
 use input::{InputManager, InputScript};
 use memory::bus::Bus;
+use memory::image::ProgramImage;
 use memory::{SharedBus, Z80Bus};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
+use vdp::Vdp;
 use z80::Z80;
 
 #[derive(Debug, Clone, Copy)]
@@ -49,6 +54,16 @@ struct Z80Change {
     new_req: bool,
     new_rst: bool,
 }
+/// User callbacks driven by the emulation loop, for scripted tests and
+/// instrumentation (read memory, assert, inject input) without forking the loop.
+pub trait ScriptHook {
+    /// Called at the start of every frame, after scripted/live input has been
+    /// applied, so the hook can inspect state and override controller input.
+    fn on_frame(&mut self, _emulator: &mut Emulator) {}
+    /// Called after every executed 68k instruction.
+    fn on_instruction(&mut self, _cpu: &mut Cpu) {}
+}
+
 struct SystemContext<'a> {
     cpu: &'a mut Cpu,
     bus: &'a mut Bus,
@@ -57,8 +72,36 @@ struct SystemContext<'a> {
     z80_last_bus_req: &'a mut bool,
     z80_last_reset: &'a mut bool,
     z80_trace_count: &'a mut u32,
+    script_hooks: &'a mut [Box<dyn ScriptHook>],
     internal_frame_count: u64,
     debug: bool,
+    /// Where to charge section timings, when profiling is on
+    profile: Option<&'a mut FrameProfile>,
+}
+
+impl SystemContext<'_> {
+    /// Start timing a section if profiling is on
+    fn profile_start(&self) -> Option<std::time::Instant> {
+        self.profile.is_some().then(std::time::Instant::now)
+    }
+
+    /// Charge the time since `start` to the profile field `pick` selects
+    fn profile_stop(
+        &mut self,
+        start: Option<std::time::Instant>,
+        pick: fn(&mut FrameProfile) -> &mut std::time::Duration,
+    ) {
+        if let (Some(profile), Some(start)) = (self.profile.as_deref_mut(), start) {
+            *pick(profile) += start.elapsed();
+        }
+    }
+
+    /// Advance the bus, charging the time to the VDP
+    fn tick_bus(&mut self, mclk: u32) {
+        let start = self.profile_start();
+        self.bus.tick(mclk);
+        self.profile_stop(start, |p| &mut p.vdp);
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -89,6 +132,135 @@ mod shared_bus_serde {
     }
 }
 
+/// Elapsed work per component, for profiling and timing checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CycleSnapshot {
+    /// 68k clock cycles
+    pub m68k: u64,
+    /// Z80 T-states
+    pub z80: u64,
+    /// VDP scanlines stepped
+    pub vdp_lines: u64,
+    /// Audio sample pairs generated
+    pub apu_samples: u64,
+}
+
+/// Wall-clock time each subsystem took over one frame, from
+/// `Emulator::last_frame_profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameProfile {
+    pub m68k: std::time::Duration,
+    pub z80: std::time::Duration,
+    /// Line rendering plus FIFO and DMA work in the access slots
+    pub vdp: std::time::Duration,
+    /// Sound chip stepping and sample generation
+    pub apu: std::time::Duration,
+}
+
+impl FrameProfile {
+    /// Time charged to everything but the 68k
+    fn non_cpu(&self) -> std::time::Duration {
+        self.z80 + self.vdp + self.apu
+    }
+}
+
+/// FNV-1a over everything written to it. Unlike `DefaultHasher` the result
+/// is fixed across Rust versions and platforms.
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        Self(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl std::io::Write for StateHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A rendered frame together with exactly the audio that belongs to it
+#[derive(Debug, Clone)]
+pub struct RecordedFrame {
+    /// RGB565, 320 pixels per line
+    pub framebuffer: Vec<u16>,
+    /// Interleaved stereo samples
+    pub audio: Vec<i16>,
+}
+
+/// Record mode state: audio generated but not yet handed out with a frame
+#[derive(Debug, Default)]
+struct AvRecorder {
+    pending_audio: Vec<i16>,
+    frames: u64,
+    /// The bus's audio phase when recording started
+    start_phase: u64,
+}
+
+impl AvRecorder {
+    /// Master clocks in one frame of real hardware (3420 per line)
+    fn frame_mclk(is_pal: bool) -> u64 {
+        3420 * if is_pal { 313 } else { 262 }
+    }
+
+    /// Sample pairs owed to the next frame. Counting from the start of the
+    /// recording keeps the fractional remainder from accumulating drift, and
+    /// the bus carries the same remainder line by line, so this is what the
+    /// core generates.
+    fn samples_for_next_frame(&self, sample_rate: u32, is_pal: bool) -> usize {
+        let mclk = if is_pal {
+            audio::PAL_MCLK
+        } else {
+            audio::NTSC_MCLK
+        } as u64;
+        let per_frame = sample_rate as u64 * Self::frame_mclk(is_pal);
+        let total = |frames: u64| (self.start_phase + frames * per_frame) / mclk;
+        (total(self.frames + 1) - total(self.frames)) as usize
+    }
+
+    /// Cut exactly `pairs` sample pairs for a frame. The core should have
+    /// generated just that; if it didn't (recording started mid-frame, or
+    /// the region or rate changed), pad with the last sample or drop the
+    /// surplus so audio can't drift from the video, and warn.
+    fn take_audio(&mut self, pairs: usize) -> Vec<i16> {
+        let len = pairs * 2;
+        if self.pending_audio.len() != len {
+            log::warn!(
+                "Record mode: frame {} has {} audio samples, expected {}; {}",
+                self.frames,
+                self.pending_audio.len() / 2,
+                pairs,
+                if self.pending_audio.len() < len {
+                    "padding"
+                } else {
+                    "dropping the surplus"
+                }
+            );
+        }
+        if self.pending_audio.len() < len {
+            let last = match self.pending_audio.len() {
+                0 => [0, 0],
+                n => [self.pending_audio[n - 2], self.pending_audio[n - 1]],
+            };
+            while self.pending_audio.len() < len {
+                self.pending_audio.extend_from_slice(&last);
+            }
+        }
+        let audio: Vec<i16> = self.pending_audio.drain(..len).collect();
+        self.pending_audio.clear();
+        self.frames += 1;
+        audio
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Emulator {
     pub cpu: Cpu,
@@ -101,10 +273,18 @@ pub struct Emulator {
     #[serde(skip)]
     pub wav_writer: Option<wav_writer::FileWavWriter>,
     pub internal_frame_count: u64,
+    #[serde(default)]
+    pub scanline_count: u64,
+    /// Next scanline to run in the current frame; 0 between frames
+    #[serde(default)]
+    frame_line: u16,
     pub z80_last_bus_req: bool,
     pub z80_last_reset: bool,
     pub z80_trace_count: u32,
     pub input_mapping: InputMapping,
+    /// Custom player 1 key bindings, used instead of `input_mapping`
+    #[serde(default)]
+    pub key_map: Option<KeyMap>,
     pub debug: bool,
     pub paused: bool,
     pub single_step: bool,
@@ -113,6 +293,27 @@ pub struct Emulator {
     pub allowed_paths: Vec<std::path::PathBuf>,
     pub current_rom_path: Option<std::path::PathBuf>,
     pub z80_cycle_debt: f32,
+    #[serde(skip)]
+    script_hooks: Vec<Box<dyn ScriptHook>>,
+    #[serde(skip)]
+    scheduled_inputs: std::collections::BTreeMap<u64, input::FrameInput>,
+    #[serde(skip)]
+    recorder: Option<AvRecorder>,
+    /// Codes for the loaded ROM, kept applied as ROM patches on the bus
+    #[serde(skip)]
+    cheats: CheatList,
+    /// Where to save a screenshot each time a GDB breakpoint stops the CPU
+    #[serde(skip)]
+    breakpoint_screenshot_dir: Option<std::path::PathBuf>,
+    /// Whether frames are timed per subsystem; off by default as the
+    /// timers cost a little on every sync
+    #[serde(skip)]
+    profiling: bool,
+    /// Timings accumulated so far in the current frame
+    #[serde(skip)]
+    frame_profile: FrameProfile,
+    #[serde(skip)]
+    last_frame_profile: Option<FrameProfile>,
 }
 impl Default for Emulator {
     fn default() -> Self {
@@ -139,10 +340,13 @@ impl Emulator {
             audio_buffer: Vec::with_capacity(crate::audio::samples_per_frame() * 2),
             wav_writer: None,
             internal_frame_count: 0,
+            scanline_count: 0,
+            frame_line: 0,
             z80_last_bus_req: false,
             z80_last_reset: true,
             z80_trace_count: 0,
             input_mapping: InputMapping::default(),
+            key_map: None,
             debug: false,
             paused: false,
             single_step: false,
@@ -150,6 +354,14 @@ impl Emulator {
             current_rom_path: None,
             allowed_paths: Vec::new(),
             z80_cycle_debt: 0.0,
+            script_hooks: Vec::new(),
+            scheduled_inputs: std::collections::BTreeMap::new(),
+            recorder: None,
+            cheats: CheatList::new(),
+            breakpoint_screenshot_dir: None,
+            profiling: false,
+            frame_profile: FrameProfile::default(),
+            last_frame_profile: None,
         };
         {
             let mut bus = emulator.bus.borrow_mut();
@@ -159,6 +371,17 @@ impl Emulator {
         emulator
     }
 
+    /// Soft reset, as from the console reset button: resets the CPUs and
+    /// transient controller state but keeps RAM, VRAM and port configuration
+    pub fn soft_reset(&mut self) {
+        {
+            let mut bus = self.bus.borrow_mut();
+            bus.io.reset(false);
+            self.cpu.reset(&mut *bus);
+        }
+        self.z80.reset();
+    }
+
     /// Hard reset of the emulator (clears RAM, VRAM, resets CPUs, keeps ROM)
     pub fn hard_reset(&mut self) {
         {
@@ -168,6 +391,7 @@ impl Emulator {
         }
         self.z80.reset();
         self.internal_frame_count = 0;
+        self.frame_line = 0;
     }
 
     /// Close current ROM and return to default state
@@ -176,13 +400,121 @@ impl Emulator {
 
         let allowed_paths = self.allowed_paths.clone();
         let mapping = self.input_mapping;
+        let key_map = self.key_map.take();
         let sample_rate = self.bus.borrow().sample_rate;
+        let border_width = self.bus.borrow().vdp.border_width();
+        let script_hooks = std::mem::take(&mut self.script_hooks);
 
         *self = Self::new();
 
         self.allowed_paths = allowed_paths;
         self.input_mapping = mapping;
+        self.key_map = key_map;
         self.bus.borrow_mut().sample_rate = sample_rate;
+        self.bus.borrow_mut().vdp.set_border_width(border_width);
+        self.script_hooks = script_hooks;
+    }
+
+    /// Register a hook called every frame and every 68k instruction.
+    /// With no hooks registered the loop skips the callbacks entirely.
+    pub fn add_script_hook(&mut self, hook: Box<dyn ScriptHook>) {
+        self.script_hooks.push(hook);
+    }
+
+    /// Queue `input` to be applied when frame `frame` runs.
+    /// Frames are counted by `internal_frame_count`; a scheduled input
+    /// overrides both the input script and any input passed to `step_frame`
+    /// for that frame only.
+    pub fn schedule_input(&mut self, frame: u64, input: input::FrameInput) {
+        self.scheduled_inputs.insert(frame, input);
+    }
+
+    /// Replace the cheat list and apply its enabled codes to the ROM
+    pub fn set_cheats(&mut self, cheats: CheatList) {
+        self.cheats = cheats;
+        self.apply_cheats();
+    }
+
+    pub fn cheats(&self) -> &CheatList {
+        &self.cheats
+    }
+
+    /// Load a cheat list saved with `save_cheats` and apply it
+    pub fn load_cheats<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<(), String> {
+        self.set_cheats(CheatList::load(path)?);
+        Ok(())
+    }
+
+    pub fn save_cheats<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), String> {
+        self.cheats.save(path)
+    }
+
+    /// Re-register the cheat list with the bus: undo any previous patches,
+    /// then patch the ROM for each enabled code
+    fn apply_cheats(&mut self) {
+        let mut bus = self.bus.borrow_mut();
+        bus.clear_rom_patches();
+        for (addr, value) in self.cheats.active_patches() {
+            bus.apply_rom_patch(addr, value);
+        }
+        drop(bus);
+        // Patched words may already be in the decode cache
+        self.cpu.invalidate_cache();
+    }
+
+    /// Enable or disable record mode. While enabled, `step_frame_recorded`
+    /// hands out each frame with exactly the audio for the region and sample
+    /// rate, so a muxer gets aligned A/V.
+    pub fn set_record_mode(&mut self, enabled: bool) {
+        let start_phase = self.bus.borrow().audio_phase();
+        self.recorder = enabled.then(|| AvRecorder {
+            start_phase,
+            ..AvRecorder::default()
+        });
+    }
+
+    pub fn record_mode(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Step one frame and return it with its audio. Returns `None` outside
+    /// record mode or when no frame ran (paused).
+    pub fn step_frame_recorded(
+        &mut self,
+        input: Option<&input::FrameInput>,
+    ) -> Option<RecordedFrame> {
+        self.recorder.as_ref()?;
+        let start_frame = self.internal_frame_count;
+        self.step_frame(input);
+        if self.internal_frame_count == start_frame {
+            return None;
+        }
+
+        let bus = self.bus.borrow();
+        let recorder = self.recorder.as_mut()?;
+        let pairs = recorder.samples_for_next_frame(bus.sample_rate, bus.vdp.is_pal);
+        Some(RecordedFrame {
+            framebuffer: bus.vdp.front_buffer().to_vec(),
+            audio: recorder.take_audio(pairs),
+        })
+    }
+
+    /// Remove all registered script hooks
+    pub fn clear_script_hooks(&mut self) {
+        self.script_hooks.clear();
+    }
+
+    fn run_frame_hooks(&mut self) {
+        if self.script_hooks.is_empty() {
+            return;
+        }
+        let mut hooks = std::mem::take(&mut self.script_hooks);
+        for hook in hooks.iter_mut() {
+            hook.on_frame(self);
+        }
+        // Keep any hooks registered from within a callback
+        hooks.append(&mut self.script_hooks);
+        self.script_hooks = hooks;
     }
 
     pub fn load_sram(&mut self) {
@@ -313,9 +645,14 @@ impl Emulator {
             Ok(new_emulator) => {
                 // 1. Preserve critical session state
                 let gdb = self.gdb.take();
+                let script_hooks = std::mem::take(&mut self.script_hooks);
                 let allowed_paths = self.allowed_paths.clone();
                 let current_rom_path = self.current_rom_path.clone();
                 let sample_rate = self.bus.borrow().sample_rate;
+                let border_width = self.bus.borrow().vdp.border_width();
+                let recorder = self.recorder.take();
+                let cheats = std::mem::take(&mut self.cheats);
+                let z80_contention = self.z80.memory.contention_penalty();
 
                 // 2. Load ROM data into the new emulator's bus
                 if let Some(ref rom_path) = current_rom_path {
@@ -330,9 +667,14 @@ impl Emulator {
 
                 // 4. Restore critical session state
                 self.gdb = gdb;
+                self.script_hooks = script_hooks;
                 self.allowed_paths = allowed_paths;
                 self.current_rom_path = current_rom_path;
                 self.bus.borrow_mut().sample_rate = sample_rate;
+                self.bus.borrow_mut().vdp.set_border_width(border_width);
+                self.z80.memory.set_contention_penalty(z80_contention);
+                self.recorder = recorder;
+                self.set_cheats(cheats);
 
                 println!("Loaded state from {:?}", state_path);
             }
@@ -349,6 +691,25 @@ impl Emulator {
         Ok(())
     }
 
+    /// Load a program image instead of a cartridge ROM, starting at its
+    /// entry point rather than the reset vector. The stack comes from vector
+    /// 0 if the image provides one, else it starts at the top of work RAM.
+    pub fn load_image(&mut self, image: &ProgramImage) -> Result<(), String> {
+        let mut bus = self.bus.borrow_mut();
+        bus.load_image(image)?;
+        self.cheats = CheatList::new();
+        self.current_rom_path = None;
+
+        self.cpu.reset(&mut *bus);
+        self.z80.reset();
+        self.cpu.pc = image.entry;
+        if self.cpu.ssp == 0 {
+            self.cpu.ssp = 0x0100_0000;
+            self.cpu.a[7] = self.cpu.ssp;
+        }
+        Ok(())
+    }
+
     pub fn load_rom(&mut self, path: &str) -> std::io::Result<()> {
         // Security: Validate path against whitelist
         let path_obj = std::path::Path::new(path);
@@ -385,6 +746,8 @@ impl Emulator {
         let mut bus = self.bus.borrow_mut();
         bus.load_rom(&data);
         drop(bus);
+        // Codes are specific to a game
+        self.cheats = CheatList::new();
 
         self.current_rom_path = Some(canonical_path);
         self.load_sram();
@@ -469,9 +832,10 @@ impl Emulator {
         // Reset single_step if it was set
         self.single_step = false;
 
-        // Apply inputs from script or live input
+        // Apply inputs from the schedule, script or live input
+        let scheduled = self.scheduled_inputs.remove(&self.internal_frame_count);
         let (p1, p2, command) = {
-            let frame_input = match input {
+            let frame_input = match scheduled.as_ref().or(input) {
                 Some(i) => {
                     self.input.record((*i).clone());
                     std::borrow::Cow::Borrowed(i)
@@ -496,6 +860,7 @@ impl Emulator {
             self.execute_script_command(cmd);
         }
 
+        self.run_frame_hooks();
         self.step_frame_internal();
     }
 
@@ -551,38 +916,35 @@ impl Emulator {
 
     fn handle_byte_cmd(&self, cmd: &str, parts: &[&str]) {
         match cmd {
-            "READ_BYTE" => {
-                if parts.len() > 1 {
-                    if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
-                        let val = self.bus.borrow_mut().read_byte(addr);
-                        println!("Script: READ_BYTE 0x{:06X} = 0x{:02X}", addr, val);
-                    }
+            "READ_BYTE" if parts.len() > 1 => {
+                if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
+                    let val = self.bus.borrow_mut().read_byte(addr);
+                    println!("Script: READ_BYTE 0x{:06X} = 0x{:02X}", addr, val);
                 }
             }
-            "WRITE_BYTE" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u8::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
-                        self.bus.borrow_mut().write_byte(addr, val);
-                        println!("Script: WRITE_BYTE 0x{:06X} = 0x{:02X}", addr, val);
-                    }
+            "WRITE_BYTE" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u8::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
+                    self.bus.borrow_mut().write_byte(addr, val);
+                    println!("Script: WRITE_BYTE 0x{:06X} = 0x{:02X}", addr, val);
                 }
             }
-            "ASSERT_BYTE" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u8::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
-                        let actual = self.bus.borrow_mut().read_byte(addr);
-                        if actual != expected {
-                            panic!("Script Assertion Failed: [0x{:06X}] == 0x{:02X} (Expected 0x{:02X})", addr, actual, expected);
-                        }
-                        println!(
-                            "Script: ASSERT_BYTE 0x{:06X} == 0x{:02X} OK",
-                            addr, expected
+            "ASSERT_BYTE" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u8::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
+                    let actual = self.bus.borrow_mut().read_byte(addr);
+                    if actual != expected {
+                        panic!(
+                            "Script Assertion Failed: [0x{:06X}] == 0x{:02X} (Expected 0x{:02X})",
+                            addr, actual, expected
                         );
                     }
+                    println!(
+                        "Script: ASSERT_BYTE 0x{:06X} == 0x{:02X} OK",
+                        addr, expected
+                    );
                 }
             }
             _ => {}
@@ -591,38 +953,35 @@ impl Emulator {
 
     fn handle_word_cmd(&self, cmd: &str, parts: &[&str]) {
         match cmd {
-            "READ_WORD" => {
-                if parts.len() > 1 {
-                    if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
-                        let val = self.bus.borrow_mut().read_word(addr);
-                        println!("Script: READ_WORD 0x{:06X} = 0x{:04X}", addr, val);
-                    }
+            "READ_WORD" if parts.len() > 1 => {
+                if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
+                    let val = self.bus.borrow_mut().read_word(addr);
+                    println!("Script: READ_WORD 0x{:06X} = 0x{:04X}", addr, val);
                 }
             }
-            "WRITE_WORD" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u16::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
-                        self.bus.borrow_mut().write_word(addr, val);
-                        println!("Script: WRITE_WORD 0x{:06X} = 0x{:04X}", addr, val);
-                    }
+            "WRITE_WORD" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u16::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
+                    self.bus.borrow_mut().write_word(addr, val);
+                    println!("Script: WRITE_WORD 0x{:06X} = 0x{:04X}", addr, val);
                 }
             }
-            "ASSERT_WORD" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u16::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
-                        let actual = self.bus.borrow_mut().read_word(addr);
-                        if actual != expected {
-                            panic!("Script Assertion Failed: [0x{:06X}] == 0x{:04X} (Expected 0x{:04X})", addr, actual, expected);
-                        }
-                        println!(
-                            "Script: ASSERT_WORD 0x{:06X} == 0x{:04X} OK",
-                            addr, expected
+            "ASSERT_WORD" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u16::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
+                    let actual = self.bus.borrow_mut().read_word(addr);
+                    if actual != expected {
+                        panic!(
+                            "Script Assertion Failed: [0x{:06X}] == 0x{:04X} (Expected 0x{:04X})",
+                            addr, actual, expected
                         );
                     }
+                    println!(
+                        "Script: ASSERT_WORD 0x{:06X} == 0x{:04X} OK",
+                        addr, expected
+                    );
                 }
             }
             _ => {}
@@ -631,38 +990,35 @@ impl Emulator {
 
     fn handle_long_cmd(&self, cmd: &str, parts: &[&str]) {
         match cmd {
-            "READ_LONG" => {
-                if parts.len() > 1 {
-                    if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
-                        let val = self.bus.borrow_mut().read_long(addr);
-                        println!("Script: READ_LONG 0x{:06X} = 0x{:08X}", addr, val);
-                    }
+            "READ_LONG" if parts.len() > 1 => {
+                if let Ok(addr) = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16) {
+                    let val = self.bus.borrow_mut().read_long(addr);
+                    println!("Script: READ_LONG 0x{:06X} = 0x{:08X}", addr, val);
                 }
             }
-            "WRITE_LONG" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
-                        self.bus.borrow_mut().write_long(addr, val);
-                        println!("Script: WRITE_LONG 0x{:06X} = 0x{:08X}", addr, val);
-                    }
+            "WRITE_LONG" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(val)) = (addr_res, val_res) {
+                    self.bus.borrow_mut().write_long(addr, val);
+                    println!("Script: WRITE_LONG 0x{:06X} = 0x{:08X}", addr, val);
                 }
             }
-            "ASSERT_LONG" => {
-                if parts.len() > 2 {
-                    let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
-                    let val_res = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16);
-                    if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
-                        let actual = self.bus.borrow_mut().read_long(addr);
-                        if actual != expected {
-                            panic!("Script Assertion Failed: [0x{:06X}] == 0x{:08X} (Expected 0x{:08X})", addr, actual, expected);
-                        }
-                        println!(
-                            "Script: ASSERT_LONG 0x{:06X} == 0x{:08X} OK",
-                            addr, expected
+            "ASSERT_LONG" if parts.len() > 2 => {
+                let addr_res = u32::from_str_radix(parts[1].trim_start_matches("0x"), 16);
+                let val_res = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16);
+                if let (Ok(addr), Ok(expected)) = (addr_res, val_res) {
+                    let actual = self.bus.borrow_mut().read_long(addr);
+                    if actual != expected {
+                        panic!(
+                            "Script Assertion Failed: [0x{:06X}] == 0x{:08X} (Expected 0x{:08X})",
+                            addr, actual, expected
                         );
                     }
+                    println!(
+                        "Script: ASSERT_LONG 0x{:06X} == 0x{:08X} OK",
+                        addr, expected
+                    );
                 }
             }
             _ => {}
@@ -674,39 +1030,161 @@ impl Emulator {
             println!("Script LOG: {}", parts[1..].join(" "));
         }
     }
+    /// Run the rest of the current frame, or a whole frame between frames
     pub fn step_frame_internal(&mut self) {
+        while !self.step_frame_line() {}
+    }
+
+    /// Run until the VDP enters vertical blanking, the safe window for VRAM
+    /// updates. Stops after the scanline where the VBlank flag rises, leaving
+    /// the frame part-way through; the next `step_frame` finishes it. If the
+    /// VDP is already in VBlank this waits for the next one.
+    pub fn run_to_vblank(&mut self) {
+        let in_vblank =
+            |emulator: &Self| emulator.bus.borrow().vdp.status & vdp::constants::STATUS_VBLANK != 0;
+        // At most two frames of the longest (PAL, 313-line) length always
+        // cover a VBlank
+        for _ in 0..313 * 2 {
+            let was_in_vblank = in_vblank(self);
+            self.step_frame_line();
+            if !was_in_vblank && in_vblank(self) {
+                return;
+            }
+        }
+    }
+
+    /// Run the next scanline of the frame, starting a new frame if none is
+    /// in progress. Returns true once the frame's last line has run.
+    fn step_frame_line(&mut self) -> bool {
         let (lines, active_lines) = {
-            let bus = self.bus.borrow();
+            let mut bus = self.bus.borrow_mut();
+            if self.frame_line == 0 {
+                bus.io.reset_poll_counts();
+            }
             if bus.vdp.is_pal {
                 (313, 240)
             } else {
                 (262, 224)
             }
         };
-        let samples_per_line = audio::samples_per_frame() as f32 / lines as f32;
-
-        for line in 0..lines {
-            self.step_scanline(line, active_lines, samples_per_line);
+        self.step_scanline(self.frame_line, active_lines);
+        // The last active line is drawn, so the frame is complete as VBlank
+        // starts
+        {
+            let mut bus = self.bus.borrow_mut();
+            if self.frame_line + 1 == bus.vdp.screen_height() {
+                bus.vdp.present();
+            }
+        }
+        self.frame_line += 1;
+        if self.frame_line < lines {
+            return false;
         }
+
+        self.frame_line = 0;
         self.internal_frame_count += 1;
         if self.debug && self.internal_frame_count.is_multiple_of(60) {
             self.log_debug(self.internal_frame_count);
         }
 
-        self.generate_audio_samples(samples_per_line);
-        self.bus.borrow_mut().vdp.update_v30_offset();
+        self.generate_audio_samples();
+        if self.profiling {
+            self.last_frame_profile = Some(std::mem::take(&mut self.frame_profile));
+        }
+        let mut bus = self.bus.borrow_mut();
+        bus.vdp.update_v30_offset();
+        let frames_per_second = if bus.vdp.is_pal { 50 } else { 60 };
+        bus.io.advance_turbo_frame(frames_per_second);
+        true
     }
-    fn step_scanline(&mut self, line: u16, active_lines: u16, _samples_per_line: f32) {
+    fn step_scanline(&mut self, line: u16, active_lines: u16) {
         self.vdp_scanline_setup(line, active_lines);
         self.run_cpu_loop(line, active_lines);
         self.handle_interrupts();
+        self.scanline_count += 1;
+    }
+
+    /// Digest of the whole machine: 68k, Z80, the bus with everything on it
+    /// (work RAM, VDP memory and registers, sound chips, I/O), the ROM as
+    /// patched with its record of patches, and the scheduler's position:
+    /// the line reached in the current frame, Z80 cycle debt and the last
+    /// seen Z80 bus request and reset lines. Runs that hash equal are in
+    /// the same state. Frontend settings that aren't saved in states, such
+    /// as CRAM dots or the border, don't contribute.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        let bus = self.bus.borrow();
+        let scheduler = (
+            self.frame_line,
+            self.z80_cycle_debt.to_bits(),
+            self.z80_last_bus_req,
+            self.z80_last_reset,
+        );
+        serde_json::to_writer(&mut hasher, &self.cpu).expect("68k state serializes");
+        serde_json::to_writer(&mut hasher, &self.z80).expect("Z80 state serializes");
+        serde_json::to_writer(&mut hasher, &*bus).expect("bus state serializes");
+        serde_json::to_writer(&mut hasher, bus.rom_patches()).expect("ROM patches serialize");
+        serde_json::to_writer(&mut hasher, &scheduler).expect("scheduler state serializes");
+        std::io::Write::write_all(&mut hasher, &bus.rom).expect("hashing can't fail");
+        hasher.0
+    }
+
+    /// Turn per-subsystem frame timing on or off. Either way the profile
+    /// starts again from the next frame.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+        self.frame_profile = FrameProfile::default();
+        self.last_frame_profile = None;
+    }
+
+    /// Timings of the last complete frame run with profiling on
+    pub fn last_frame_profile(&self) -> Option<FrameProfile> {
+        self.last_frame_profile
+    }
+
+    /// Cycles and work units elapsed in each component since power-on
+    pub fn cycle_counts(&self) -> CycleSnapshot {
+        CycleSnapshot {
+            m68k: self.cpu.cycles,
+            z80: self.z80.cycles,
+            vdp_lines: self.scanline_count,
+            apu_samples: self.bus.borrow().apu.samples_generated,
+        }
     }
     fn vdp_scanline_setup(&mut self, line: u16, _active_lines: u16) {
         let mut bus = self.bus.borrow_mut();
 
         // Process scanline if within framebuffer bounds (320x240)
         if line < 240 {
-            bus.vdp.render_line(line);
+            let start = self.profiling.then(std::time::Instant::now);
+            bus.vdp.render_scanline(line);
+            if let Some(start) = start {
+                self.frame_profile.vdp += start.elapsed();
+            }
+        }
+    }
+
+    fn vdp_interrupt_pending(vdp: &Vdp, source: InterruptSource) -> bool {
+        match source {
+            InterruptSource::VBlank => vdp.vblank_pending(),
+            InterruptSource::HBlank => vdp.hint_pending(),
+            InterruptSource::External => vdp.ext_int_pending(),
+        }
+    }
+
+    /// Mirror the VDP's pending interrupt flags onto the CPU's IPL lines
+    fn sync_interrupt_lines(cpu: &mut Cpu, vdp: &Vdp) {
+        for source in InterruptSource::ALL {
+            cpu.set_interrupt_line(source, Self::vdp_interrupt_pending(vdp, source));
+        }
+    }
+
+    /// Clear the VDP flag behind the interrupt the CPU just took
+    fn acknowledge_vdp_interrupt(vdp: &mut Vdp, source: InterruptSource) {
+        match source {
+            InterruptSource::VBlank => vdp.acknowledge_vint(),
+            InterruptSource::HBlank => vdp.acknowledge_hint(),
+            InterruptSource::External => vdp.acknowledge_ext_int(),
         }
     }
 
@@ -717,7 +1195,7 @@ impl Emulator {
 
         let mclk = m68k_cycles * 7;
 
-        let (z80_can_run, z80_is_reset, cycles_per_sample) = {
+        let (z80_can_run, z80_is_reset) = {
             let prev = *ctx.z80_last_bus_req;
             if ctx.debug && ctx.bus.z80_bus_request != prev {
                 log::debug!(
@@ -731,13 +1209,7 @@ impl Emulator {
 
             let z80_can_run = !ctx.bus.z80_reset && !ctx.bus.z80_bus_request;
             let z80_is_reset = ctx.bus.z80_reset;
-            let master_clock = if ctx.bus.vdp.is_pal {
-                audio::PAL_MCLK
-            } else {
-                audio::NTSC_MCLK
-            };
-            let cycles_per_sample = master_clock as f32 / (ctx.bus.sample_rate as f32);
-            (z80_can_run, z80_is_reset, cycles_per_sample)
+            (z80_can_run, z80_is_reset)
         };
 
         if z80_is_reset && !*ctx.z80_last_reset {
@@ -754,38 +1226,43 @@ impl Emulator {
             ctx.z80.debug = false;
         }
 
-        if trigger_vint && !z80_is_reset {
-            ctx.z80.trigger_interrupt(0xFF);
+        let deliver_vint = trigger_vint && !z80_is_reset;
+        if z80_can_run || deliver_vint {
+            // Bound for the interrupt too: taking it pushes PC onto the
+            // Z80's stack, and the caller holds the shared bus
+            unsafe {
+                ctx.z80.memory.bind_bus(ctx.bus);
+                ctx.z80.io.bind_bus(ctx.bus);
+            }
+            if deliver_vint {
+                ctx.z80.trigger_interrupt(0xFF);
+            }
         }
 
         if z80_can_run {
             const Z80_CYCLES_PER_M68K_CYCLE: f32 = 3579545.0 / 7670453.0;
             *ctx.z80_cycle_debt += m68k_cycles as f32 * Z80_CYCLES_PER_M68K_CYCLE;
-            unsafe {
-                ctx.z80.memory.bind_bus(ctx.bus);
-                ctx.z80.io.bind_bus(ctx.bus);
-            }
+            // A 68k-to-VRAM DMA holds the 68k bus, stalling banked accesses
+            ctx.z80.memory.set_bus_contended(ctx.bus.dma_active());
 
+            // A halted Z80 still steps, so it stays in time with the 68k
+            let start = ctx.profile_start();
             while *ctx.z80_cycle_debt >= 1.0 {
                 let cycles = ctx.z80.step();
                 *ctx.z80_cycle_debt -= cycles as f32;
             }
+            ctx.profile_stop(start, |p| &mut p.z80);
+        }
 
+        if z80_can_run || deliver_vint {
             ctx.z80.memory.unbind_bus();
             ctx.z80.io.unbind_bus();
         }
 
+        let start = ctx.profile_start();
         ctx.bus.apu.tick_cycles(m68k_cycles);
-        ctx.bus.audio_accumulator += mclk as f32;
-
-        while ctx.bus.audio_accumulator >= cycles_per_sample {
-            let (l, r) = ctx.bus.apu.generate_sample();
-            if ctx.bus.audio_buffer.len() < 32768 {
-                ctx.bus.audio_buffer.push(l);
-                ctx.bus.audio_buffer.push(r);
-            }
-            ctx.bus.audio_accumulator -= cycles_per_sample;
-        }
+        ctx.bus.advance_audio(mclk);
+        ctx.profile_stop(start, |p| &mut p.apu);
     }
 
     fn run_cpu_batch_static(
@@ -804,17 +1281,8 @@ impl Emulator {
                 // Final sync for the batch
                 let trigger_vint = line == active_lines && pending_cycles < 10;
                 if deferred_bus_cycles > 0 {
-                    ctx.bus.tick(deferred_bus_cycles * 7);
-                    if ctx.bus.vdp.vblank_pending() {
-                        ctx.cpu.request_interrupt(6);
-                    } else {
-                        ctx.cpu.cancel_interrupt(6);
-                    }
-                    if ctx.bus.vdp.hint_pending() {
-                        ctx.cpu.request_interrupt(4);
-                    } else {
-                        ctx.cpu.cancel_interrupt(4);
-                    }
+                    ctx.tick_bus(deferred_bus_cycles * 7);
+                    Self::sync_interrupt_lines(ctx.cpu, &ctx.bus.vdp);
                 }
                 Self::sync_audio_z80(ctx, deferred_audio_cycles, trigger_vint);
                 return CpuBatchResult {
@@ -826,13 +1294,15 @@ impl Emulator {
             let m68k_cycles = if ctx.bus.dma_active() {
                 2 // Yield 2 cycles to let the bus step during DMA
             } else {
-                ctx.cpu.step_instruction(ctx.bus)
+                let cycles = ctx.cpu.step_instruction(ctx.bus);
+                for hook in ctx.script_hooks.iter_mut() {
+                    hook.on_instruction(ctx.cpu);
+                }
+                cycles
             };
 
-            match ctx.cpu.last_interrupt_level {
-                6 => ctx.bus.vdp.acknowledge_vint(),
-                4 => ctx.bus.vdp.acknowledge_hint(),
-                _ => {}
+            if let Some(source) = InterruptSource::from_level(ctx.cpu.last_interrupt_level) {
+                Self::acknowledge_vdp_interrupt(&mut ctx.bus.vdp, source);
             }
 
             deferred_bus_cycles += m68k_cycles;
@@ -840,27 +1310,24 @@ impl Emulator {
 
             let trigger_vint = line == active_lines && pending_cycles < 10;
             if deferred_bus_cycles >= Z80_AUDIO_SYNC_SLICE || trigger_vint || ctx.bus.dma_active() {
-                ctx.bus.tick(deferred_bus_cycles * 7);
-                if ctx.bus.vdp.vblank_pending() {
-                    ctx.cpu.request_interrupt(6);
-                } else {
-                    ctx.cpu.cancel_interrupt(6);
-                }
-                if ctx.bus.vdp.hint_pending() {
-                    ctx.cpu.request_interrupt(4);
-                } else {
-                    ctx.cpu.cancel_interrupt(4);
-                }
+                ctx.tick_bus(deferred_bus_cycles * 7);
+                Self::sync_interrupt_lines(ctx.cpu, &ctx.bus.vdp);
                 Self::sync_audio_z80(ctx, deferred_audio_cycles, trigger_vint);
                 deferred_bus_cycles = 0;
                 deferred_audio_cycles = 0;
+            } else if ctx.bus.vdp.cram_dots() {
+                // CRAM dots are placed from the VDP's line clock, which
+                // would otherwise lag the CPU by up to a whole slice
+                ctx.tick_bus(deferred_bus_cycles * 7);
+                Self::sync_interrupt_lines(ctx.cpu, &ctx.bus.vdp);
+                deferred_bus_cycles = 0;
             }
 
             // Check for Z80 state change (rare but needs early exit)
             let (req, rst) = (ctx.bus.z80_bus_request, ctx.bus.z80_reset);
             if req != initial_req || rst != initial_rst {
                 if deferred_bus_cycles > 0 {
-                    ctx.bus.tick(deferred_bus_cycles * 7);
+                    ctx.tick_bus(deferred_bus_cycles * 7);
                 }
                 if deferred_audio_cycles > 0 {
                     Self::sync_audio_z80(ctx, deferred_audio_cycles, false);
@@ -891,10 +1358,16 @@ impl Emulator {
             z80_last_bus_req: &mut self.z80_last_bus_req,
             z80_last_reset: &mut self.z80_last_reset,
             z80_trace_count: &mut self.z80_trace_count,
+            script_hooks: &mut self.script_hooks,
             internal_frame_count: self.internal_frame_count,
             debug: self.debug,
+            profile: self.profiling.then_some(&mut self.frame_profile),
         };
 
+        // The 68k gets whatever the line took beyond the other subsystems
+        let start = ctx.profile_start();
+        let others_before = ctx.profile.as_deref().map(FrameProfile::non_cpu);
+        ctx.bus.begin_audio_line();
         while cycles_scanline < CYCLES_PER_LINE {
             let remaining = CYCLES_PER_LINE - cycles_scanline;
             // Batch size of remaining line
@@ -907,8 +1380,17 @@ impl Emulator {
                 ctx.bus.z80_reset = change.new_rst;
             }
         }
+        let audio_start = ctx.profile_start();
+        ctx.bus.finish_audio_line();
+        ctx.profile_stop(audio_start, |p| &mut p.apu);
+        if let (Some(profile), Some(start), Some(before)) =
+            (ctx.profile.as_deref_mut(), start, others_before)
+        {
+            let others = profile.non_cpu() - before;
+            profile.m68k += start.elapsed().saturating_sub(others);
+        }
     }
-    fn generate_audio_samples(&mut self, _samples_per_line: f32) {
+    fn generate_audio_samples(&mut self) {
         let mut bus = self.bus.borrow_mut();
         if bus.audio_buffer.is_empty() {
             return;
@@ -916,6 +1398,9 @@ impl Emulator {
         if let Some(writer) = &mut self.wav_writer {
             let _ = writer.write_samples(&bus.audio_buffer);
         }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.pending_audio.extend_from_slice(&bus.audio_buffer);
+        }
         // Move samples to emulator buffer for frontend consumption
         if self.audio_buffer.len() < audio::samples_per_frame() * 2 {
             self.audio_buffer.extend(bus.audio_buffer.iter());
@@ -930,11 +1415,10 @@ impl Emulator {
         self.cpu.cancel_interrupt(4);
 
         let bus = self.bus.borrow_mut();
-        if bus.vdp.vblank_pending() {
-            self.cpu.request_interrupt(6);
-        }
-        if bus.vdp.hint_pending() {
-            self.cpu.request_interrupt(4);
+        for source in InterruptSource::ALL {
+            if Self::vdp_interrupt_pending(&bus.vdp, source) {
+                self.cpu.set_interrupt_line(source, true);
+            }
         }
         drop(bus);
 
@@ -1008,7 +1492,7 @@ impl Emulator {
 
     pub fn save_screenshot(&self, path: &str) -> Result<(), String> {
         let bus = self.bus.borrow();
-        let fb = &bus.vdp.framebuffer;
+        let fb = bus.vdp.front_buffer();
         let mut rgb_data = Vec::with_capacity(fb.len() * 3);
         for &pixel in fb {
             let r5 = ((pixel >> 11) & 0x1F) as u8;
@@ -1021,6 +1505,51 @@ impl Emulator {
         image::save_buffer(path, &rgb_data, 320, 240, image::ExtendedColorType::Rgb8)
             .map_err(|e| e.to_string())
     }
+    /// Save a screenshot into `dir` whenever a breakpoint is hit, or stop
+    /// doing so with `None`
+    pub fn set_breakpoint_screenshot_dir(&mut self, dir: Option<std::path::PathBuf>) {
+        self.breakpoint_screenshot_dir = dir;
+    }
+    /// Save `breakpoint_<pc>_<unix ms>.png` into the breakpoint screenshot
+    /// directory, if one is set
+    fn capture_breakpoint_screenshot(&self) {
+        let Some(dir) = &self.breakpoint_screenshot_dir else {
+            return;
+        };
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = dir.join(format!("breakpoint_{:06X}_{}.png", self.cpu.pc, millis));
+        let result = std::fs::create_dir_all(dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| self.save_screenshot(&path.to_string_lossy()));
+        match result {
+            Ok(()) => log::info!("Breakpoint screenshot saved to: {}", path.display()),
+            Err(e) => log::warn!("Failed to save breakpoint screenshot: {}", e),
+        }
+    }
+    /// Execute one instruction for the GDB loop, reporting a stop to the
+    /// client if it lands on a breakpoint or `stepping` is set
+    fn step_for_gdb(&mut self, stepping: bool) -> Option<StopReason> {
+        let mut bus = self.bus.borrow_mut();
+        self.cpu.step_instruction(&mut *bus);
+        drop(bus);
+        let gdb = self.gdb.as_mut()?;
+        let reason = if gdb.is_breakpoint(self.cpu.pc) {
+            StopReason::Breakpoint
+        } else if stepping {
+            StopReason::Step
+        } else {
+            return None;
+        };
+        gdb.stop_reason = reason;
+        gdb.send_packet(reason.signal_string()).ok();
+        if reason == StopReason::Breakpoint {
+            self.capture_breakpoint_screenshot();
+        }
+        Some(reason)
+    }
     /// Poll GDB for commands and update state
     pub fn poll_gdb(&mut self) {
         let Some(gdb) = &mut self.gdb else { return };
@@ -1067,7 +1596,6 @@ impl Emulator {
     pub fn run_with_gdb(&mut self, port: u16, password: Option<String>) -> std::io::Result<()> {
         let gdb = GdbServer::new(port, password.clone())?;
         self.gdb = Some(gdb);
-        let gdb = self.gdb.as_mut().unwrap();
         println!("Waiting for GDB connection on port {}...", port);
         if let Some(pwd) = password {
             println!(
@@ -1080,15 +1608,16 @@ impl Emulator {
             port
         );
         // Wait for connection
-        while !gdb.accept() {
+        while !self.gdb.as_mut().unwrap().accept() {
             std::thread::sleep(std::time::Duration::from_millis(100));
         }
         let mut stepping = false;
         let mut running = false;
-        let mut mem_access = BusGdbMemory { bus: &self.bus };
         loop {
+            let gdb = self.gdb.as_mut().unwrap();
             // Check for GDB commands
             if let Some(cmd) = gdb.receive_packet() {
+                let mut mem_access = BusGdbMemory { bus: &self.bus };
                 // Build register state from CPU
                 let mut regs = GdbRegisters {
                     d: self.cpu.d,
@@ -1117,20 +1646,9 @@ impl Emulator {
                     _ => {}
                 }
             }
-            // Execute if running
+            // Execute if running, until a breakpoint or the end of a step
             if running {
-                // Step one instruction
-                let mut bus = self.bus.borrow_mut();
-                self.cpu.step_instruction(&mut *bus);
-                drop(bus);
-                // Check for breakpoint
-                if gdb.is_breakpoint(self.cpu.pc) {
-                    gdb.stop_reason = StopReason::Breakpoint;
-                    gdb.send_packet(StopReason::Breakpoint.signal_string()).ok();
-                    running = false;
-                } else if stepping {
-                    gdb.stop_reason = StopReason::Step;
-                    gdb.send_packet(StopReason::Step.signal_string()).ok();
+                if self.step_for_gdb(stepping).is_some() {
                     running = false;
                 }
             } else {
@@ -1138,6 +1656,7 @@ impl Emulator {
                 std::thread::sleep(std::time::Duration::from_millis(10));
             }
             // Check if client disconnected
+            let gdb = self.gdb.as_mut().unwrap();
             if !gdb.is_connected() && !gdb.accept() {
                 println!("GDB client disconnected");
                 break;
@@ -1203,6 +1722,7 @@ fn print_usage() {
     println!("  --screenshot <path> Save screenshot after headless run");
     println!("  --gdb [port]     Start GDB server (default port: 1234)");
     println!("                   Note: Set GENTEEL_GDB_PASSWORD env var for custom password.");
+    println!("  --break-screenshots <dir> Save a screenshot to <dir> at each GDB breakpoint");
     println!("  --dump-audio <file> Dump audio output to WAV file");
     println!(
         "  --input-mapping <type> Set keyboard mapping (original|ergonomic, default: original)"
@@ -1251,6 +1771,7 @@ struct Config {
     screenshot_path: Option<String>,
     gdb_port: Option<u16>,
     gdb_password: Option<String>,
+    breakpoint_screenshot_dir: Option<String>,
     dump_audio_path: Option<String>,
     record_path: Option<String>,
     input_mapping: InputMapping,
@@ -1311,6 +1832,10 @@ impl Config {
                     }
                     config.gdb_port = Some(port);
                 }
+                "--break-screenshots" => {
+                    config.breakpoint_screenshot_dir = iter.next();
+                    current_opt = iter.next();
+                }
                 "--dump-audio" => {
                     config.dump_audio_path = iter.next();
                     current_opt = iter.next();
@@ -1351,7 +1876,8 @@ impl Config {
     }
 }
 fn main() {
-    env_logger::init();
+    // Diagnostics go through `log`; show warnings unless RUST_LOG says otherwise
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
     let config = Config::from_args(std::env::args());
     if config.show_help {
         print_usage();
@@ -1364,6 +1890,7 @@ fn main() {
     let mut emulator = Emulator::new();
     emulator.input_mapping = config.input_mapping;
     emulator.debug = config.debug;
+    emulator.set_breakpoint_screenshot_dir(config.breakpoint_screenshot_dir.map(Into::into));
     if let Some(path) = dump_audio_path {
         println!("Dumping audio to: {}", path);
         match wav_writer::WavWriter::new(&path, audio::SAMPLE_RATE, 2) {
@@ -1803,4 +2330,556 @@ mod tests {
             "Should advance when resumed"
         );
     }
+
+    #[test]
+    fn test_script_hook_frame_and_input() {
+        use std::cell::Cell;
+
+        struct PressStart {
+            frames: Rc<Cell<u32>>,
+            instructions: Rc<Cell<u32>>,
+        }
+        impl ScriptHook for PressStart {
+            fn on_frame(&mut self, emulator: &mut Emulator) {
+                self.frames.set(self.frames.get() + 1);
+                if let Some(ctrl) = emulator.bus.borrow_mut().io.controller(1) {
+                    ctrl.start = true;
+                }
+            }
+            fn on_instruction(&mut self, _cpu: &mut Cpu) {
+                self.instructions.set(self.instructions.get() + 1);
+            }
+        }
+
+        let frames = Rc::new(Cell::new(0));
+        let instructions = Rc::new(Cell::new(0));
+        let mut emulator = Emulator::new();
+        emulator.add_script_hook(Box::new(PressStart {
+            frames: frames.clone(),
+            instructions: instructions.clone(),
+        }));
+
+        for _ in 0..3 {
+            emulator.step_frame(None);
+        }
+
+        assert_eq!(frames.get(), 3);
+        assert!(instructions.get() > 0);
+        let mut bus = emulator.bus.borrow_mut();
+        assert!(bus.io.port1.state.start, "Hook input should reach port 1");
+        // Start reads active-low on the data port with TH=0
+        bus.write_byte(0xA10003, 0x00);
+        assert_eq!(bus.read_byte(0xA10003) & 0x20, 0);
+        drop(bus);
+
+        emulator.clear_script_hooks();
+        emulator.step_frame(None);
+        assert_eq!(frames.get(), 3);
+    }
+
+    #[test]
+    fn test_scheduled_input_applies_on_its_frame() {
+        use std::cell::RefCell as Shared;
+
+        struct WatchStart {
+            seen: Rc<Shared<Vec<u64>>>,
+        }
+        impl ScriptHook for WatchStart {
+            fn on_frame(&mut self, emulator: &mut Emulator) {
+                if emulator.bus.borrow().io.port1.state.start {
+                    self.seen.borrow_mut().push(emulator.internal_frame_count);
+                }
+            }
+        }
+
+        let seen = Rc::new(Shared::new(Vec::new()));
+        let mut emulator = Emulator::new();
+        emulator.add_script_hook(Box::new(WatchStart { seen: seen.clone() }));
+
+        let mut start = input::FrameInput::default();
+        start.p1.start = true;
+        emulator.schedule_input(10, start);
+
+        for _ in 0..20 {
+            emulator.step_frame(None);
+        }
+
+        assert_eq!(*seen.borrow(), vec![10]);
+        assert!(emulator.scheduled_inputs.is_empty());
+    }
+
+    #[test]
+    fn test_cycle_counts_track_component_ratios() {
+        let mut emulator = Emulator::new();
+        {
+            // SSP 0xFF0000, PC 0x200: BRA.S to itself
+            let mut rom = vec![0u8; 0x400];
+            rom[0..4].copy_from_slice(&0x00FF0000u32.to_be_bytes());
+            rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+            rom[0x200..0x202].copy_from_slice(&[0x60, 0xFE]);
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(&rom);
+            emulator.cpu.reset(&mut *bus);
+            // Release the Z80 so it runs alongside the 68k (RAM is all NOPs)
+            bus.z80_reset = false;
+        }
+        let before = emulator.cycle_counts();
+
+        emulator.step_frame(None);
+        let after = emulator.cycle_counts();
+
+        let m68k = after.m68k - before.m68k;
+        let z80 = after.z80 - before.z80;
+        assert_eq!(after.vdp_lines - before.vdp_lines, 262);
+
+        // NTSC: 7.67 MHz 68k for one 60 Hz frame
+        assert!((120_000..=135_000).contains(&m68k), "68k cycles {}", m68k);
+
+        // Z80 runs at MCLK/15 against the 68k's MCLK/7
+        let ratio = z80 as f64 / m68k as f64;
+        assert!((7.0 / 15.0 - ratio).abs() < 0.01, "Z80/68k ratio {}", ratio);
+
+        let samples = after.apu_samples - before.apu_samples;
+        // NTSC runs slightly under 60 Hz, so allow a little over a nominal frame
+        let expected = audio::samples_per_frame() as u64;
+        assert!(
+            samples.abs_diff(expected) <= expected / 100,
+            "APU samples {} vs {}",
+            samples,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_record_mode_pairs_each_frame_with_its_audio() {
+        let mut emulator = Emulator::new();
+        assert!(emulator.step_frame_recorded(None).is_none());
+
+        // Start part-way through a sample so the carried phase matters
+        emulator.step_frame(None);
+        let phase = emulator.bus.borrow().audio_phase();
+        assert_ne!(phase, 0);
+        emulator.set_record_mode(true);
+        let sample_rate = emulator.bus.borrow().sample_rate as u64;
+        // NTSC frame: 262 lines of 3420 master clocks
+        let per_frame = sample_rate * 262 * 3420;
+        let total = |frames: u64| (phase + frames * per_frame) / audio::NTSC_MCLK as u64;
+        let expected = |frame: u64| (total(frame + 1) - total(frame)) as usize;
+
+        let mut recorded_pairs = 0;
+        for frame in 0..10 {
+            let generated = emulator.cycle_counts().apu_samples;
+            let recorded = emulator.step_frame_recorded(None).unwrap();
+            assert_eq!(recorded.framebuffer.len(), 320 * 240);
+            assert_eq!(recorded.audio.len(), expected(frame) * 2, "frame {}", frame);
+            // The core sampled exactly that; nothing was padded or dropped
+            let generated = emulator.cycle_counts().apu_samples - generated;
+            assert_eq!(generated as usize, expected(frame), "frame {}", frame);
+            recorded_pairs += recorded.audio.len() / 2;
+        }
+        assert_eq!(recorded_pairs as u64, total(10) - total(0));
+
+        emulator.set_record_mode(false);
+        assert!(emulator.step_frame_recorded(None).is_none());
+    }
+
+    #[test]
+    fn test_external_interrupt_requests_level_2() {
+        let mut emulator = Emulator::new();
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            bus.vdp.registers[11] = 0x08;
+            bus.write_byte(0xA10009, 0x80);
+            bus.drive_th(1, false);
+        }
+        emulator.handle_interrupts();
+        assert_eq!(emulator.cpu.pending_interrupt, 2);
+    }
+
+    #[test]
+    fn test_cheat_list_round_trip_patches_reads() {
+        let mut rom = vec![0u8; 0x400];
+        rom[0x200..0x204].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+
+        let mut cheats = CheatList::new();
+        cheats
+            .add(cheats::Cheat::new("Lives", "000200:ABCD"))
+            .unwrap();
+        let mut disabled = cheats::Cheat::new("Level select", "000202:0000");
+        disabled.enabled = false;
+        cheats.add(disabled).unwrap();
+        assert!(cheats
+            .add(cheats::Cheat::new("Odd", "000201:0000"))
+            .is_err());
+
+        let path = std::env::temp_dir().join("genteel_test_cheats.json");
+        let mut emulator = Emulator::new();
+        emulator.set_cheats(cheats.clone());
+        emulator.save_cheats(&path).unwrap();
+
+        let mut fresh = Emulator::new();
+        fresh.bus.borrow_mut().load_rom(&rom);
+        fresh.load_cheats(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(fresh.cheats(), &cheats);
+        {
+            let mut bus = fresh.bus.borrow_mut();
+            assert_eq!(bus.read_word(0x200), 0xABCD);
+            assert_eq!(bus.read_byte(0x201), 0xCD);
+            assert_eq!(bus.read_word(0x202), 0x5678, "Disabled code isn't applied");
+        }
+
+        // Replacing the list restores the original ROM
+        fresh.set_cheats(CheatList::new());
+        assert_eq!(fresh.bus.borrow_mut().read_word(0x200), 0x1234);
+    }
+
+    #[test]
+    fn test_poll_counts_cover_one_frame() {
+        let mut emulator = Emulator::new();
+        {
+            // MOVE.B $A10003,D0 ; MOVE.W $A10002,D0, then BRA.S to itself
+            let mut rom = vec![0u8; 0x400];
+            rom[0..4].copy_from_slice(&0x00FF0000u32.to_be_bytes());
+            rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+            rom[0x200..0x20E].copy_from_slice(&[
+                0x10, 0x39, 0x00, 0xA1, 0x00, 0x03, 0x30, 0x39, 0x00, 0xA1, 0x00, 0x02, 0x60, 0xFE,
+            ]);
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(&rom);
+            emulator.cpu.reset(&mut *bus);
+        }
+
+        emulator.step_frame_internal();
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            assert_eq!(bus.io.poll_count(1), 2, "one poll per read, word or byte");
+            assert_eq!(bus.io.poll_count(2), 0);
+            // A debugger peek isn't a poll
+            bus.read_byte(0xA10003);
+            assert_eq!(bus.io.poll_count(1), 2);
+        }
+
+        // The next frame only spins, so it's a lag frame
+        emulator.step_frame_internal();
+        assert_eq!(emulator.bus.borrow().io.poll_count(1), 0);
+    }
+
+    #[test]
+    fn test_load_elf_places_segments_and_enters() {
+        // ELF header + two PT_LOAD headers: BRA.S * at 0x200 and four bytes
+        // of data (plus four of bss) at 0xFF0100
+        let mut elf = vec![0u8; 52 + 2 * 32];
+        elf[..6].copy_from_slice(&[0x7F, b'E', b'L', b'F', 1, 2]);
+        elf[18..20].copy_from_slice(&4u16.to_be_bytes());
+        elf[24..28].copy_from_slice(&0x200u32.to_be_bytes());
+        elf[28..32].copy_from_slice(&52u32.to_be_bytes());
+        elf[42..44].copy_from_slice(&32u16.to_be_bytes());
+        elf[44..46].copy_from_slice(&2u16.to_be_bytes());
+        let segments: [(u32, u32, u32, u32); 2] = [(116, 0x200, 2, 2), (118, 0xFF0100, 4, 8)];
+        for (i, (offset, address, file_size, mem_size)) in segments.into_iter().enumerate() {
+            let ph = 52 + i * 32;
+            let fields = [1, offset, address, address, file_size, mem_size];
+            for (j, field) in fields.iter().enumerate() {
+                elf[ph + j * 4..ph + j * 4 + 4].copy_from_slice(&field.to_be_bytes());
+            }
+        }
+        elf.extend_from_slice(&[0x60, 0xFE, 0xDE, 0xAD, 0xBE, 0xEF]);
+
+        let mut emulator = Emulator::new();
+        {
+            // Leftover RAM shows the bss is cleared
+            let mut bus = emulator.bus.borrow_mut();
+            bus.write_long(0xFF0104, 0xFFFFFFFF);
+        }
+        let image = ProgramImage::parse(&elf, 0).unwrap();
+        emulator.load_image(&image).unwrap();
+
+        assert_eq!(emulator.cpu.pc, 0x200);
+        assert_eq!(
+            emulator.cpu.a[7], 0x0100_0000,
+            "No vectors, stack at top of RAM"
+        );
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            assert_eq!(bus.read_word(0x200), 0x60FE);
+            assert_eq!(bus.read_long(0xFF0100), 0xDEADBEEF);
+            assert_eq!(bus.read_long(0xFF0104), 0);
+        }
+        emulator.step_frame_internal();
+        assert_eq!(emulator.cpu.pc, 0x200);
+
+        // A segment outside ROM and RAM is refused
+        let stray = ProgramImage::from_binary(&[0; 4], 0xC00000);
+        assert!(emulator.load_image(&stray).is_err());
+    }
+
+    #[test]
+    fn test_state_hash_matches_for_identical_runs() {
+        // Read pad 1 into D0 and store it in RAM, forever
+        let mut rom = vec![0u8; 0x400];
+        rom[0..4].copy_from_slice(&0x00FF0000u32.to_be_bytes());
+        rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+        rom[0x200..0x20E].copy_from_slice(&[
+            0x10, 0x39, 0x00, 0xA1, 0x00, 0x03, // MOVE.B $A10003,D0
+            0x13, 0xC0, 0x00, 0xFF, 0x00, 0x00, // MOVE.B D0,$FF0000
+            0x60, 0xF2, // BRA.S to the start
+        ]);
+        let boot = |emulator: &mut Emulator| {
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(&rom);
+            emulator.cpu.reset(&mut *bus);
+        };
+        let mut first = Emulator::new();
+        let mut second = Emulator::new();
+        boot(&mut first);
+        boot(&mut second);
+        assert_eq!(first.state_hash(), second.state_hash());
+
+        let mut input = input::FrameInput::default();
+        input.p1.set_button("B", true);
+        for emulator in [&mut first, &mut second] {
+            emulator.step_frame(Some(&input));
+            emulator.step_frame(None);
+        }
+        assert_eq!(first.state_hash(), second.state_hash());
+        assert_ne!(first.state_hash(), {
+            let mut fresh = Emulator::new();
+            boot(&mut fresh);
+            fresh.state_hash()
+        });
+
+        // One register apart
+        second.cpu.d[3] ^= 1;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.cpu.d[3] ^= 1;
+        assert_eq!(first.state_hash(), second.state_hash());
+        second.z80.a ^= 0x80;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.z80.a ^= 0x80;
+
+        // ROM contents and patches are skipped by savestates but hashed
+        second.bus.borrow_mut().apply_rom_patch(0x300, 0x1234);
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.bus.borrow_mut().clear_rom_patches();
+        assert_eq!(first.state_hash(), second.state_hash());
+        second.bus.borrow_mut().rom[0x3FF] ^= 1;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.bus.borrow_mut().rom[0x3FF] ^= 1;
+
+        // So is where the scheduler stands within the frame
+        second.frame_line = 100;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.frame_line = 0;
+        second.z80_cycle_debt += 0.5;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.z80_cycle_debt -= 0.5;
+        second.z80_last_bus_req = !second.z80_last_bus_req;
+        assert_ne!(first.state_hash(), second.state_hash());
+        second.z80_last_bus_req = !second.z80_last_bus_req;
+        assert_eq!(first.state_hash(), second.state_hash());
+    }
+
+    #[test]
+    fn test_cram_dots_follow_the_writing_instruction() {
+        let mut emulator = Emulator::new();
+        {
+            let mut rom = vec![0u8; 0x400];
+            rom[0..4].copy_from_slice(&0x00FF0000u32.to_be_bytes());
+            rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+            let mut code = vec![
+                0x33, 0xFC, 0x81, 0x44, 0x00, 0xC0, 0x00, 0x04, // MOVE.W #$8144,$C00004
+                0x33, 0xFC, 0x8C, 0x81, 0x00, 0xC0, 0x00, 0x04, // MOVE.W #$8C81,$C00004
+                0x33, 0xFC, 0x8F, 0x00, 0x00, 0xC0, 0x00, 0x04, // MOVE.W #$8F00,$C00004
+                0x23, 0xFC, 0xC0, 0x02, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x04, // CRAM entry 1
+                0x41, 0xF9, 0x00, 0xC0, 0x00, 0x00, // LEA $C00000,A0
+                0x30, 0x3C, 0x0E, 0xEE, // MOVE.W #$0EEE,D0
+                0x30, 0x80, // MOVE.W D0,(A0)
+            ];
+            // Ten NOPs, then BRA.S back to the write: 58 cycles a pass
+            for _ in 0..10 {
+                code.extend_from_slice(&[0x4E, 0x71]);
+            }
+            code.extend_from_slice(&[0x60, 0xE8]);
+            rom[0x200..0x200 + code.len()].copy_from_slice(&code);
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(&rom);
+            emulator.cpu.reset(&mut *bus);
+            bus.vdp.set_cram_dots(true);
+            // Straight to CRAM, so a dot shows where its write ran rather
+            // than at the next access slot
+            bus.vdp.bypass_fifo = true;
+        }
+        emulator.step_frame(None);
+        emulator.step_frame(None);
+
+        let bus = emulator.bus.borrow();
+        let white = vdp::Vdp::genesis_color_to_rgb565(0x0EEE);
+        let row = &bus.vdp.front_buffer()[100 * 320..101 * 320];
+        let dots: Vec<usize> = (0..320).filter(|&x| row[x] == white).collect();
+        assert!(dots.len() >= 4, "{:?}", dots);
+        // 406 MCLKs apart, the beam moves 50.75 H40 pixels between writes
+        for pair in dots.windows(2) {
+            assert!(matches!(pair[1] - pair[0], 50 | 51), "{:?}", dots);
+        }
+    }
+
+    #[test]
+    fn test_run_to_vblank_stops_at_vblank_start() {
+        let mut emulator = Emulator::new();
+        {
+            // BRA.S to itself
+            let mut rom = vec![0u8; 0x400];
+            rom[0..4].copy_from_slice(&0x00FF0000u32.to_be_bytes());
+            rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+            rom[0x200..0x202].copy_from_slice(&[0x60, 0xFE]);
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(&rom);
+            emulator.cpu.reset(&mut *bus);
+            bus.vdp.load_test_pattern(vdp::Pattern::ColorBars);
+        }
+
+        emulator.run_to_vblank();
+        {
+            let bus = emulator.bus.borrow();
+            assert_ne!(bus.vdp.status & vdp::constants::STATUS_VBLANK, 0);
+            assert_eq!(bus.vdp.v_counter, bus.vdp.screen_height());
+            // The frame's active lines are drawn, and already presented
+            let active = bus.vdp.screen_height() as usize * 320;
+            assert_eq!(
+                bus.vdp.front_buffer()[..active],
+                bus.vdp.framebuffer[..active]
+            );
+            assert!(bus.vdp.front_buffer().iter().any(|&p| p != 0));
+        }
+        assert_eq!(
+            emulator.internal_frame_count, 0,
+            "Frame is still in progress"
+        );
+
+        // Already in VBlank, so the next call waits for the following frame's
+        emulator.run_to_vblank();
+        assert_eq!(emulator.internal_frame_count, 1);
+        let bus = emulator.bus.borrow();
+        assert_eq!(bus.vdp.v_counter, bus.vdp.screen_height());
+
+        drop(bus);
+        emulator.step_frame_internal();
+        assert_eq!(emulator.internal_frame_count, 2);
+    }
+
+    #[test]
+    fn test_upload_z80_program_runs_after_release() {
+        let mut emulator = Emulator::new();
+        // LD A,0x42 ; LD (0x1000),A ; HALT
+        let driver = [0x3E, 0x42, 0x32, 0x00, 0x10, 0x76];
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            bus.upload_z80_program(&driver).unwrap();
+            assert_eq!(&bus.z80_ram[..driver.len()], &driver);
+            assert!(!bus.z80_bus_request, "bus handed back to the Z80");
+            assert!(!bus.z80_reset, "reset released");
+            assert!(bus.upload_z80_program(&[0; 0x2001]).is_err());
+        }
+
+        emulator.step_frame_internal();
+        assert_eq!(emulator.bus.borrow().z80_ram[0x1000], 0x42);
+        assert!(emulator.z80.halted);
+    }
+
+    #[test]
+    fn test_breakpoint_hit_saves_screenshot() {
+        // Unique per process and per call, so parallel runs don't share it
+        static RUNS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+        let run = RUNS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "genteel_test_breakpoint_shots_{}_{}",
+            std::process::id(),
+            run
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut emulator = Emulator::new();
+        emulator.set_breakpoint_screenshot_dir(Some(dir.clone()));
+        let mut gdb = GdbServer::new(0, None).unwrap();
+        // NOP ; NOP, with a breakpoint on the second
+        gdb.breakpoints.insert(0xFF0002);
+        emulator.gdb = Some(gdb);
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            bus.write_word(0xFF0000, 0x4E71);
+            bus.write_word(0xFF0002, 0x4E71);
+        }
+        emulator.cpu.pc = 0xFF0000;
+        emulator.cpu.invalidate_cache();
+
+        assert_eq!(emulator.step_for_gdb(false), Some(StopReason::Breakpoint));
+        let shots: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(shots.len(), 1);
+        assert!(shots[0].starts_with("breakpoint_FF0002_"));
+        assert!(shots[0].ends_with(".png"));
+        let img = image::open(dir.join(&shots[0])).unwrap();
+        assert_eq!((img.width(), img.height()), (320, 240));
+
+        // Carrying on to a plain step takes no further screenshot
+        assert_eq!(emulator.step_for_gdb(true), Some(StopReason::Step));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_frame_profile_times_active_subsystems() {
+        let mut emulator = Emulator::new();
+        emulator.step_frame_internal();
+        assert_eq!(emulator.last_frame_profile(), None, "off by default");
+
+        // JR $ keeps the Z80 busy all frame
+        emulator
+            .bus
+            .borrow_mut()
+            .upload_z80_program(&[0x18, 0xFE])
+            .unwrap();
+        emulator.set_profiling(true);
+        emulator.step_frame_internal();
+        let profile = emulator.last_frame_profile().unwrap();
+        let zero = std::time::Duration::ZERO;
+        assert!(profile.m68k > zero, "{:?}", profile);
+        assert!(profile.z80 > zero, "{:?}", profile);
+        assert!(profile.vdp > zero, "{:?}", profile);
+        assert!(profile.apu > zero, "{:?}", profile);
+
+        emulator.set_profiling(false);
+        assert_eq!(emulator.last_frame_profile(), None);
+    }
+
+    #[test]
+    fn test_halted_z80_keeps_pace_and_wakes_on_vint() {
+        let mut emulator = Emulator::new();
+        // IM 1 ; EI ; HALT, with the handler at 0x38: LD A,0x42 ;
+        // LD (0x1000),A ; HALT
+        let mut driver = vec![0xED, 0x56, 0xFB, 0x76];
+        driver.resize(0x38, 0);
+        driver.extend([0x3E, 0x42, 0x32, 0x00, 0x10, 0x76]);
+        emulator
+            .bus
+            .borrow_mut()
+            .upload_z80_program(&driver)
+            .unwrap();
+
+        emulator.step_frame_internal();
+        // 262 lines of 488 68k cycles at the Z80's clock ratio
+        let expected = (262.0 * 488.0 * 3579545.0 / 7670453.0) as u64;
+        let cycles = emulator.z80.cycles;
+        assert!(
+            cycles.abs_diff(expected) < 100,
+            "{} T-states, expected about {}",
+            cycles,
+            expected
+        );
+        assert_eq!(emulator.bus.borrow().z80_ram[0x1000], 0x42, "VINT ran");
+        assert!(emulator.z80.halted);
+        assert_eq!(emulator.z80.pc, 0x3E);
+    }
 }
@@ -17,10 +17,24 @@
 //! | 0xA11100-0xA11101  | 2 B    | Z80 Bus Request                |
 //! | 0xA11200-0xA11201  | 2 B    | Z80 Reset                      |
 //! | 0xB00000-0xBFFFFF  | 1 MB   | Reserved                       |
-//! | 0xC00000-0xC0001F  | 32 B   | VDP Ports                      |
+//! | 0xC00000-0xC0FFFF  | 64 KB  | VDP Ports (32 B mirrored)      |
 //! | 0xE00000-0xFFFFFF  | 2 MB   | Work RAM (64KB mirrored)       |
+//!
+//! ## Byte Access to 16-bit Devices
+//!
+//! The VDP and I/O chips sit on the 16-bit data bus, so byte accesses follow
+//! the hardware rules rather than touching half a register:
+//!
+//! * VDP data/control byte writes put the byte on both halves of the bus, so
+//!   the port sees the byte duplicated (`0x81` is written as `0x8181`).
+//! * VDP byte reads perform a full word access and return the addressed half.
+//! * The PSG lives on the low byte lane of 0xC00010-0xC00017; word writes
+//!   there deliver their low byte and even byte writes are ignored.
+//! * I/O registers live on the low byte lane; byte reads at the even address
+//!   mirror the odd one, while even byte writes are ignored.
 
 use super::byte_utils;
+use super::image::ProgramImage;
 use super::MemoryInterface;
 use crate::apu::Apu;
 use crate::audio;
@@ -29,10 +43,103 @@ use crate::io::Io;
 use crate::vdp::Vdp;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
 /// Maximum SRAM size in bytes (2MB) to prevent OOM/DoS
 const MAX_SRAM_SIZE: usize = 2 * 1024 * 1024;
 
+/// Component that answers accesses to a memory region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryDevice {
+    Rom,
+    Sram,
+    Z80Ram,
+    Ym2612,
+    Z80Bank,
+    Io,
+    Z80Control,
+    Tmss,
+    Vdp,
+    WorkRam,
+}
+
+/// Extra 68k cycles each bus access to a region costs. A stock console runs
+/// cartridge ROM and work RAM with no wait states, so everything defaults to
+/// zero; slow cartridges or experiments can raise them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitStates {
+    /// Cartridge space, including SRAM
+    pub rom: u32,
+    /// Z80 RAM, YM2612 and the bank register
+    pub z80_area: u32,
+    pub io: u32,
+    pub vdp: u32,
+    pub work_ram: u32,
+}
+
+impl WaitStates {
+    pub fn for_address(&self, address: u32) -> u32 {
+        match address & 0xFFFFFF {
+            0x000000..=0x3FFFFF => self.rom,
+            0xA00000..=0xA0FFFF => self.z80_area,
+            0xA10000..=0xA1FFFF => self.io,
+            0xC00000..=0xDFFFFF => self.vdp,
+            0xE00000..=0xFFFFFF => self.work_ram,
+            _ => 0,
+        }
+    }
+}
+
+/// One entry of `Bus::memory_map`, bounds inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionInfo {
+    pub name: &'static str,
+    pub start: u32,
+    pub end: u32,
+    pub writable: bool,
+    pub device: MemoryDevice,
+}
+
+impl RegionInfo {
+    const fn new(
+        name: &'static str,
+        start: u32,
+        end: u32,
+        writable: bool,
+        device: MemoryDevice,
+    ) -> Self {
+        Self {
+            name,
+            start,
+            end,
+            writable,
+            device,
+        }
+    }
+
+    pub fn contains(&self, addr: u32) -> bool {
+        (self.start..=self.end).contains(&addr)
+    }
+}
+
+/// Older states stored `audio_accumulator` as a float count of master
+/// clocks. Less than a sample of progress is lost by restarting it at zero.
+fn deserialize_audio_accumulator<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Accumulator {
+        Scaled(u64),
+        Legacy(#[allow(dead_code)] f64),
+    }
+    Ok(match Accumulator::deserialize(deserializer)? {
+        Accumulator::Scaled(value) => value,
+        Accumulator::Legacy(_) => 0,
+    })
+}
+
 /// Sega Genesis Memory Bus
 ///
 /// Routes memory accesses to the appropriate component based on address.
@@ -42,6 +149,10 @@ pub struct Bus {
     #[serde(skip)]
     pub rom: Vec<u8>,
 
+    /// Original ROM words overwritten by cheat patches, keyed by address
+    #[serde(skip)]
+    rom_patches: BTreeMap<u32, u16>,
+
     /// Work RAM (64KB at 0xFF0000-0xFFFFFF, mirrored in 0xE00000-0xFFFFFF)
     pub work_ram: Box<[u8]>,
 
@@ -77,8 +188,28 @@ pub struct Bus {
     #[serde(skip)]
     pub tmss_register: [u8; 4],
 
-    /// Audio synchronization
-    pub audio_accumulator: f32,
+    /// Per-region access timing, a configuration rather than machine state
+    #[serde(skip)]
+    pub wait_states: WaitStates,
+    /// Wait cycles accrued since the CPU last collected them
+    #[serde(skip)]
+    wait_cycles: u32,
+    /// MCLKs the VDP has already run past the CPU while it waited on a
+    /// full FIFO; the next ticks skip that much VDP time
+    #[serde(skip)]
+    vdp_lead: u32,
+
+    /// Progress toward the next sample, in master clocks scaled by the
+    /// sample rate so whole samples fall due without rounding
+    #[serde(default, deserialize_with = "deserialize_audio_accumulator")]
+    pub audio_accumulator: u64,
+    /// Remainder of the nominal line lengths' sample shares, in the same
+    /// units, carried so every frame gets its exact share
+    #[serde(default)]
+    audio_phase: u64,
+    /// Samples the current line still owes
+    #[serde(default)]
+    audio_line_budget: u32,
     #[serde(skip)]
     pub audio_buffer: Vec<i16>,
     pub sample_rate: u32,
@@ -95,6 +226,10 @@ impl Bus {
     pub fn new() -> Self {
         Self {
             rom: Vec::new(),
+            rom_patches: BTreeMap::new(),
+            wait_states: WaitStates::default(),
+            wait_cycles: 0,
+            vdp_lead: 0,
             work_ram: vec![0; 0x10000].into_boxed_slice(),
             z80_ram: vec![0; 0x2000].into_boxed_slice(),
             sram: vec![0; 0x10000].into_boxed_slice(),
@@ -109,15 +244,54 @@ impl Bus {
             z80_bank_addr: 0,
             tmss_unlocked: false,
             tmss_register: [0; 4],
-            audio_accumulator: 0.0,
+            audio_accumulator: 0,
+            audio_phase: 0,
+            audio_line_budget: 0,
             audio_buffer: Vec::with_capacity(2048),
             sample_rate: audio::SAMPLE_RATE,
         }
     }
 
+    /// Load a program image, building the ROM from the segments that fall in
+    /// cartridge space and copying the rest into work RAM. Segments outside
+    /// those two regions, or straddling them, are rejected and nothing is
+    /// loaded.
+    pub fn load_image(&mut self, image: &ProgramImage) -> Result<(), String> {
+        let mut rom_size = 0;
+        for segment in &image.segments {
+            let start = segment.address as u64;
+            let in_rom = segment.end() <= 0x400000;
+            let in_ram = start >= 0xFF0000 && segment.end() <= 0x1000000;
+            if !in_rom && !in_ram {
+                return Err(format!(
+                    "Segment {:06X}-{:06X} is outside ROM and work RAM",
+                    start,
+                    segment.end()
+                ));
+            }
+            if in_rom {
+                rom_size = rom_size.max(segment.end() as usize);
+            }
+        }
+
+        let mut rom = vec![0u8; rom_size];
+        for segment in image.segments.iter().filter(|s| s.end() <= 0x400000) {
+            let start = segment.address as usize;
+            rom[start..start + segment.data.len()].copy_from_slice(&segment.data);
+        }
+        self.load_rom(&rom);
+
+        for segment in image.segments.iter().filter(|s| s.address >= 0xFF0000) {
+            let start = (segment.address & 0xFFFF) as usize;
+            self.work_ram[start..start + segment.data.len()].copy_from_slice(&segment.data);
+        }
+        Ok(())
+    }
+
     /// Load a ROM into the bus
     pub fn load_rom(&mut self, data: &[u8]) {
         self.rom = data.to_vec();
+        self.rom_patches.clear();
         // Pad ROM to at least 512 bytes to ensure vector table exists
         if self.rom.len() < 512 {
             self.rom.resize(512, 0);
@@ -148,9 +322,10 @@ impl Bus {
                 self.sram = vec![0; size].into_boxed_slice();
                 self.sram_enabled = true;
             } else if size > MAX_SRAM_SIZE {
-                eprintln!(
+                log::warn!(
                     "ROM header specified SRAM size too large: {} bytes (max {})",
-                    size, MAX_SRAM_SIZE
+                    size,
+                    MAX_SRAM_SIZE
                 );
             }
         } else {
@@ -159,6 +334,57 @@ impl Bus {
             self.sram_end = 0x20FFFF;
             self.sram_enabled = false; // Will be enabled by software via $A130F1
         }
+
+        for problem in self.reset_vector_problems() {
+            log::warn!("{}", problem);
+        }
+    }
+
+    /// Reasons the reset vectors can't boot the loaded ROM: a stack outside
+    /// work RAM, or an entry point outside the ROM image and work RAM. Each
+    /// mentions the header's region codes, since a ROM dumped for another
+    /// region is the usual cause. The CPU takes the vectors regardless.
+    pub fn reset_vector_problems(&self) -> Vec<String> {
+        let (Some(vectors), Some(region)) = (self.rom.get(0..8), self.rom.get(0x1F0..0x1F3)) else {
+            return vec![format!(
+                "{}-byte ROM too short for a vector table/header",
+                self.rom.len()
+            )];
+        };
+        let vector = |at: usize| {
+            u32::from_be_bytes([
+                vectors[at],
+                vectors[at + 1],
+                vectors[at + 2],
+                vectors[at + 3],
+            ])
+        };
+        let in_work_ram = |addr: u32| (0xE00000..=0xFFFFFF).contains(&(addr & 0xFFFFFF));
+        let region = String::from_utf8_lossy(region);
+        let region = region.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+
+        let mut problems = Vec::new();
+        let sp = vector(0);
+        // An SP of 0 wraps to the top of work RAM on the first push
+        if sp & 1 != 0 || !(in_work_ram(sp) || sp & 0xFFFFFF == 0) {
+            problems.push(format!(
+                "reset SP {:08X} is not an even work RAM address",
+                sp
+            ));
+        }
+        let pc = vector(4);
+        let in_rom = ((pc & 0xFFFFFF) as usize) < self.rom.len();
+        if pc & 1 != 0 || !(in_rom || in_work_ram(pc)) {
+            problems.push(format!(
+                "reset PC {:08X} is not an even address in the {}-byte ROM or work RAM",
+                pc,
+                self.rom.len()
+            ));
+        }
+        for problem in &mut problems {
+            problem.push_str(&format!(" (header region {:?})", region));
+        }
+        problems
     }
 
     /// Clear the ROM
@@ -171,6 +397,28 @@ impl Bus {
         self.rom.len()
     }
 
+    /// Load a Z80 program the way games upload sound drivers: request the
+    /// bus and hold the Z80 in reset, copy through the 68k window at
+    /// 0xA00000, then release the bus and the reset so the Z80 starts
+    /// from address 0.
+    pub fn upload_z80_program(&mut self, program: &[u8]) -> Result<(), String> {
+        if program.len() > self.z80_ram.len() {
+            return Err(format!(
+                "Z80 program is {} bytes; Z80 RAM holds {}",
+                program.len(),
+                self.z80_ram.len()
+            ));
+        }
+        self.write_byte(0xA11100, 0x01);
+        self.write_byte(0xA11200, 0x00);
+        for (offset, &byte) in program.iter().enumerate() {
+            self.write_byte(0xA00000 + offset as u32, byte);
+        }
+        self.write_byte(0xA11100, 0x00);
+        self.write_byte(0xA11200, 0x01);
+        Ok(())
+    }
+
     /// Check if DMA is currently active
     pub fn dma_active(&self) -> bool {
         use crate::vdp::DmaOps;
@@ -190,7 +438,7 @@ impl Bus {
         self.vdp.vsram.fill(0);
         self.vdp.reconstruct_cram_cache();
 
-        self.io.reset();
+        self.io.reset(true);
         self.apu.reset();
 
         self.z80_bus_request = false;
@@ -198,10 +446,42 @@ impl Bus {
         self.z80_bank_addr = 0;
         self.tmss_unlocked = false;
         self.tmss_register = [0; 4];
-        self.audio_accumulator = 0.0;
+        self.audio_accumulator = 0;
+        self.audio_phase = 0;
+        self.audio_line_budget = 0;
         self.audio_buffer.clear();
     }
 
+    /// Regions the 68000 address decoder currently routes to a device, in
+    /// address order. Addresses outside every region are open bus. When SRAM
+    /// is switched in its window is listed after the ROM it overlays.
+    pub fn memory_map(&self) -> Vec<RegionInfo> {
+        use MemoryDevice::*;
+        let mut map = vec![RegionInfo::new("ROM", 0x000000, 0x3FFFFF, false, Rom)];
+        if self.sram_enabled {
+            map.push(RegionInfo::new(
+                "SRAM",
+                self.sram_start,
+                self.sram_end,
+                true,
+                Sram,
+            ));
+        }
+        map.extend([
+            RegionInfo::new("Z80 RAM", 0xA00000, 0xA01FFF, true, Z80Ram),
+            RegionInfo::new("YM2612", 0xA04000, 0xA04003, true, Ym2612),
+            RegionInfo::new("Z80 Bank Register", 0xA06000, 0xA060FF, true, Z80Bank),
+            RegionInfo::new("I/O Ports", 0xA10000, 0xA1001F, true, Io),
+            RegionInfo::new("Z80 Bus Request", 0xA11100, 0xA11101, true, Z80Control),
+            RegionInfo::new("Z80 Reset", 0xA11200, 0xA11201, true, Z80Control),
+            RegionInfo::new("SRAM Control", 0xA130F1, 0xA130F1, true, Sram),
+            RegionInfo::new("TMSS", 0xA14000, 0xA14003, true, Tmss),
+            RegionInfo::new("VDP Ports", 0xC00000, 0xC0FFFF, true, Vdp),
+            RegionInfo::new("Work RAM", 0xE00000, 0xFFFFFF, true, WorkRam),
+        ]);
+        map
+    }
+
     /// Read a byte from the memory map
     pub fn read_byte(&mut self, address: u32) -> u8 {
         let addr = address & 0xFFFFFF; // 24-bit address bus
@@ -227,10 +507,10 @@ impl Bus {
         let addr = address & 0xFFFFFF;
 
         match addr {
-            0x000000..=0x3FFFFF => {
-                if self.sram_enabled && addr >= self.sram_start && addr <= self.sram_end {
-                    self.write_sram(addr, value);
-                }
+            0x000000..=0x3FFFFF
+                if self.sram_enabled && addr >= self.sram_start && addr <= self.sram_end =>
+            {
+                self.write_sram(addr, value);
             }
             0xA00000..=0xA0FFFF => self.write_z80_area(addr, value),
             0xA10000..=0xA1FFFF => {
@@ -240,12 +520,46 @@ impl Bus {
                     self.write_io_area(addr, value);
                 }
             }
+            0x000000..=0x3FFFFF => {
+                // Cartridge ROM is read-only; patches go through apply_rom_patch
+                log::debug!("Ignored write {:02X} to ROM at {:06X}", value, addr);
+            }
             0xC00000..=0xC0FFFF => self.write_vdp_area(addr, value),
             0xE00000..=0xFFFFFF => self.write_ram(addr, value),
             _ => {}
         }
     }
 
+    /// Replace the ROM word at `addr`, as a Game Genie does, keeping the
+    /// original so the patch can be undone. Returns false if `addr` is odd
+    /// or outside the loaded ROM.
+    pub fn apply_rom_patch(&mut self, addr: u32, value: u16) -> bool {
+        let idx = addr as usize;
+        if !addr.is_multiple_of(2) || idx + 1 >= self.rom.len() {
+            return false;
+        }
+        let original = byte_utils::join_u16(self.rom[idx], self.rom[idx + 1]);
+        self.rom_patches.entry(addr).or_insert(original);
+        let (high, low) = byte_utils::split_u16(value);
+        self.rom[idx] = high;
+        self.rom[idx + 1] = low;
+        true
+    }
+
+    /// Original words of the ROM addresses currently patched
+    pub fn rom_patches(&self) -> &BTreeMap<u32, u16> {
+        &self.rom_patches
+    }
+
+    /// Restore every ROM word changed by `apply_rom_patch`
+    pub fn clear_rom_patches(&mut self) {
+        for (addr, original) in std::mem::take(&mut self.rom_patches) {
+            let (high, low) = byte_utils::split_u16(original);
+            self.rom[addr as usize] = high;
+            self.rom[addr as usize + 1] = low;
+        }
+    }
+
     fn read_rom(&self, addr: u32) -> u8 {
         let rom_addr = addr as usize;
         if rom_addr < self.rom.len() {
@@ -281,13 +595,7 @@ impl Bus {
     fn read_z80_area(&mut self, addr: u32) -> u8 {
         match addr {
             // Z80 RAM (8KB)
-            0xA00000..=0xA01FFF => {
-                if self.z80_bus_request {
-                    self.z80_ram[(addr & 0x1FFF) as usize]
-                } else {
-                    0xFF
-                }
-            }
+            0xA00000..=0xA01FFF if self.z80_bus_request => self.z80_ram[(addr & 0x1FFF) as usize],
             // YM2612
             0xA04000..=0xA04003 => self.apu.fm.read((addr & 3) as u8),
             _ => 0xFF,
@@ -322,35 +630,39 @@ impl Bus {
         }
     }
 
-    fn read_vdp_area(&mut self, addr: u32) -> u8 {
-        match addr {
-            0xC00000..=0xC00003 => {
-                // Byte read from data port (even=hi, odd=lo)
-                // Note: Real hardware behavior for byte reads is complex,
-                // but usually we return the appropriate byte of the last data word.
-                if (addr & 1) == 0 {
-                    (self.vdp.read_data() >> 8) as u8
-                } else {
-                    (self.vdp.read_data() & 0xFF) as u8
-                }
-            }
-            0xC00004..=0xC00007 => {
-                let val = self.vdp.read_status();
-                if (addr & 1) == 0 {
-                    (val >> 8) as u8
-                } else {
-                    (val & 0xFF) as u8
-                }
+    fn read_vdp_port_word(&mut self, addr: u32) -> u16 {
+        match addr & 0x1F {
+            0x00..=0x03 => self.vdp.read_data(),
+            0x04..=0x07 => self.vdp.read_status(),
+            0x08..=0x0F => self.vdp.read_hv_counter(),
+            _ => 0xFFFF,
+        }
+    }
+
+    fn write_vdp_port_word(&mut self, addr: u32, value: u16) {
+        match addr & 0x1F {
+            0x00..=0x03 => {
+                self.wait_for_fifo_room();
+                self.vdp.write_data(value)
             }
-            0xC00008..=0xC0000F => {
-                let val = self.vdp.read_hv_counter();
+            0x04..=0x07 => self.vdp.write_control(value),
+            // PSG is wired to the low byte lane
+            0x10..=0x17 => self.apu.psg.write(value as u8),
+            _ => {}
+        }
+    }
+
+    fn read_vdp_area(&mut self, addr: u32) -> u8 {
+        match addr & 0x1F {
+            // Byte reads are full word accesses; return the addressed half
+            0x00..=0x0F => {
+                let val = self.read_vdp_port_word(addr);
                 if (addr & 1) == 0 {
                     (val >> 8) as u8
                 } else {
                     (val & 0xFF) as u8
                 }
             }
-            0xC00010..=0xC00011 => 0xFF,
             _ => 0xFF,
         }
     }
@@ -361,10 +673,8 @@ impl Bus {
 
     fn write_z80_area(&mut self, addr: u32, value: u8) {
         match addr {
-            0xA00000..=0xA01FFF => {
-                if self.z80_bus_request {
-                    self.z80_ram[(addr & 0x1FFF) as usize] = value;
-                }
+            0xA00000..=0xA01FFF if self.z80_bus_request => {
+                self.z80_ram[(addr & 0x1FFF) as usize] = value;
             }
             0xA04000..=0xA04003 => {
                 let port = (addr & 2) >> 1;
@@ -412,6 +722,7 @@ impl Bus {
             0x00..=0x03 => {
                 // Data Port Byte Write: duplicate byte to both halves
                 let val16 = ((value as u16) << 8) | (value as u16);
+                self.wait_for_fifo_room();
                 self.vdp.write_data(val16);
             }
             0x04..=0x07 => {
@@ -419,7 +730,7 @@ impl Bus {
                 let val16 = ((value as u16) << 8) | (value as u16);
                 self.vdp.write_control(val16);
             }
-            0x11 => self.apu.psg.write(value),
+            0x11 | 0x13 | 0x15 | 0x17 => self.apu.psg.write(value),
             _ => {}
         }
     }
@@ -450,18 +761,8 @@ impl Bus {
         }
 
         // VDP Ports
-        if (0xC00000..=0xC0001F).contains(&addr) {
-            let offset = addr & 0x1F;
-            if offset < 4 {
-                return self.vdp.read_data();
-            }
-            if offset < 8 {
-                return self.vdp.read_status();
-            }
-            if offset < 0x10 {
-                return self.vdp.read_hv_counter();
-            }
-            return 0xFFFF;
+        if (0xC00000..=0xC0FFFF).contains(&addr) {
+            return self.read_vdp_port_word(addr);
         }
 
         // Optimize Work RAM access (0xE00000-0xFFFFFF, 64KB mirrored)
@@ -482,12 +783,8 @@ impl Bus {
         let addr = address & 0xFFFFFF;
 
         // VDP Ports
-        if (0xC00000..=0xC00007).contains(&addr) {
-            if (addr & 0x1F) < 4 {
-                self.vdp.write_data(value);
-            } else {
-                self.vdp.write_control(value);
-            }
+        if (0xC00000..=0xC0FFFF).contains(&addr) {
+            self.write_vdp_port_word(addr, value);
             return;
         }
 
@@ -523,16 +820,10 @@ impl Bus {
             }
         }
 
-        // VDP Data Port (Long access = 2 word reads)
-        if addr == 0xC00000 {
-            let high = self.vdp.read_data();
-            let low = self.vdp.read_data();
-            return ((high as u32) << 16) | (low as u32);
-        }
-        // VDP Control Port (Long access)
-        if addr == 0xC00004 {
-            let high = self.vdp.read_status();
-            let low = self.vdp.read_status();
+        // VDP Ports (Long access = 2 word reads)
+        if (0xC00000..=0xC0FFFF).contains(&addr) {
+            let high = self.read_vdp_port_word(addr);
+            let low = self.read_vdp_port_word(addr.wrapping_add(2));
             return ((high as u32) << 16) | (low as u32);
         }
 
@@ -547,21 +838,6 @@ impl Bus {
             }
         }
 
-        // VDP Ports (Unaligned/Other)
-        if (0xC00000..=0xC0001F).contains(&addr) {
-            let offset = addr & 0x1F;
-            // VDP H/V Counter
-            if offset == 8 {
-                let high = self.vdp.read_hv_counter();
-                let low = self.vdp.read_hv_counter();
-                return ((high as u32) << 16) | (low as u32);
-            }
-            // Unaligned/Other VDP Access
-            let high = self.read_word(address);
-            let low = self.read_word(address.wrapping_add(2));
-            return ((high as u32) << 16) | (low as u32);
-        }
-
         let b0 = self.read_byte(address);
         let b1 = self.read_byte(address.wrapping_add(1));
         let b2 = self.read_byte(address.wrapping_add(2));
@@ -573,20 +849,11 @@ impl Bus {
     pub fn write_long(&mut self, address: u32, value: u32) {
         let addr = address & 0xFFFFFF;
 
-        // VDP Data Port (Long access = 2 word writes)
-        if (0xC00000..=0xC00003).contains(&addr) {
-            let high = (value >> 16) as u16;
-            let low = (value & 0xFFFF) as u16;
-            self.vdp.write_data(high);
-            self.vdp.write_data(low);
-            return;
-        }
-
-        // VDP Control Port (Long access)
-        if (0xC00004..=0xC00007).contains(&addr) {
+        // VDP Ports (Long access = 2 word writes)
+        if (0xC00000..=0xC0FFFF).contains(&addr) {
             let (high, low) = byte_utils::split_u32_to_words(value);
-            self.vdp.write_control(high);
-            self.vdp.write_control(low);
+            self.write_vdp_port_word(addr, high);
+            self.write_vdp_port_word(addr.wrapping_add(2), low);
             return;
         }
 
@@ -610,8 +877,147 @@ impl Bus {
         self.write_byte(address.wrapping_add(3), b3);
     }
 
+    // Explicit byte-order views for tools. The 68k bus is big-endian, so the
+    // `_be` methods are the plain `read_word`/`read_long` under a name that
+    // says so; the `_le` ones return the same bytes assembled the other way,
+    // for host-side formats that expect little-endian values.
+
+    /// Word at `address` with the byte at `address` as the high byte, as the
+    /// 68k sees it
+    pub fn read_word_be(&mut self, address: u32) -> u16 {
+        self.read_word(address)
+    }
+
+    /// Long at `address` with the byte at `address` as the most significant,
+    /// as the 68k sees it
+    pub fn read_long_be(&mut self, address: u32) -> u32 {
+        self.read_long(address)
+    }
+
+    /// Word at `address` with the byte at `address` as the low byte
+    pub fn read_word_le(&mut self, address: u32) -> u16 {
+        self.read_word(address).swap_bytes()
+    }
+
+    /// Long at `address` with the byte at `address` as the least significant
+    pub fn read_long_le(&mut self, address: u32) -> u32 {
+        self.read_long(address).swap_bytes()
+    }
+
+    /// Store `value` high byte first, as a 68k MOVE.W would
+    pub fn write_word_be(&mut self, address: u32, value: u16) {
+        self.write_word(address, value);
+    }
+
+    /// Store `value` most significant byte first, as a 68k MOVE.L would
+    pub fn write_long_be(&mut self, address: u32, value: u32) {
+        self.write_long(address, value);
+    }
+
+    /// Store `value` low byte first
+    pub fn write_word_le(&mut self, address: u32, value: u16) {
+        self.write_word(address, value.swap_bytes());
+    }
+
+    /// Store `value` least significant byte first
+    pub fn write_long_le(&mut self, address: u32, value: u32) {
+        self.write_long(address, value.swap_bytes());
+    }
+
+    /// A peripheral drives TH on controller `port`. With the port's TH
+    /// interrupt enabled, a falling edge asserts HL on the VDP.
+    pub fn drive_th(&mut self, port: u8, level: bool) {
+        if self.io.drive_th(port, level) {
+            self.vdp.external_interrupt();
+        }
+    }
+
     /// Advance system state by N MCLK cycles.
     pub fn tick(&mut self, mclk: u32) {
+        let lead = self.vdp_lead.min(mclk);
+        self.vdp_lead -= lead;
+        self.tick_vdp(mclk - lead);
+
+        // Controller handshake timeouts are counted in 68k cycles (MCLK/7)
+        self.io.update(mclk / 7);
+
+        // Z80 runs at MCLK/15
+        // APU timing: sampled by advance_audio
+    }
+
+    /// Master clock frequency of the current region
+    fn master_clock(&self) -> u64 {
+        if self.vdp.is_pal {
+            crate::audio::PAL_MCLK
+        } else {
+            crate::audio::NTSC_MCLK
+        }
+        .into()
+    }
+
+    /// Remainder of the nominal line lengths' sample shares carried into
+    /// the next line, in master clocks scaled by the sample rate
+    pub fn audio_phase(&self) -> u64 {
+        self.audio_phase
+    }
+
+    /// Owe the samples a nominal 3420-MCLK line is worth, carrying the
+    /// fraction, so each frame samples exactly its share of the rate no
+    /// matter how far the CPU overran its lines
+    pub fn begin_audio_line(&mut self) {
+        let master_clock = self.master_clock();
+        self.audio_phase += 3420 * u64::from(self.sample_rate);
+        self.audio_line_budget = (self.audio_phase / master_clock) as u32;
+        self.audio_phase %= master_clock;
+        self.audio_accumulator = 0;
+    }
+
+    /// Sample the APU as `mclk` more master clocks fall due, up to what the
+    /// line owes
+    pub fn advance_audio(&mut self, mclk: u32) {
+        let master_clock = self.master_clock();
+        self.audio_accumulator += u64::from(mclk) * u64::from(self.sample_rate);
+        while self.audio_accumulator >= master_clock && self.audio_line_budget > 0 {
+            self.push_audio_sample();
+            self.audio_accumulator -= master_clock;
+        }
+    }
+
+    /// Take the samples the line still owes when the CPU ran it short
+    pub fn finish_audio_line(&mut self) {
+        while self.audio_line_budget > 0 {
+            self.push_audio_sample();
+        }
+    }
+
+    fn push_audio_sample(&mut self) {
+        let (l, r) = self.apu.generate_sample();
+        if self.audio_buffer.len() < 32768 {
+            self.audio_buffer.push(l);
+            self.audio_buffer.push(r);
+        }
+        self.audio_line_budget -= 1;
+    }
+
+    /// Hold a data port write until the FIFO has room, as the 68k is held
+    /// on hardware. The VDP runs ahead through its access slots meanwhile,
+    /// and the time spent is charged to the CPU as wait cycles.
+    fn wait_for_fifo_room(&mut self) {
+        // Every line has external slots; past two lines something is wrong
+        // and the VDP falls back to committing the oldest word itself
+        const MAX_WAIT_MCLK: u32 = 2 * 3420;
+        // At least one slot boundary per step in either mode
+        let step = if self.vdp.h40_mode() { 17 } else { 20 };
+        let mut waited = 0;
+        while self.vdp.fifo_full && !self.vdp.bypass_fifo && waited < MAX_WAIT_MCLK {
+            self.tick_vdp(step);
+            waited += step;
+        }
+        self.vdp_lead += waited;
+        self.wait_cycles = self.wait_cycles.saturating_add(waited.div_ceil(7));
+    }
+
+    fn tick_vdp(&mut self, mclk: u32) {
         let rom = &self.rom;
         let work_ram = &self.work_ram;
 
@@ -636,37 +1042,69 @@ impl Bus {
                 0xFFFF // Unmapped or unused DMA source
             }
         });
+    }
+}
 
-        // Z80 runs at MCLK/15
-        // APU timing: handled by audio_accumulator currently.
+impl Bus {
+    #[inline(always)]
+    fn charge_access(&mut self, address: u32, accesses: u32) {
+        self.wait_cycles = self
+            .wait_cycles
+            .saturating_add(self.wait_states.for_address(address) * accesses);
+    }
+
+    #[inline(always)]
+    fn count_io_polls(&mut self, address: u32, len: u32) {
+        if (0xA10000..=0xA1001F).contains(&(address & 0xFFFFFF)) {
+            self.io.count_polls(address & 0xFFFFFF, len);
+        }
     }
 }
 
+// Wait states and controller polls are counted here rather than in the
+// inherent methods so a word access that falls back to byte reads still
+// counts as one bus cycle, and debugger peeks through the inherent methods
+// count as neither
 impl MemoryInterface for Bus {
     #[inline(always)]
     fn read_byte(&mut self, address: u32) -> u8 {
+        self.charge_access(address, 1);
+        self.count_io_polls(address, 1);
         self.read_byte(address)
     }
     #[inline(always)]
     fn write_byte(&mut self, address: u32, value: u8) {
+        self.charge_access(address, 1);
         self.write_byte(address, value)
     }
     #[inline(always)]
     fn read_word(&mut self, address: u32) -> u16 {
+        self.charge_access(address, 1);
+        self.count_io_polls(address, 2);
         self.read_word(address)
     }
     #[inline(always)]
     fn write_word(&mut self, address: u32, value: u16) {
+        self.charge_access(address, 1);
         self.write_word(address, value)
     }
     #[inline(always)]
     fn read_long(&mut self, address: u32) -> u32 {
+        self.charge_access(address, 2);
+        self.count_io_polls(address, 4);
         self.read_long(address)
     }
     #[inline(always)]
     fn write_long(&mut self, address: u32, value: u32) {
+        self.charge_access(address, 2);
         self.write_long(address, value)
     }
+    fn take_wait_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.wait_cycles)
+    }
+    fn note_access(&mut self, address: u32) {
+        self.charge_access(address, 1);
+    }
 }
 
 impl Debuggable for Bus {
@@ -714,8 +1152,8 @@ impl Debuggable for Bus {
             }
         }
         if let Some(val) = state.get("audio_accumulator") {
-            if let Some(f) = val.as_f64() {
-                self.audio_accumulator = f as f32;
+            if let Some(u) = val.as_u64() {
+                self.audio_accumulator = u;
             }
         }
         if let Some(val) = state.get("sample_rate") {
@@ -754,6 +1192,25 @@ impl Debuggable for Bus {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_explicit_byte_order_views() {
+        let mut bus = Bus::new();
+        bus.write_long_be(0xFF0000, 0x1234_5678);
+        let bytes: Vec<u8> = (0..4).map(|i| bus.read_byte(0xFF0000 + i)).collect();
+        assert_eq!(bytes, [0x12, 0x34, 0x56, 0x78], "68k order: MSB first");
+
+        assert_eq!(bus.read_long_be(0xFF0000), 0x1234_5678);
+        assert_eq!(bus.read_word_be(0xFF0000), 0x1234);
+        assert_eq!(bus.read_long_le(0xFF0000), 0x7856_3412);
+        assert_eq!(bus.read_word_le(0xFF0002), 0x7856);
+
+        bus.write_long_le(0xFF0004, 0x1234_5678);
+        assert_eq!(bus.read_long_be(0xFF0004), 0x7856_3412);
+        assert_eq!(bus.read_byte(0xFF0004), 0x78);
+        bus.write_word_le(0xFF0008, 0xABCD);
+        assert_eq!(bus.read_word_be(0xFF0008), 0xCDAB);
+    }
+
     #[test]
     fn test_tmss_unlock_byte_writes() {
         let mut bus = Bus::new();
@@ -796,7 +1253,7 @@ mod tests {
                 bus.z80_reset = false;
                 bus.z80_bank_addr = 0x12345;
                 bus.tmss_unlocked = true;
-                bus.audio_accumulator = 1.234;
+                bus.audio_accumulator = 1234;
                 bus.sample_rate = 48000;
 
                 // Modify RAM
@@ -832,7 +1289,7 @@ mod tests {
                 assert!(!new_bus.z80_reset);
                 assert_eq!(new_bus.z80_bank_addr, 0x12345);
                 assert!(new_bus.tmss_unlocked);
-                assert!((new_bus.audio_accumulator - 1.234).abs() < 1e-6);
+                assert_eq!(new_bus.audio_accumulator, 1234);
                 assert_eq!(new_bus.sample_rate, 48000);
 
                 // Assert RAM equality
@@ -851,6 +1308,56 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_bus_state_with_float_audio_accumulator_still_loads() {
+        std::thread::Builder::new()
+            .stack_size(16 * 1024 * 1024)
+            .spawn(|| {
+                let mut bus = Bus::new();
+                bus.audio_accumulator = 1234;
+                bus.work_ram[0] = 0xAA;
+                let mut state = serde_json::to_value(&bus).unwrap();
+                let restored: Bus = serde_json::from_value(state.clone()).unwrap();
+                assert_eq!(restored.audio_accumulator, 1234);
+
+                // As saved before the accumulator was scaled
+                state["audio_accumulator"] = json!(1.5);
+                let restored: Bus = serde_json::from_value(state.clone()).unwrap();
+                assert_eq!(restored.audio_accumulator, 0);
+                assert_eq!(restored.work_ram[0], 0xAA);
+
+                state.as_object_mut().unwrap().remove("audio_accumulator");
+                let restored: Bus = serde_json::from_value(state).unwrap();
+                assert_eq!(restored.audio_accumulator, 0);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tick_times_out_6button_handshake() {
+        use crate::io::{ControllerType, TH_TIMEOUT_CYCLES};
+
+        let mut bus = Bus::new();
+        bus.io.set_controller_type(1, ControllerType::SixButton);
+
+        // Two TH pulses via the data port
+        for _ in 0..2 {
+            bus.write_byte(0xA10003, 0x00);
+            bus.write_byte(0xA10003, 0x40);
+        }
+        assert_eq!(bus.io.port1.th_counter, 2);
+
+        // A short gap keeps the handshake alive
+        bus.tick(1000 * 7);
+        assert_eq!(bus.io.port1.th_counter, 2);
+
+        // A gap longer than the timeout, with no TH activity, resets it
+        bus.tick(TH_TIMEOUT_CYCLES * 7);
+        assert_eq!(bus.io.port1.th_counter, 0);
+    }
+
     #[test]
     fn test_clear_rom() {
         let mut bus = Bus::new();
@@ -872,4 +1379,172 @@ mod tests {
         bus.clear_rom();
         assert_eq!(bus.rom_size(), 0);
     }
+
+    #[test]
+    fn test_memory_map_boundaries() {
+        let mut bus = Bus::new();
+        let map = bus.memory_map();
+        let find = |device| {
+            map.iter()
+                .find(|region| region.device == device)
+                .map(|region| (region.start, region.end, region.writable))
+        };
+        assert_eq!(find(MemoryDevice::Rom), Some((0x000000, 0x3FFFFF, false)));
+        assert_eq!(
+            find(MemoryDevice::WorkRam),
+            Some((0xE00000, 0xFFFFFF, true))
+        );
+        assert_eq!(find(MemoryDevice::Z80Ram), Some((0xA00000, 0xA01FFF, true)));
+        assert_eq!(find(MemoryDevice::Vdp), Some((0xC00000, 0xC0FFFF, true)));
+        assert_eq!(find(MemoryDevice::Io), Some((0xA10000, 0xA1001F, true)));
+        assert!(!map.iter().any(|r| r.name == "SRAM"));
+        assert!(map.windows(2).all(|w| w[0].end < w[1].start));
+        assert!(!map.iter().any(|r| r.contains(0x800000)), "Open bus");
+
+        bus.write_byte(0xA130F1, 0x01);
+        let sram = bus.memory_map()[1];
+        assert_eq!(
+            (sram.name, sram.start, sram.end),
+            ("SRAM", 0x200000, 0x20FFFF)
+        );
+    }
+
+    /// 68k cycles spent writing 20 words to VRAM from the top of line 0
+    fn vram_burst_wait(display: bool) -> (Bus, u32) {
+        let mut bus = Bus::new();
+        // H40, auto-increment 2
+        bus.write_word(0xC00004, 0x8C81);
+        bus.write_word(0xC00004, 0x8F02);
+        bus.write_word(0xC00004, if display { 0x8144 } else { 0x8104 });
+        // VRAM write to 0x0000
+        bus.write_long(0xC00004, 0x4000_0000);
+        MemoryInterface::take_wait_cycles(&mut bus);
+        for i in 0..20u16 {
+            MemoryInterface::write_word(&mut bus, 0xC00000, 0x1000 + i);
+        }
+        let wait = MemoryInterface::take_wait_cycles(&mut bus);
+        (bus, wait)
+    }
+
+    #[test]
+    fn test_active_display_vram_writes_are_paced_by_access_slots() {
+        let (mut bus, wait) = vram_burst_wait(true);
+        // 16 words had to drain through the sparse external slots first
+        assert!(wait > 300, "stalled only {} cycles", wait);
+        assert_eq!(bus.vdp.fifo.len(), 4);
+        assert_eq!(&bus.vdp.vram[30..32], &[0x10, 0x0F]);
+        assert_eq!(&bus.vdp.vram[32..34], &[0, 0], "word 16 is still queued");
+
+        // The VDP was run ahead by the stall; that time isn't ticked twice
+        let lead = bus.vdp_lead;
+        bus.tick(lead);
+        assert_eq!(bus.vdp_lead, 0);
+        assert_eq!(bus.vdp.fifo.len(), 4);
+        bus.tick(3420);
+        assert!(bus.vdp.fifo.is_empty());
+        assert_eq!(&bus.vdp.vram[38..40], &[0x10, 0x13]);
+
+        // With the display off every slot is free, so the same burst
+        // barely waits
+        let (_, blanked_wait) = vram_burst_wait(false);
+        assert!(blanked_wait * 4 < wait, "{} vs {}", blanked_wait, wait);
+    }
+
+    #[test]
+    fn test_out_of_range_reset_pc_is_reported_but_still_boots() {
+        use crate::cpu::Cpu;
+
+        let mut rom = vec![0u8; 0x400];
+        rom[0..4].copy_from_slice(&0x00FF_FE00u32.to_be_bytes());
+        rom[4..8].copy_from_slice(&0x0000_0200u32.to_be_bytes());
+        rom[0x1F0..0x1F3].copy_from_slice(b"J  ");
+        let mut bus = Bus::new();
+        bus.load_rom(&rom);
+        assert!(bus.reset_vector_problems().is_empty());
+
+        rom[4..8].copy_from_slice(&0x0050_0000u32.to_be_bytes());
+        bus.load_rom(&rom);
+        let problems = bus.reset_vector_problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("reset PC 00500000"), "{}", problems[0]);
+        assert!(problems[0].contains("\"J\""), "{}", problems[0]);
+
+        let cpu = Cpu::new(&mut bus);
+        assert_eq!((cpu.a[7], cpu.pc), (0x00FF_FE00, 0x0050_0000));
+
+        // An odd stack and an odd entry point are both reported
+        rom[0..4].copy_from_slice(&0x00FF_FE01u32.to_be_bytes());
+        rom[4..8].copy_from_slice(&0x0000_0201u32.to_be_bytes());
+        bus.load_rom(&rom);
+        assert_eq!(bus.reset_vector_problems().len(), 2);
+    }
+
+    #[test]
+    fn test_reset_vector_problems_without_a_full_header() {
+        let mut bus = Bus::new();
+        let problems = bus.reset_vector_problems();
+        assert_eq!(problems, ["0-byte ROM too short for a vector table/header"]);
+
+        // Vectors but no region field; load_rom pads, so set it directly
+        bus.rom = vec![0u8; 0x100];
+        let problems = bus.reset_vector_problems();
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].contains("256-byte ROM too short"),
+            "{}",
+            problems[0]
+        );
+    }
+
+    #[test]
+    fn test_wait_states_charge_slow_region_accesses() {
+        use crate::cpu::Cpu;
+
+        let mut bus = Bus::new();
+        bus.load_rom(&[0u8; 0x400]);
+        bus.wait_states.rom = 2;
+        // MOVE.W (A0),D0 running from work RAM
+        bus.write_word(0xFF1000, 0x3010);
+
+        let mut timed = |source: u32| {
+            let mut cpu = Cpu::new(&mut bus);
+            cpu.pc = 0xFF1000;
+            cpu.a[0] = source;
+            cpu.step_instruction(&mut bus)
+        };
+        let from_ram = timed(0xFF2000);
+        let from_rom = timed(0x000100);
+        assert_eq!(from_rom, from_ram + 2, "One word read from ROM");
+
+        // Opcode fetches from ROM pay too, even when served from the cache
+        bus.load_rom(&[0x4E, 0x71, 0x4E, 0x71]); // NOP
+        let mut cpu = Cpu::new(&mut bus);
+        cpu.pc = 0;
+        let first = cpu.step_instruction(&mut bus);
+        cpu.pc = 0;
+        assert_eq!(cpu.step_instruction(&mut bus), first);
+        assert_eq!(first, 4 + 2);
+    }
+
+    #[test]
+    fn test_rom_region_is_write_protected() {
+        let mut bus = Bus::new();
+        let mut rom = vec![0u8; 0x400];
+        rom[0x100..0x108].copy_from_slice(&[0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0]);
+        bus.load_rom(&rom);
+
+        bus.write_byte(0x000100, 0xFF);
+        bus.write_word(0x000102, 0xFFFF);
+        bus.write_long(0x000104, 0xFFFF_FFFF);
+        assert_eq!(bus.read_long(0x000100), 0x12345678);
+        assert_eq!(bus.read_long(0x000104), 0x9ABCDEF0);
+        assert_eq!(&bus.rom[0x100..0x108], &rom[0x100..0x108]);
+
+        // Work RAM and enabled SRAM stay writable
+        bus.write_word(0xFF0000, 0xBEEF);
+        assert_eq!(bus.read_word(0xFF0000), 0xBEEF);
+        bus.write_byte(0xA130F1, 0x01);
+        bus.write_byte(0x200000, 0x5A);
+        assert_eq!(bus.read_byte(0x200000), 0x5A);
+    }
 }
@@ -0,0 +1,166 @@
+//! Program Images
+//!
+//! Homebrew built with a linker isn't always laid out as a cartridge with
+//! its vector table at 0. A program image is a set of segments placed at
+//! their own addresses plus an entry point, built from either a raw binary
+//! and a load address or a big-endian 68000 ELF executable.
+//!
+//! Segments may target cartridge ROM (0x000000-0x3FFFFF) or work RAM
+//! (0xFF0000-0xFFFFFF); see `Bus::load_image`.
+
+/// ELF program header type for loadable segments
+const PT_LOAD: u32 = 1;
+/// ELF machine number for the Motorola 68000 family
+const EM_68K: u16 = 4;
+
+/// Bytes placed at a fixed address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+impl Segment {
+    /// One past the last byte of the segment
+    pub fn end(&self) -> u64 {
+        self.address as u64 + self.data.len() as u64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramImage {
+    pub segments: Vec<Segment>,
+    /// Initial PC
+    pub entry: u32,
+}
+
+impl ProgramImage {
+    /// A raw binary placed at `load_address` and entered at its first byte
+    pub fn from_binary(data: &[u8], load_address: u32) -> Self {
+        Self {
+            segments: vec![Segment {
+                address: load_address,
+                data: data.to_vec(),
+            }],
+            entry: load_address,
+        }
+    }
+
+    /// Parse an ELF executable if `data` has the ELF magic, otherwise treat it
+    /// as a raw binary at `load_address`
+    pub fn parse(data: &[u8], load_address: u32) -> Result<Self, String> {
+        if data.starts_with(b"\x7FELF") {
+            Self::from_elf(data)
+        } else {
+            Ok(Self::from_binary(data, load_address))
+        }
+    }
+
+    /// Load the PT_LOAD segments of a 32-bit big-endian 68000 ELF.
+    ///
+    /// Segments go to their physical (load) address, so initialised data
+    /// linked to run from RAM stays in ROM for the startup code to copy.
+    /// Space between the file and memory sizes is zero-filled.
+    pub fn from_elf(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 52 || !data.starts_with(b"\x7FELF") {
+            return Err("Not an ELF file".to_string());
+        }
+        if data[4] != 1 || data[5] != 2 {
+            return Err("ELF must be 32-bit big-endian".to_string());
+        }
+        if be16(data, 18)? != EM_68K {
+            return Err("ELF is not a 68000 executable".to_string());
+        }
+
+        let entry = be32(data, 24)?;
+        let phoff = be32(data, 28)? as usize;
+        let phentsize = be16(data, 42)? as usize;
+        let phnum = be16(data, 44)? as usize;
+        if phentsize < 32 {
+            return Err(format!("Bad ELF program header size {}", phentsize));
+        }
+
+        let mut segments = Vec::new();
+        for i in 0..phnum {
+            let ph = phoff + i * phentsize;
+            if be32(data, ph)? != PT_LOAD {
+                continue;
+            }
+            let offset = be32(data, ph + 4)? as usize;
+            let address = be32(data, ph + 12)?;
+            let file_size = be32(data, ph + 16)? as usize;
+            let mem_size = be32(data, ph + 20)? as usize;
+            if mem_size == 0 {
+                continue;
+            }
+            if file_size > mem_size || mem_size > 0x1000000 {
+                return Err(format!("Bad size for ELF segment {}", i));
+            }
+            let bytes = offset
+                .checked_add(file_size)
+                .and_then(|end| data.get(offset..end))
+                .ok_or_else(|| format!("ELF segment {} runs past the end of the file", i))?;
+
+            let mut segment = bytes.to_vec();
+            segment.resize(mem_size, 0);
+            segments.push(Segment {
+                address,
+                data: segment,
+            });
+        }
+
+        if segments.is_empty() {
+            return Err("ELF has no loadable segments".to_string());
+        }
+        Ok(Self { segments, entry })
+    }
+}
+
+fn be16(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Truncated ELF header".to_string())
+}
+
+fn be32(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Truncated ELF header".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_binary_enters_at_load_address() {
+        let image = ProgramImage::parse(&[0x60, 0xFE], 0x1000).unwrap();
+        assert_eq!(image.entry, 0x1000);
+        assert_eq!(
+            image.segments,
+            vec![Segment {
+                address: 0x1000,
+                data: vec![0x60, 0xFE]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_elf_rejects_wrong_class_and_machine() {
+        let mut header = vec![0u8; 52];
+        header[..4].copy_from_slice(b"\x7FELF");
+        header[4] = 1;
+        header[5] = 1; // little-endian
+        assert!(ProgramImage::from_elf(&header).is_err());
+
+        header[5] = 2;
+        header[18..20].copy_from_slice(&3u16.to_be_bytes()); // x86
+        assert!(ProgramImage::from_elf(&header).is_err());
+
+        header[18..20].copy_from_slice(&EM_68K.to_be_bytes());
+        assert_eq!(
+            ProgramImage::from_elf(&header),
+            Err("Bad ELF program header size 0".to_string())
+        );
+    }
+}
@@ -7,6 +7,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 pub mod bus;
 pub mod byte_utils;
+pub mod image;
 pub mod tests_performance;
 pub mod z80_bus;
 use bus::Bus;
@@ -48,6 +49,16 @@ pub trait MemoryInterface: std::fmt::Debug {
             Size::Long => self.write_long(address, value),
         }
     }
+
+    /// Wait-state cycles owed for the accesses made since the last call.
+    /// Memory without a timing model never adds any.
+    fn take_wait_cycles(&mut self) -> u32 {
+        0
+    }
+
+    /// Account for an access the CPU answered without touching memory, such
+    /// as an opcode fetch served from its decode cache
+    fn note_access(&mut self, _address: u32) {}
 }
 
 pub trait IoInterface: std::fmt::Debug {
@@ -81,6 +92,12 @@ impl MemoryInterface for Box<dyn MemoryInterface> {
     fn write_long(&mut self, address: u32, value: u32) {
         (**self).write_long(address, value);
     }
+    fn take_wait_cycles(&mut self) -> u32 {
+        (**self).take_wait_cycles()
+    }
+    fn note_access(&mut self, address: u32) {
+        (**self).note_access(address);
+    }
 }
 
 // Blanket impl for Box<T> where T: MemoryInterface
@@ -103,6 +120,12 @@ impl<T: MemoryInterface> MemoryInterface for Box<T> {
     fn write_long(&mut self, address: u32, value: u32) {
         (**self).write_long(address, value);
     }
+    fn take_wait_cycles(&mut self) -> u32 {
+        (**self).take_wait_cycles()
+    }
+    fn note_access(&mut self, address: u32) {
+        (**self).note_access(address);
+    }
 }
 
 // Blanket impl for Box<dyn IoInterface>
@@ -189,6 +212,14 @@ impl MemoryInterface for SharedBus {
     fn write_long(&mut self, address: u32, value: u32) {
         self.bus.borrow_mut().write_long(address, value);
     }
+
+    fn take_wait_cycles(&mut self) -> u32 {
+        MemoryInterface::take_wait_cycles(&mut *self.bus.borrow_mut())
+    }
+
+    fn note_access(&mut self, address: u32) {
+        MemoryInterface::note_access(&mut *self.bus.borrow_mut(), address);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -136,4 +136,125 @@ mod tests {
         let val2 = bus.read_word(0xC00000);
         assert_eq!(val2, 0x2222, "Subsequent data read should be 0x2222");
     }
+
+    #[test]
+    fn test_vdp_control_byte_write_duplicates_byte() {
+        let mut bus = Bus::new();
+        bus.vdp.bypass_fifo = true;
+
+        // A word write sets register 1 from the low byte
+        bus.write_word(0xC00004, 0x8114);
+        assert_eq!(bus.vdp.registers[1], 0x14);
+
+        // A byte write puts the byte on both lanes: 0x81 becomes 0x8181
+        bus.write_byte(0xC00004, 0x81);
+        assert_eq!(bus.vdp.registers[1], 0x81);
+
+        // The odd address drives the same duplicated word
+        bus.write_byte(0xC00005, 0x8F);
+        assert_eq!(bus.vdp.registers[15], 0x8F);
+        assert_eq!(bus.vdp.registers[1], 0x81);
+    }
+
+    #[test]
+    fn test_vdp_ports_mirror_for_all_access_sizes() {
+        let mut bus = Bus::new();
+        bus.vdp.bypass_fifo = true;
+
+        // Word write through a mirror must not be split into duplicated bytes
+        bus.write_word(0xC00104, 0x8114);
+        assert_eq!(bus.vdp.registers[1], 0x14);
+
+        bus.write_long(0xC00204, 0x8F028C81);
+        assert_eq!(bus.vdp.registers[15], 0x02);
+        assert_eq!(bus.vdp.registers[12], 0x81);
+
+        let status = bus.read_word(0xC00004);
+        assert_eq!(bus.read_word(0xC00304) & 0xFF00, status & 0xFF00);
+        assert_eq!(bus.read_byte(0xC00304), (status >> 8) as u8);
+    }
+
+    #[test]
+    fn test_psg_uses_low_byte_lane() {
+        let mut bus = Bus::new();
+
+        // Word write delivers the low byte to the PSG
+        bus.write_word(0xC00010, 0xFFC0);
+        assert_eq!(bus.apu.psg.latch_channel, 2);
+
+        // Even byte writes do not reach the PSG
+        bus.write_byte(0xC00010, 0xA0);
+        assert_eq!(bus.apu.psg.latch_channel, 2);
+
+        bus.write_byte(0xC00013, 0xA0);
+        assert_eq!(bus.apu.psg.latch_channel, 1);
+    }
+
+    #[test]
+    fn test_io_even_byte_reads_mirror_odd() {
+        let mut bus = Bus::new();
+        assert_eq!(bus.read_byte(0xA10000), bus.read_byte(0xA10001));
+        let version = bus.io.version as u16;
+        assert_eq!(bus.read_word(0xA10000), (version << 8) | version);
+
+        // Even byte writes are ignored; the odd address holds the register
+        bus.write_byte(0xA10008, 0x40);
+        assert_eq!(bus.io.port1.control, 0x00);
+        bus.write_byte(0xA10009, 0x40);
+        assert_eq!(bus.io.port1.control, 0x40);
+        assert_eq!(bus.read_byte(0xA10008), 0x40);
+    }
+
+    #[test]
+    fn test_th_pulse_latches_hv_and_raises_external_interrupt() {
+        let mut bus = Bus::new();
+        bus.vdp.bypass_fifo = true;
+        bus.write_word(0xC00004, 0x8006); // Reg 0: HV latch enable
+        bus.write_word(0xC00004, 0x8B08); // Reg 11: external interrupt enable
+        bus.write_byte(0xA10009, 0x80); // Port 1: TH input, TH interrupt enable
+
+        bus.vdp.set_v_counter(0x40);
+        let live = bus.read_word(0xC00008);
+        bus.drive_th(1, false);
+        assert!(bus.vdp.ext_int_pending());
+        assert_eq!(bus.vdp.hv_latch, Some(live));
+
+        // Reads return the latched value while the beam moves on
+        bus.vdp.set_v_counter(0x80);
+        assert_eq!(bus.read_word(0xC00008), live);
+
+        // Releasing TH is a rising edge and doesn't re-latch
+        bus.vdp.acknowledge_ext_int();
+        bus.drive_th(1, true);
+        assert!(!bus.vdp.ext_int_pending());
+        assert_eq!(bus.read_word(0xC00008), live);
+    }
+
+    #[test]
+    fn test_th_pulse_needs_port_and_vdp_enables() {
+        let mut bus = Bus::new();
+        bus.vdp.bypass_fifo = true;
+        bus.write_word(0xC00004, 0x8B08);
+
+        // TH interrupt not enabled on the port
+        bus.write_byte(0xA10009, 0x00);
+        bus.drive_th(1, false);
+        assert!(!bus.vdp.ext_int_pending);
+
+        // TH configured as output: the peripheral can't drive it
+        bus.drive_th(1, true);
+        bus.write_byte(0xA10009, 0xC0);
+        bus.drive_th(1, false);
+        assert!(!bus.vdp.ext_int_pending);
+
+        // HL asserted, but IE2 clear in mode register 3
+        bus.write_word(0xC00004, 0x8B00);
+        bus.write_byte(0xA10009, 0x80);
+        bus.drive_th(1, true);
+        bus.drive_th(1, false);
+        assert!(bus.vdp.ext_int_pending);
+        assert!(!bus.vdp.ext_int_pending());
+        // Latch disabled: no capture
+        assert_eq!(bus.vdp.hv_latch, None);
+    }
 }
@@ -21,6 +21,17 @@ pub struct Z80Bus {
     /// during active emulation cycles where the caller already holds a borrow.
     #[serde(skip)]
     borrowed_bus: Option<std::ptr::NonNull<Bus>>,
+
+    /// Wait T-states added to each access to the banked 68k window while
+    /// the 68k side holds the bus; zero disables contention
+    #[serde(skip)]
+    contention_penalty: u32,
+    /// Set by the scheduler while the 68k bus is held (e.g. by a
+    /// 68k-to-VRAM DMA), so banked accesses stall
+    #[serde(skip)]
+    bus_contended: bool,
+    #[serde(skip)]
+    wait_cycles: u32,
 }
 
 impl Z80Bus {
@@ -29,6 +40,9 @@ impl Z80Bus {
         Self {
             bus,
             borrowed_bus: None,
+            contention_penalty: 0,
+            bus_contended: false,
+            wait_cycles: 0,
         }
     }
 }
@@ -51,6 +65,28 @@ impl Z80Bus {
         self.borrowed_bus = None;
     }
 
+    /// Wait T-states charged per banked-window access during contention
+    pub fn set_contention_penalty(&mut self, t_states: u32) {
+        self.contention_penalty = t_states;
+    }
+
+    pub fn contention_penalty(&self) -> u32 {
+        self.contention_penalty
+    }
+
+    /// Mark whether the 68k currently holds the bus
+    pub fn set_bus_contended(&mut self, contended: bool) {
+        self.bus_contended = contended;
+    }
+
+    /// Charge the contention penalty for an access to the banked window
+    #[inline(always)]
+    fn note_banked_access(&mut self, address: u32) {
+        if self.bus_contended && address as u16 >= 0x8000 {
+            self.wait_cycles = self.wait_cycles.saturating_add(self.contention_penalty);
+        }
+    }
+
     /// The value written becomes the upper bits of the 68k address
     pub fn set_bank(&mut self, value: u8) {
         if let Some(mut bus) = self.borrowed_bus {
@@ -152,6 +188,7 @@ impl Z80Bus {
 
 impl MemoryInterface for Z80Bus {
     fn read_byte(&mut self, address: u32) -> u8 {
+        self.note_banked_access(address);
         if let Some(mut bus) = self.borrowed_bus {
             unsafe { Self::read_byte_from_bus(bus.as_mut(), address) }
         } else {
@@ -160,6 +197,7 @@ impl MemoryInterface for Z80Bus {
     }
 
     fn write_byte(&mut self, address: u32, value: u8) {
+        self.note_banked_access(address);
         if let Some(mut bus) = self.borrowed_bus {
             unsafe { Self::write_byte_to_bus(bus.as_mut(), address, value) }
         } else {
@@ -206,6 +244,10 @@ impl MemoryInterface for Z80Bus {
             crate::cpu::decoder::Size::Long => self.write_long(address, value),
         }
     }
+
+    fn take_wait_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.wait_cycles)
+    }
 }
 
 impl IoInterface for Z80Bus {
@@ -445,4 +487,32 @@ mod tests {
             assert_eq!(bus.z80_bank_addr, 0x0AA << 15);
         }
     }
+
+    #[test]
+    fn test_contention_stalls_banked_accesses() {
+        use crate::z80::Z80;
+
+        let z80_bus = create_test_z80_bus();
+        let mut z80 = Z80::new(z80_bus.clone(), z80_bus);
+        // LD A,($8000) ; LD A,($1000) ; LD A,($8000)
+        for (i, &b) in [0x3A, 0x00, 0x80, 0x3A, 0x00, 0x10, 0x3A, 0x00, 0x80]
+            .iter()
+            .enumerate()
+        {
+            z80.memory.write_byte(i as u32, b);
+        }
+        z80.memory.set_contention_penalty(3);
+
+        assert_eq!(
+            z80.step(),
+            13,
+            "no stall while the 68k leaves the bus alone"
+        );
+
+        z80.memory.set_bus_contended(true);
+        assert_eq!(z80.step(), 13, "Z80 RAM isn't behind the 68k bus");
+        let before = z80.cycles;
+        assert_eq!(z80.step(), 16, "banked read stalls by the penalty");
+        assert_eq!(z80.cycles - before, 16);
+    }
 }
@@ -0,0 +1,126 @@
+//! Test support for exercising whole emulators from this crate's tests.
+
+use crate::input::FrameInput;
+use crate::{Emulator, StateHasher};
+
+/// Boot `rom` in two fresh emulators, feed both the same `inputs` (one per
+/// frame, then none) and compare their state hashes after every one of
+/// `frames` frames. Panics at the first frame they differ on, naming the
+/// parts of the machine that diverged. Returns the per-frame hashes.
+pub fn assert_deterministic(rom: &[u8], inputs: &[FrameInput], frames: usize) -> Vec<u64> {
+    assert_runs_match(rom, inputs, inputs, frames)
+}
+
+/// As `assert_deterministic`, but the second run is fed `inputs_b`, so the
+/// tests below can check that a divergence is caught.
+fn assert_runs_match(
+    rom: &[u8],
+    inputs_a: &[FrameInput],
+    inputs_b: &[FrameInput],
+    frames: usize,
+) -> Vec<u64> {
+    fn part_hash<T: serde::Serialize + ?Sized>(part: &T) -> u64 {
+        let mut hasher = StateHasher::new();
+        serde_json::to_writer(&mut hasher, part).expect("machine state serializes");
+        hasher.0
+    }
+    let boot = || {
+        let mut emulator = Emulator::new();
+        {
+            let mut bus = emulator.bus.borrow_mut();
+            bus.load_rom(rom);
+            emulator.cpu.reset(&mut *bus);
+        }
+        emulator
+    };
+    let mut a = boot();
+    let mut b = boot();
+
+    let mut hashes = Vec::with_capacity(frames);
+    for frame in 0..frames {
+        a.step_frame(inputs_a.get(frame));
+        b.step_frame(inputs_b.get(frame));
+        let hash = a.state_hash();
+        if hash != b.state_hash() {
+            let bus_a = a.bus.borrow();
+            let bus_b = b.bus.borrow();
+            let diverged: Vec<&str> = [
+                ("68k", part_hash(&a.cpu) != part_hash(&b.cpu)),
+                ("Z80", part_hash(&a.z80) != part_hash(&b.z80)),
+                ("VDP", part_hash(&bus_a.vdp) != part_hash(&bus_b.vdp)),
+                ("APU", part_hash(&bus_a.apu) != part_hash(&bus_b.apu)),
+                ("bus", part_hash(&*bus_a) != part_hash(&*bus_b)),
+            ]
+            .into_iter()
+            .filter_map(|(name, differs)| differs.then_some(name))
+            .collect();
+            panic!(
+                "runs diverged after frame {}: {:016X} vs {:016X} ({})",
+                frame,
+                hash,
+                b.state_hash(),
+                diverged.join(", ")
+            );
+        }
+        hashes.push(hash);
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Turn the display and VINT on, then loop reading pad 1, counting in D1
+    /// and writing the count to the PSG. VINT counts frames in RAM.
+    fn busy_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x400];
+        rom[0..4].copy_from_slice(&0x00FF8000u32.to_be_bytes());
+        rom[4..8].copy_from_slice(&0x00000200u32.to_be_bytes());
+        rom[0x78..0x7C].copy_from_slice(&0x00000300u32.to_be_bytes());
+        rom[0x200..0x21C].copy_from_slice(&[
+            0x33, 0xFC, 0x81, 0x64, 0x00, 0xC0, 0x00, 0x04, // MOVE.W #$8164,$C00004
+            0x46, 0xFC, 0x20, 0x00, // MOVE #$2000,SR
+            0x10, 0x39, 0x00, 0xA1, 0x00, 0x03, // MOVE.B $A10003,D0
+            0x52, 0x81, // ADDQ.L #1,D1
+            0x13, 0xC1, 0x00, 0xC0, 0x00, 0x11, // MOVE.B D1,$C00011
+            0x60, 0xF0, // BRA.S back to the pad read
+        ]);
+        rom[0x300..0x308].copy_from_slice(&[
+            0x52, 0x79, 0x00, 0xFF, 0x00, 0x00, // ADDQ.W #1,$FF0000
+            0x4E, 0x73, // RTE
+        ]);
+        rom
+    }
+
+    fn busy_inputs() -> Vec<FrameInput> {
+        (0..20)
+            .map(|frame| {
+                let mut input = FrameInput::default();
+                input.p1.set_button("A", frame % 3 == 0);
+                input.p1.set_button("Right", frame % 5 < 2);
+                input
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_assert_deterministic_over_a_busy_program() {
+        let hashes = assert_deterministic(&busy_rom(), &busy_inputs(), 30);
+
+        assert_eq!(hashes.len(), 30);
+        assert!(
+            hashes.windows(2).all(|pair| pair[0] != pair[1]),
+            "the machine moves on every frame"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "runs diverged after frame 7")]
+    fn test_assert_runs_match_catches_one_frame_of_different_input() {
+        let inputs = busy_inputs();
+        let mut other = inputs.clone();
+        other[7].p1.set_button("Start", true);
+        assert_runs_match(&busy_rom(), &inputs, &other, 30);
+    }
+}
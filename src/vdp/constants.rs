@@ -5,6 +5,8 @@ pub const CRAM_WRITE: u8 = 0x03;
 pub const VSRAM_READ: u8 = 0x04;
 pub const VSRAM_WRITE: u8 = 0x05;
 pub const CRAM_READ: u8 = 0x08;
+/// Undocumented byte-wide VRAM read; the high byte comes from the FIFO
+pub const VRAM_READ_8BIT: u8 = 0x0C;
 
 // Register indices
 pub const REG_MODE1: usize = 0;
@@ -30,11 +32,13 @@ pub const REG_DMA_SRC_MID: usize = 22;
 pub const REG_DMA_SRC_HI: usize = 23;
 
 // Mode bits
+pub const MODE1_HV_LATCH: u8 = 0x02;
 pub const MODE1_HINT_ENABLE: u8 = 0x10;
 pub const MODE2_V30_MODE: u8 = 0x08;
 pub const MODE2_DMA_ENABLE: u8 = 0x10;
 pub const MODE2_VINT_ENABLE: u8 = 0x20;
 pub const MODE2_DISPLAY_ENABLE: u8 = 0x40;
+pub const MODE3_EXT_INT_ENABLE: u8 = 0x08;
 pub const MODE4_H40_MODE: u8 = 0x81; // H40 mode check mask
 
 // DMA Modes
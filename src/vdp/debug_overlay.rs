@@ -0,0 +1,124 @@
+//! Per-pixel layer overlay for diagnosing the renderer
+//!
+//! `Vdp::pixel_sources` redraws the display from the current VRAM, CRAM,
+//! VSRAM and registers and reports, for every pixel, which layer won
+//! priority and which tile or sprite supplied it. Nothing is written to the
+//! framebuffer, so it can be called at any point between frames.
+
+use super::constants::*;
+use super::render::{render_sprite_scanline, top_layer, Layer, PixelLayerData, RenderOps};
+use super::{SpriteAttributes, Vdp};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PixelLayer {
+    Backdrop,
+    PlaneA,
+    PlaneB,
+    Window,
+    Sprite,
+}
+
+/// Where one pixel's colour came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PixelSource {
+    pub layer: PixelLayer,
+    /// Pattern index for plane and window pixels, sprite table index for
+    /// sprites, 0 for the backdrop
+    pub index: u16,
+    /// CRAM index (palette * 16 + colour) before shadow/highlight
+    pub color_idx: u8,
+}
+
+impl Vdp {
+    /// Source of every pixel of the display, row by row, 320 per line
+    pub fn pixel_sources(&self) -> Vec<PixelSource> {
+        (0..self.screen_height())
+            .flat_map(|line| self.line_sources(line))
+            .collect()
+    }
+
+    /// Source of the pixel at `(x, y)`, or `None` outside the display
+    pub fn pixel_source(&self, x: usize, y: usize) -> Option<PixelSource> {
+        if x >= 320 || y >= self.screen_height() as usize {
+            return None;
+        }
+        Some(self.line_sources(y as u16)[x])
+    }
+
+    fn line_sources(&self, line: u16) -> [PixelSource; 320] {
+        let (pal_line, color) = self.bg_color();
+        let backdrop = PixelSource {
+            layer: PixelLayer::Backdrop,
+            index: 0,
+            color_idx: (pal_line << 4) | color,
+        };
+        let mut sources = [backdrop; 320];
+        if !self.display_enabled() {
+            return sources;
+        }
+
+        let mut sprite_buffer = [SpriteAttributes::default(); 80];
+        let sprite_count = self.get_active_sprites(line, &mut sprite_buffer);
+        let screen_width = self.screen_width();
+
+        // Same back-to-front order as `render_sprites`, noting which sprite
+        // each pixel was last drawn by
+        let mut buf_s = [0u8; 320];
+        let mut owner = [0u8; 320];
+        for attr in sprite_buffer[..sprite_count].iter().rev() {
+            let mut single = [0u8; 320];
+            render_sprite_scanline(&self.vram, &mut single, line, attr, screen_width);
+            for x in 0..320 {
+                if single[x] != 0 {
+                    buf_s[x] = single[x];
+                    owner[x] = attr.index;
+                }
+            }
+        }
+
+        let mut buf_a = [0u8; 320];
+        let mut buf_b = [0u8; 320];
+        self.render_plane(true, line, &mut buf_a);
+        self.render_plane(false, line, &mut buf_b);
+
+        let mask_col0 = (self.registers[REG_MODE1] & 0x20) != 0;
+        let line_width = screen_width.min(self.frame_width()) as usize;
+        let plane_a = self.tile_params(true, false, line);
+        let plane_b = self.tile_params(false, false, line);
+        let window = self.tile_params(true, true, line);
+
+        for (x, source) in sources.iter_mut().enumerate().take(line_width) {
+            if mask_col0 && x < 8 {
+                continue;
+            }
+            let px = PixelLayerData::from_layers(backdrop.color_idx, buf_b[x], buf_a[x], buf_s[x]);
+            let (color_idx, layer) = top_layer(&px, true);
+            let tile = |params| self.tile_at(params, x as u16).0 & 0x07FF;
+            *source = match layer {
+                Layer::Backdrop => continue,
+                Layer::Sprite => PixelSource {
+                    layer: PixelLayer::Sprite,
+                    index: owner[x] as u16,
+                    color_idx,
+                },
+                Layer::PlaneB => PixelSource {
+                    layer: PixelLayer::PlaneB,
+                    index: tile(&plane_b),
+                    color_idx,
+                },
+                Layer::PlaneA if self.window_covers(x as u16, line) => PixelSource {
+                    layer: PixelLayer::Window,
+                    index: tile(&window),
+                    color_idx,
+                },
+                Layer::PlaneA => PixelSource {
+                    layer: PixelLayer::PlaneA,
+                    index: tile(&plane_a),
+                    color_idx,
+                },
+            };
+        }
+        sources
+    }
+}
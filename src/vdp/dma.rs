@@ -102,6 +102,9 @@ impl DmaOps for Vdp {
         let len = if length == 0 { 0x10000 } else { length };
 
         let mode = self.registers[REG_DMA_SRC_HI] & DMA_MODE_MASK;
+        if mode == DMA_MODE_FILL || mode == DMA_MODE_COPY {
+            self.invalidate_sprite_cache();
+        }
 
         match mode {
             DMA_MODE_FILL => {
@@ -158,6 +161,7 @@ impl DmaOps for Vdp {
 
                 if (self.command.code & 0x0F) == VRAM_WRITE {
                     self.vram[addr as usize] = val;
+                    self.note_vram_write(addr as usize);
                 }
 
                 self.command.address = addr.wrapping_add(inc);
@@ -169,6 +173,7 @@ impl DmaOps for Vdp {
                 if (self.command.code & 0x0F) == VRAM_WRITE {
                     let val = self.vram[source as usize];
                     self.vram[addr as usize] = val;
+                    self.note_vram_write(addr as usize);
                 }
 
                 let next_source = source.wrapping_add(1);
@@ -184,19 +189,8 @@ impl DmaOps for Vdp {
                 let addr = self.command.address;
                 let code = self.command.code;
                 match code & 0x0F {
-                    VRAM_WRITE => {
-                        let idx = addr as usize;
-                        if idx < self.vram.len() {
-                            self.vram[idx] = (val >> 8) as u8;
-                            self.vram[idx ^ 1] = (val & 0xFF) as u8;
-                        }
-                    }
-                    CRAM_WRITE => {
-                        let idx = (addr as usize / 2) & 0x3F;
-                        self.cram[idx * 2] = (val & 0xFF) as u8;
-                        self.cram[idx * 2 + 1] = (val >> 8) as u8;
-                        self.cram_cache[idx] = Self::genesis_color_to_rgb565(val);
-                    }
+                    VRAM_WRITE => self.write_vram_word(addr, val),
+                    CRAM_WRITE => self.write_cram_word(addr, val),
                     VSRAM_WRITE => {
                         let idx = (addr as usize) % 80;
                         self.vsram[idx] = (val >> 8) as u8;
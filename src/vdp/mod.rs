@@ -11,6 +11,12 @@ pub use dma::DmaOps;
 pub mod render;
 pub use render::{RenderOps, SpriteAttributes, SpriteIterator};
 
+pub mod test_pattern;
+pub use test_pattern::Pattern;
+
+pub mod debug_overlay;
+pub use debug_overlay::{PixelLayer, PixelSource};
+
 pub mod big_array_vram {
     use serde::de::{self, SeqAccess, Visitor};
     use serde::{Deserializer, Serializer};
@@ -173,6 +179,10 @@ pub struct CommandState {
     pub cd4_flag: bool,
     #[serde(default)]
     pub dma_fill_first: bool,
+    /// A fill command has been latched; the next data-port write supplies
+    /// the fill value and starts it. Slots don't advance the DMA until then.
+    #[serde(default)]
+    pub dma_fill_awaiting_data: bool,
 }
 
 /// VDP Write FIFO Entry
@@ -207,6 +217,12 @@ pub struct Vdp {
     pub line_counter: u16,
     #[serde(skip, default)]
     pub hint_pending: bool,
+    /// External interrupt raised by the HL line (controller TH)
+    #[serde(default)]
+    pub ext_int_pending: bool,
+    /// H/V counter captured by HL while the latch is enabled (reg 0 bit 1)
+    #[serde(default)]
+    pub hv_latch: Option<u16>,
     pub last_data_write: u16,
     pub v30_offset: u16,
     pub is_pal: bool,
@@ -216,8 +232,41 @@ pub struct Vdp {
     pub fifo_full: bool,
     pub bypass_fifo: bool,
 
+    /// Back buffer: lines are rendered here as the beam reaches them
     #[serde(skip, default = "default_framebuffer")]
     pub framebuffer: Vec<u16>,
+    /// Last complete frame, copied from `framebuffer` by `present`
+    #[serde(skip, default = "default_framebuffer")]
+    front_buffer: Vec<u16>,
+    /// H40 as it was when line 0 of the current frame was drawn; sets the
+    /// output width for the whole frame. Unset until a line 0 is drawn.
+    #[serde(default)]
+    frame_h40: Option<bool>,
+
+    /// Draw CRAM dots: a CRAM write during active display shows the
+    /// written colour at the pixel being output at that moment
+    #[serde(skip)]
+    cram_dots: bool,
+
+    /// Width of the backdrop border drawn around the active display in
+    /// `border_framebuffer`; zero disables border rendering
+    #[serde(skip)]
+    border_width: usize,
+    /// Bordered lines as they are drawn, like `framebuffer`
+    #[serde(skip)]
+    border_framebuffer: Vec<u16>,
+    /// Bordered output of the last complete frame and its size
+    #[serde(skip)]
+    border_front: Vec<u16>,
+    #[serde(skip)]
+    border_front_size: (usize, usize),
+
+    /// Parsed sprite attribute table in link order, rebuilt lazily after
+    /// a write lands inside the SAT or the SAT base/size changes
+    #[serde(skip)]
+    sat_cache: Vec<SpriteAttributes>,
+    #[serde(skip)]
+    sat_cache_valid: bool,
 }
 
 impl Default for Vdp {
@@ -241,6 +290,8 @@ impl Vdp {
             v_counter: 0,
             line_counter: 0,
             hint_pending: false,
+            ext_int_pending: false,
+            hv_latch: None,
             last_data_write: 0,
             v30_offset: 0,
             is_pal: false,
@@ -248,6 +299,15 @@ impl Vdp {
             fifo_full: false,
             bypass_fifo: false,
             framebuffer: vec![0; 320 * 240],
+            front_buffer: vec![0; 320 * 240],
+            frame_h40: None,
+            cram_dots: false,
+            border_width: 0,
+            border_framebuffer: Vec::new(),
+            border_front: Vec::new(),
+            border_front_size: (0, 0),
+            sat_cache: Vec::with_capacity(80),
+            sat_cache_valid: false,
         };
         vdp.reset();
         vdp
@@ -277,13 +337,193 @@ impl Vdp {
         self.h_counter = 0;
         self.line_counter = 0;
         self.hint_pending = false;
+        self.ext_int_pending = false;
+        self.hv_latch = None;
+        self.frame_h40 = None;
         self.reconstruct_cram_cache();
+        self.invalidate_sprite_cache();
+    }
+
+    /// Capture VRAM, CRAM, VSRAM, registers and command/FIFO state as a
+    /// standalone snapshot, for reproducing rendering bugs without the rest
+    /// of the emulator
+    pub fn dump_state(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| format!("Failed to serialize VDP state: {}", e))
+    }
+
+    /// Restore a snapshot taken with `dump_state`. Frontend settings
+    /// (CRAM dots, border) and the framebuffer allocation are kept.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let new_vdp: Vdp =
+            serde_json::from_slice(data).map_err(|e| format!("Invalid VDP state: {}", e))?;
+        self.adopt_state(new_vdp);
+        Ok(())
+    }
+
+    fn adopt_state(&mut self, mut new_vdp: Vdp) {
+        // Swap framebuffers to preserve allocation
+        std::mem::swap(&mut self.framebuffer, &mut new_vdp.framebuffer);
+        std::mem::swap(&mut self.front_buffer, &mut new_vdp.front_buffer);
+        new_vdp.cram_dots = self.cram_dots;
+        new_vdp.border_width = self.border_width;
+        std::mem::swap(
+            &mut self.border_framebuffer,
+            &mut new_vdp.border_framebuffer,
+        );
+        std::mem::swap(&mut self.border_front, &mut new_vdp.border_front);
+        new_vdp.border_front_size = self.border_front_size;
+
+        // Reconstruct CRAM cache
+        new_vdp.reconstruct_cram_cache();
+
+        *self = new_vdp;
     }
 
     pub fn set_pal(&mut self, is_pal: bool) {
         self.is_pal = is_pal;
     }
 
+    /// Model the CRAM dot artifact. Off by default.
+    pub fn set_cram_dots(&mut self, enabled: bool) {
+        self.cram_dots = enabled;
+    }
+
+    pub fn cram_dots(&self) -> bool {
+        self.cram_dots
+    }
+
+    /// Store a colour in CRAM from the data port or a DMA.
+    pub(crate) fn write_cram_word(&mut self, addr: u16, value: u16) {
+        let idx = (addr as usize / 2) & 0x3F;
+        self.cram[idx * 2] = (value & 0xFF) as u8;
+        self.cram[idx * 2 + 1] = (value >> 8) as u8;
+        self.cram_cache[idx] = Self::genesis_color_to_rgb565(value);
+        if self.cram_dots {
+            self.draw_cram_dot(self.cram_cache[idx]);
+        }
+    }
+
+    /// Overwrite the pixel under the beam with `color`. The current line
+    /// was rendered when the beam reached it, so the dot lands on top. The
+    /// active display is taken as the start of each line at 8 MCLKs a pixel
+    /// in H40 and 10 in H32, ending where the HBlank flag rises. While
+    /// dots are on the scheduler ticks the bus after every instruction, so a
+    /// write drawn through the FIFO lands at most one instruction past the
+    /// access slot that took it, rather than up to a 32-cycle sync slice.
+    fn draw_cram_dot(&mut self, color: u16) {
+        let line = self.v_counter;
+        if !self.display_enabled() || line >= self.screen_height() {
+            return;
+        }
+        let mclk_per_pixel = if self.h40_mode() { 8 } else { 10 };
+        let x = (self.mclk_line_clocks / mclk_per_pixel) as usize;
+        if x < self.screen_width() as usize {
+            self.framebuffer[line as usize * 320 + x] = color;
+        }
+    }
+
+    /// Scheduler entry point, called once per scanline as the beam passes
+    /// it, so mid-frame register, scroll and palette writes show up on the
+    /// lines they affect
+    pub fn render_scanline(&mut self, line: u16) {
+        self.render_line(line);
+    }
+
+    /// Enable border rendering with `width` pixels of backdrop on every
+    /// side of the active display, or disable it with zero
+    pub fn set_border_width(&mut self, width: usize) {
+        self.border_width = width;
+        if width == 0 {
+            self.border_framebuffer = Vec::new();
+            self.border_front = Vec::new();
+            self.border_front_size = (0, 0);
+        }
+    }
+
+    pub fn border_width(&self) -> usize {
+        self.border_width
+    }
+
+    /// Width and height of `bordered_framebuffer`, as the display mode was
+    /// for the presented frame
+    pub fn bordered_size(&self) -> (usize, usize) {
+        self.border_front_size
+    }
+
+    /// Size of the bordered frame being drawn. The width follows the mode
+    /// latched for the frame like `frame_width`.
+    fn back_bordered_size(&self) -> (usize, usize) {
+        let border = self.border_width * 2;
+        (
+            self.frame_width() as usize + border,
+            self.screen_height() as usize + border,
+        )
+    }
+
+    /// Active display of the last presented frame surrounded by the
+    /// backdrop border, as on a CRT with no overscan. Empty while border
+    /// rendering is disabled.
+    pub fn bordered_framebuffer(&self) -> &[u16] {
+        &self.border_front
+    }
+
+    /// Copy a rendered line into the bordered output, filling the border
+    /// with the backdrop colour the line was drawn with. The top and bottom
+    /// borders take the backdrop from the first and last active lines.
+    pub(crate) fn update_border_line(&mut self, line: u16, backdrop: u16) {
+        let border = self.border_width;
+        if border == 0 {
+            return;
+        }
+        let (width, height) = self.back_bordered_size();
+        if self.border_framebuffer.len() != width * height {
+            self.border_framebuffer = vec![backdrop; width * height];
+        }
+
+        let active_width = width - border * 2;
+        let src = line as usize * 320;
+        let row = (line as usize + border) * width;
+        let out = &mut self.border_framebuffer[row..row + width];
+        out.fill(backdrop);
+        out[border..border + active_width]
+            .copy_from_slice(&self.framebuffer[src..src + active_width]);
+
+        if line == 0 {
+            self.border_framebuffer[..border * width].fill(backdrop);
+        }
+        if line + 1 == self.screen_height() {
+            self.border_framebuffer[(height - border) * width..].fill(backdrop);
+        }
+    }
+
+    /// Publish the frame rendered into `framebuffer`, and its bordered
+    /// output, so readers of `front_buffer` never see a frame that is still
+    /// being drawn. Called once the last active line has been drawn. Rows
+    /// below the active display are cleared rather than left over from a
+    /// taller frame.
+    pub fn present(&mut self) {
+        let active = self.screen_height() as usize * 320;
+        self.front_buffer[..active].copy_from_slice(&self.framebuffer[..active]);
+        self.front_buffer[active..].fill(0);
+        let (width, height) = self.back_bordered_size();
+        if self.border_width > 0 && self.border_framebuffer.len() == width * height {
+            self.border_front.clone_from(&self.border_framebuffer);
+            self.border_front_size = (width, height);
+        }
+    }
+
+    /// The most recently presented frame
+    pub fn front_buffer(&self) -> &[u16] {
+        &self.front_buffer
+    }
+
+    /// Render every active line from the current register state
+    pub fn render_frame(&mut self) {
+        for line in 0..self.screen_height() {
+            self.render_line(line);
+        }
+    }
+
     pub fn write_data(&mut self, value: u16) {
         self.command.pending = false;
         self.last_data_write = value;
@@ -294,6 +534,7 @@ impl Vdp {
                 && (self.registers[REG_DMA_SRC_HI] & DMA_MODE_MASK) == DMA_MODE_FILL
                 && self.command.dma_pending
             {
+                self.command.dma_fill_awaiting_data = false;
                 self.execute_dma();
                 return;
             }
@@ -313,6 +554,7 @@ impl Vdp {
             {
                 // For now, we still handle DMA Fill synchronously to pass existing tests,
                 // but we will move it to process_slot soon for full cycle accuracy.
+                self.command.dma_fill_awaiting_data = false;
                 self.execute_dma();
                 return;
             }
@@ -332,8 +574,12 @@ impl Vdp {
                     self.status |= STATUS_FIFO_FULL;
                 }
             } else {
-                // Stall modeling - currently force process
-                self.process_fifo_entry(FifoEntry {
+                // The bus holds the CPU until a slot frees an entry, so this
+                // only happens when driven directly: commit the oldest word
+                // as that slot would, keeping writes in order
+                let oldest = self.fifo.remove(0);
+                self.process_fifo_entry(oldest);
+                self.fifo.push(FifoEntry {
                     address: self.command.address,
                     code: self.command.code,
                     value,
@@ -347,25 +593,17 @@ impl Vdp {
             .wrapping_add(self.auto_increment() as u16);
     }
 
+    /// Commit one FIFO word. A code that isn't a write target (a read code or
+    /// an unused one) changes no memory, but the word still took a FIFO slot
+    /// and advanced the address, as on hardware.
     fn process_fifo_entry(&mut self, entry: FifoEntry) {
         let addr = entry.address;
         let code = entry.code;
         let value = entry.value;
 
         match code & 0x0F {
-            VRAM_WRITE => {
-                let idx = addr as usize;
-                if idx < self.vram.len() {
-                    self.vram[idx] = (value >> 8) as u8;
-                    self.vram[idx ^ 1] = (value & 0xFF) as u8;
-                }
-            }
-            CRAM_WRITE => {
-                let idx = (addr as usize / 2) & 0x3F;
-                self.cram[idx * 2] = (value & 0xFF) as u8;
-                self.cram[idx * 2 + 1] = (value >> 8) as u8;
-                self.cram_cache[idx] = Self::genesis_color_to_rgb565(value);
-            }
+            VRAM_WRITE => self.write_vram_word(addr, value),
+            CRAM_WRITE => self.write_cram_word(addr, value),
             VSRAM_WRITE => {
                 let idx = (addr as usize) % 80;
                 self.vsram[idx] = (value >> 8) as u8;
@@ -377,8 +615,19 @@ impl Vdp {
         }
     }
 
+    /// Data port read, returning the word prefetched by the last command or
+    /// read. With a code that isn't a read target the VDP never completes
+    /// the access and the 68000 stalls on hardware; here the read returns
+    /// the word most recently written through the FIFO and leaves the
+    /// address alone.
     pub fn read_data(&mut self) -> u16 {
         self.command.pending = false;
+        if !matches!(
+            self.command.code & 0x0F,
+            VRAM_READ | VSRAM_READ | CRAM_READ | VRAM_READ_8BIT
+        ) {
+            return self.last_data_write;
+        }
 
         let val = self.command.read_buffer;
         self.command.cd4_flag = false;
@@ -428,6 +677,12 @@ impl Vdp {
                 self.command.read_buffer = val;
                 self.command.cd4_flag = true;
             }
+            VRAM_READ_8BIT => {
+                // The unused high byte is whatever is in the next FIFO slot
+                let val = (self.last_data_write & 0xFF00) | self.vram[(addr ^ 1) as usize] as u16;
+                self.command.read_buffer = val;
+                self.command.cd4_flag = true;
+            }
             _ => {
                 self.command.cd4_flag = true;
                 return; // Do not increment address on invalid read target
@@ -452,6 +707,7 @@ impl Vdp {
                 && (self.registers[REG_MODE2] & MODE2_DMA_ENABLE) != 0
             {
                 self.command.dma_pending = true;
+                self.command.dma_fill_awaiting_data = self.is_dma_fill();
             }
 
             // Prefetch if target is a read
@@ -465,6 +721,9 @@ impl Vdp {
                 let val = (value & 0xFF) as u8;
                 if reg < NUM_REGISTERS {
                     self.registers[reg] = val;
+                    if reg == REG_SPRITE_TABLE || reg == REG_MODE4 {
+                        self.invalidate_sprite_cache();
+                    }
                 }
                 return;
             }
@@ -535,6 +794,16 @@ impl Vdp {
         }
     }
 
+    /// Active width of the frame being drawn. The H32/H40 bit may change
+    /// between lines, but the output layout follows the mode at line 0.
+    pub fn frame_width(&self) -> u16 {
+        if self.frame_h40.unwrap_or_else(|| self.h40_mode()) {
+            320
+        } else {
+            256
+        }
+    }
+
     pub fn screen_height(&self) -> u16 {
         if (self.registers[REG_MODE2] & MODE2_V30_MODE) != 0 {
             240
@@ -561,17 +830,64 @@ impl Vdp {
         }
     }
 
+    /// VRAM byte range covered by the sprite attribute table (8 bytes per
+    /// sprite, 80 sprites in H40 and 64 in H32)
+    pub fn sprite_table_range(&self) -> std::ops::Range<usize> {
+        let max_sprites = if self.h40_mode() { 80 } else { 64 };
+        let base = self.sprite_table_address();
+        base..(base + max_sprites * 8).min(self.vram.len())
+    }
+
+    /// Sprite list in link order, parsed from the SAT on first access after
+    /// an invalidating write and served from the cache afterwards
+    pub fn sprite_attributes(&mut self) -> &[SpriteAttributes] {
+        self.refresh_sprite_cache();
+        &self.sat_cache
+    }
+
+    pub(crate) fn refresh_sprite_cache(&mut self) {
+        if self.sat_cache_valid {
+            return;
+        }
+        let iter = SpriteIterator {
+            vram: &self.vram,
+            next_idx: 0,
+            count: 0,
+            max_sprites: if self.h40_mode() { 80 } else { 64 },
+            sat_base: self.sprite_table_address(),
+        };
+        self.sat_cache.clear();
+        self.sat_cache.extend(iter);
+        self.sat_cache_valid = true;
+    }
+
+    /// Drop the parsed SAT. Code poking `vram` directly must call this.
+    pub fn invalidate_sprite_cache(&mut self) {
+        self.sat_cache_valid = false;
+    }
+
+    #[inline(always)]
+    pub(crate) fn note_vram_write(&mut self, addr: usize) {
+        if self.sat_cache_valid && self.sprite_table_range().contains(&(addr & !1)) {
+            self.sat_cache_valid = false;
+        }
+    }
+
     pub fn hscroll_address(&self) -> usize {
         // Bits 0-5 specify bits 10-15 of VRAM address
         ((self.registers[REG_HSCROLL] as usize) & 0x3F) << 10
     }
 
+    /// Write a word to VRAM the way the VDP does.
+    ///
+    /// Both bytes always land in the word pair containing `addr`; an odd
+    /// address swaps them instead of spilling into the next word, so a write
+    /// at 0xFFFF stays at 0xFFFE-0xFFFF rather than touching 0x0000.
     pub fn write_vram_word(&mut self, addr: u16, value: u16) {
         let addr = addr as usize;
-        if addr < 0x10000 {
-            self.vram[addr] = (value >> 8) as u8;
-            self.vram[addr ^ 1] = (value & 0xFF) as u8;
-        }
+        self.vram[addr] = (value >> 8) as u8;
+        self.vram[addr ^ 1] = (value & 0xFF) as u8;
+        self.note_vram_write(addr);
     }
 
     pub fn set_vblank(&mut self, active: bool) {
@@ -603,7 +919,33 @@ impl Vdp {
         self.hint_pending && self.hint_enabled()
     }
 
+    /// HL line asserted by a peripheral pulling TH. Latches the H/V counter
+    /// when the latch is enabled and raises the level 2 external interrupt.
+    pub fn external_interrupt(&mut self) {
+        if (self.registers[REG_MODE1] & MODE1_HV_LATCH) != 0 {
+            self.hv_latch = Some(self.live_hv_counter());
+        }
+        self.ext_int_pending = true;
+    }
+
+    pub fn acknowledge_ext_int(&mut self) {
+        self.ext_int_pending = false;
+    }
+
+    pub fn ext_int_pending(&self) -> bool {
+        self.ext_int_pending && (self.registers[REG_MODE3] & MODE3_EXT_INT_ENABLE) != 0
+    }
+
+    /// H/V counter as the 68k reads it: the latched value while the latch
+    /// is enabled and has captured one, otherwise the live counter
     pub fn read_hv_counter(&self) -> u16 {
+        match self.hv_latch {
+            Some(latched) if (self.registers[REG_MODE1] & MODE1_HV_LATCH) != 0 => latched,
+            _ => self.live_hv_counter(),
+        }
+    }
+
+    fn live_hv_counter(&self) -> u16 {
         // Approximate Genesis HV counter mapping for H40 mode
         let h = if self.mclk_line_clocks < 2560 {
             // Active area: map 0-2560 to 0x00-0xB6
@@ -771,9 +1113,10 @@ impl Vdp {
             }
         };
 
-        // If in VBlank, nearly all slots are external opportunities
-        let in_vblank = (self.status & STATUS_VBLANK) != 0;
-        let is_available = is_external || in_vblank;
+        // In VBlank or with the display off, nearly all slots are external
+        // opportunities
+        let in_blank = (self.status & STATUS_VBLANK) != 0 || !self.display_enabled();
+        let is_available = is_external || in_blank;
 
         if !is_available {
             return;
@@ -793,7 +1136,7 @@ impl Vdp {
                     self.try_prefetch();
                 }
             }
-        } else if self.command.dma_pending {
+        } else if self.command.dma_pending && !self.command.dma_fill_awaiting_data {
             self.step_dma(read_bus_word);
         }
     }
@@ -805,21 +1148,10 @@ impl Debuggable for Vdp {
     }
 
     fn write_state(&mut self, state: &Value) {
-        let mut new_vdp: Vdp = match Vdp::deserialize(state) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Error deserializing VDP state: {}", e);
-                return;
-            }
-        };
-
-        // Swap framebuffer to preserve allocation
-        std::mem::swap(&mut self.framebuffer, &mut new_vdp.framebuffer);
-
-        // Reconstruct CRAM cache
-        new_vdp.reconstruct_cram_cache();
-
-        *self = new_vdp;
+        match Vdp::deserialize(state) {
+            Ok(new_vdp) => self.adopt_state(new_vdp),
+            Err(e) => log::error!("Error deserializing VDP state: {}", e),
+        }
     }
 }
 
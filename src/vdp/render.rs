@@ -156,11 +156,127 @@ pub struct PixelLayerData {
     pub b_col: u8,
 }
 
-pub struct ShadowHighlightParams<'a> {
-    pub top_layer: u8,
-    pub top_col: u8,
-    pub state: u8,
-    pub px: &'a PixelLayerData,
+impl PixelLayerData {
+    /// Split line buffer bytes (bit 7 priority, bits 0-5 colour) into layers
+    pub fn from_layers(bg_color_idx: u8, b: u8, a: u8, s: u8) -> Self {
+        Self {
+            bg_color_idx,
+            s_pri: (s & 0x80) != 0,
+            s_trans: (s & 0x0F) == 0,
+            s_col: s & 0x3F,
+            a_pri: (a & 0x80) != 0,
+            a_trans: (a & 0x0F) == 0,
+            a_col: a & 0x3F,
+            b_pri: (b & 0x80) != 0,
+            b_trans: (b & 0x0F) == 0,
+            b_col: b & 0x3F,
+        }
+    }
+
+    fn any_high(&self) -> bool {
+        self.s_pri || self.a_pri || self.b_pri
+    }
+}
+
+/// Brightness a pixel is drawn at in shadow/highlight mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shade {
+    Shadow,
+    Normal,
+    Highlight,
+}
+
+impl Shade {
+    fn brighter(self) -> Self {
+        match self {
+            Shade::Shadow => Shade::Normal,
+            _ => Shade::Highlight,
+        }
+    }
+
+    fn darker(self) -> Self {
+        match self {
+            Shade::Highlight => Shade::Normal,
+            _ => Shade::Shadow,
+        }
+    }
+}
+
+/// The colour a pixel ends up as after layer priority is resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResolvedPixel {
+    /// CRAM index (palette * 16 + colour)
+    pub color_idx: u8,
+    pub shade: Shade,
+}
+
+#[derive(PartialEq)]
+pub(super) enum Layer {
+    Backdrop,
+    PlaneB,
+    PlaneA,
+    Sprite,
+}
+
+/// Frontmost opaque layer. Back to front the order is backdrop, low B,
+/// low A, low sprites, high B, high A, high sprites.
+pub(super) fn top_layer(px: &PixelLayerData, with_sprites: bool) -> (u8, Layer) {
+    let sprite = with_sprites && !px.s_trans;
+    if sprite && px.s_pri {
+        (px.s_col, Layer::Sprite)
+    } else if px.a_pri && !px.a_trans {
+        (px.a_col, Layer::PlaneA)
+    } else if px.b_pri && !px.b_trans {
+        (px.b_col, Layer::PlaneB)
+    } else if sprite {
+        (px.s_col, Layer::Sprite)
+    } else if !px.a_trans {
+        (px.a_col, Layer::PlaneA)
+    } else if !px.b_trans {
+        (px.b_col, Layer::PlaneB)
+    } else {
+        (px.bg_color_idx, Layer::Backdrop)
+    }
+}
+
+/// Pick the visible colour for one pixel from its layer candidates.
+///
+/// With shadow/highlight on, a pixel is shadowed unless some layer at it is
+/// high priority. A top sprite pixel in palette 3 colour 14 or 15 isn't drawn;
+/// it highlights or shadows the planes beneath it. Colour 14 of the other
+/// sprite palettes is always drawn at normal brightness.
+pub fn resolve_pixel(px: &PixelLayerData, shadow_highlight: bool) -> ResolvedPixel {
+    let (color_idx, layer) = top_layer(px, true);
+    if !shadow_highlight {
+        return ResolvedPixel {
+            color_idx,
+            shade: Shade::Normal,
+        };
+    }
+
+    let shade = if px.any_high() {
+        Shade::Normal
+    } else {
+        Shade::Shadow
+    };
+    if layer != Layer::Sprite {
+        return ResolvedPixel { color_idx, shade };
+    }
+    match px.s_col {
+        0x3E => ResolvedPixel {
+            color_idx: top_layer(px, false).0,
+            shade: shade.brighter(),
+        },
+        0x3F => ResolvedPixel {
+            color_idx: top_layer(px, false).0,
+            shade: shade.darker(),
+        },
+        col if (col & 0x0F) == 0x0E => ResolvedPixel {
+            color_idx,
+            shade: Shade::Normal,
+        },
+        _ => ResolvedPixel { color_idx, shade },
+    }
 }
 
 pub struct CompositeLineParams<'a> {
@@ -173,145 +289,173 @@ pub struct CompositeLineParams<'a> {
 }
 
 impl Vdp {
-    fn composite_line(&mut self, params: &CompositeLineParams) {
-        let sh_enabled = (self.registers[REG_MODE4] & 0x08) != 0;
-        let mask_col0 = (self.registers[REG_MODE1] & 0x20) != 0;
+    /// Walk the sprite list in link order and keep the sprites visible on
+    /// `line`, honouring the per-line sprite and pixel limits
+    fn select_line_sprites<T: Iterator<Item = SpriteAttributes>>(
+        &self,
+        iter: T,
+        line: u16,
+        sprites: &mut [SpriteAttributes],
+    ) -> usize {
+        let mut count = 0;
+        let mut pixels = 0;
 
-        for x in 0..320 {
-            if mask_col0 && x < 8 {
-                self.framebuffer[params.line_offset + x] = params.bg_color_val;
-                continue;
-            }
+        let line_limit = if self.h40_mode() { 20 } else { 16 };
+        let pixel_limit = if self.h40_mode() { 320 } else { 256 };
 
-            let b = params.buf_b[x];
-            let a = params.buf_a[x];
-            let s = params.buf_s[x];
-
-            let b_pri = (b & 0x80) != 0;
-            let a_pri = (a & 0x80) != 0;
-            let s_pri = (s & 0x80) != 0;
-
-            let b_col = b & 0x3F;
-            let a_col = a & 0x3F;
-            let s_col = s & 0x3F;
-
-            let b_trans = (b_col & 0x0F) == 0;
-            let a_trans = (a_col & 0x0F) == 0;
-            let s_trans = (s_col & 0x0F) == 0;
-
-            let any_high = b_pri || a_pri || s_pri;
-
-            let px = PixelLayerData {
-                bg_color_idx: params.bg_color_idx,
-                s_pri,
-                s_trans,
-                s_col,
-                a_pri,
-                a_trans,
-                a_col,
-                b_pri,
-                b_trans,
-                b_col,
-            };
+        for attr in iter {
+            let sprite_v_px = (attr.v_size as u16) * 8;
 
-            let (mut top_col, top_layer) = self.determine_top_layer(&px);
+            // X=0 suppression mode
+            if attr.h_pos == 0 {
+                // Technically hardware suppresses rendering but continues processing,
+                // but for our buffer we can just skip it visually
+            }
 
-            if !sh_enabled {
-                self.framebuffer[params.line_offset + x] = self.cram_cache[top_col as usize];
-            } else {
-                let mut state = if any_high { 1 } else { 0 };
+            // Handle wrapping v_pos (top clipping) correctly using wrapping subtraction
+            if line.wrapping_sub(attr.v_pos) < sprite_v_px {
+                // If the sprite falls on this scanline, add it
+                if count < sprites.len() {
+                    sprites[count] = attr;
+                    count += 1;
 
-                let sh_params = ShadowHighlightParams {
-                    top_layer,
-                    top_col,
-                    state,
-                    px: &px,
-                };
-                let (new_top_col, new_state) = self.apply_shadow_highlight(sh_params);
-                top_col = new_top_col;
-                state = new_state;
+                    pixels += (attr.h_size as usize) * 8;
+                }
+
+                if count >= line_limit {
+                    break;
+                }
 
-                let color = self.cram_cache[top_col as usize];
-                let final_color = self.apply_color_transform(color, state);
-                self.framebuffer[params.line_offset + x] = final_color;
+                if pixels >= pixel_limit {
+                    break;
+                }
             }
         }
+        count
     }
 
-    fn determine_top_layer(&self, px: &PixelLayerData) -> (u8, u8) {
-        let mut top_col = px.bg_color_idx;
-        let mut top_layer = 0; // 0=BG, 1=B, 2=A, 3=S
-
-        if px.s_pri && !px.s_trans {
-            top_col = px.s_col;
-            top_layer = 3;
-        } else if px.a_pri && !px.a_trans {
-            top_col = px.a_col;
-            top_layer = 2;
-        } else if px.b_pri && !px.b_trans {
-            top_col = px.b_col;
-            top_layer = 1;
-        } else if !px.s_trans {
-            top_col = px.s_col;
-            top_layer = 3;
-        } else if !px.a_trans {
-            top_col = px.a_col;
-            top_layer = 2;
-        } else if !px.b_trans {
-            top_col = px.b_col;
-            top_layer = 1;
+    /// Scroll and nametable settings for drawing plane A or B, or the
+    /// window, on `fetch_line`
+    pub(super) fn tile_params(
+        &self,
+        is_plane_a: bool,
+        window: bool,
+        fetch_line: u16,
+    ) -> TileRenderParams {
+        let (plane_w, plane_h) = self.plane_size();
+        let screen_width = self.screen_width();
+        if window {
+            let win_w = if self.h40_mode() { 64 } else { 32 };
+            return TileRenderParams {
+                is_plane_a: true,
+                enable_v_scroll: false,
+                name_table_base: self.window_address(),
+                plane_w: win_w,
+                plane_h,
+                plane_w_mask: win_w - 1,
+                h_scroll: 0,
+                fetch_line,
+                scanline_width: screen_width,
+            };
         }
+        TileRenderParams {
+            is_plane_a,
+            enable_v_scroll: true,
+            name_table_base: if is_plane_a {
+                self.plane_a_address()
+            } else {
+                self.plane_b_address()
+            },
+            plane_w,
+            plane_h,
+            plane_w_mask: plane_w - 1,
+            h_scroll: self.get_h_scroll(is_plane_a, fetch_line),
+            fetch_line,
+            scanline_width: screen_width,
+        }
+    }
 
-        (top_col, top_layer)
+    /// Whether the window replaces plane A at `(screen_x, line)`
+    pub(super) fn window_covers(&self, screen_x: u16, line: u16) -> bool {
+        let h_pos = self.registers[REG_WINDOW_H_POS];
+        let v_pos = self.registers[REG_WINDOW_V_POS];
+        let h_point = (h_pos as u16 & 0x1F) * 16;
+        let v_point = (v_pos as u16 & 0x1F) * 8;
+        let in_h = if (h_pos & 0x80) != 0 {
+            screen_x >= h_point
+        } else {
+            screen_x < h_point
+        };
+        let in_v = if (v_pos & 0x80) != 0 {
+            line >= v_point
+        } else {
+            line < v_point
+        };
+        in_h || in_v
     }
 
-    fn apply_shadow_highlight(&self, mut params: ShadowHighlightParams) -> (u8, u8) {
-        if params.top_layer == 3 {
-            if params.px.s_col == 0x3E {
-                params.top_col = params.px.bg_color_idx;
-                if params.px.a_pri && !params.px.a_trans {
-                    params.top_col = params.px.a_col;
-                } else if params.px.b_pri && !params.px.b_trans {
-                    params.top_col = params.px.b_col;
-                } else if !params.px.a_trans {
-                    params.top_col = params.px.a_col;
-                } else if !params.px.b_trans {
-                    params.top_col = params.px.b_col;
-                }
-                if params.state < 2 {
-                    params.state += 1;
-                }
-            } else if params.px.s_col == 0x3F {
-                params.top_col = params.px.bg_color_idx;
-                if params.px.a_pri && !params.px.a_trans {
-                    params.top_col = params.px.a_col;
-                } else if params.px.b_pri && !params.px.b_trans {
-                    params.top_col = params.px.b_col;
-                } else if !params.px.a_trans {
-                    params.top_col = params.px.a_col;
-                } else if !params.px.b_trans {
-                    params.top_col = params.px.b_col;
-                }
-                if params.state > 0 {
-                    params.state -= 1;
-                }
-            } else if (params.px.s_col & 0x0F) == 0x0E {
-                params.state = 1;
+    /// Nametable entry under `screen_x`, with the pixel's column and row
+    /// within that tile
+    pub(super) fn tile_at(&self, params: &TileRenderParams, screen_x: u16) -> (u16, u16, u16) {
+        // Horizontal position in plane
+        let scrolled_h = screen_x.wrapping_sub(params.h_scroll);
+        let pixel_h = scrolled_h & 0x07;
+        let tile_h = ((scrolled_h >> 3) as usize) & params.plane_w_mask;
+
+        // Fetch V-scroll for this specific column (per-column VS support)
+        // If not using scroll (e.g. Window plane), V-scroll is 0.
+        let v_scroll = if params.enable_v_scroll {
+            self.get_v_scroll(params.is_plane_a, (screen_x >> 3) as usize)
+        } else {
+            0
+        };
+
+        // Vertical position in plane
+        let scrolled_v = params.fetch_line.wrapping_add(v_scroll);
+        let tile_v = ((scrolled_v / 8) as usize) % params.plane_h;
+        let pixel_v = scrolled_v % 8;
+
+        let entry =
+            self.fetch_nametable_entry(params.name_table_base, tile_v, tile_h, params.plane_w);
+        (entry, pixel_h, pixel_v)
+    }
+
+    fn composite_line(&mut self, params: &CompositeLineParams) {
+        let sh_enabled = (self.registers[REG_MODE4] & 0x08) != 0;
+        let mask_col0 = (self.registers[REG_MODE1] & 0x20) != 0;
+        // A line drawn after a mid-frame H32/H40 switch keeps its own mode's
+        // pixels, clipped to the frame width, with backdrop beyond
+        let line_width = self.screen_width().min(self.frame_width()) as usize;
+
+        for x in 0..320 {
+            if (mask_col0 && x < 8) || x >= line_width {
+                self.framebuffer[params.line_offset + x] = params.bg_color_val;
+                continue;
             }
+
+            let px = PixelLayerData::from_layers(
+                params.bg_color_idx,
+                params.buf_b[x],
+                params.buf_a[x],
+                params.buf_s[x],
+            );
+            let pixel = resolve_pixel(&px, sh_enabled);
+            let color = self.cram_cache[pixel.color_idx as usize];
+            self.framebuffer[params.line_offset + x] =
+                self.apply_color_transform(color, pixel.shade);
         }
-        (params.top_col, params.state)
     }
 
-    fn apply_color_transform(&self, color: u16, state: u8) -> u16 {
-        match state {
-            0 => {
+    fn apply_color_transform(&self, color: u16, shade: Shade) -> u16 {
+        match shade {
+            Shade::Shadow => {
                 // Shadow (halve brightness)
                 let r = ((color >> 11) & 0x1E) >> 1;
                 let g = ((color >> 6) & 0x1E) >> 1;
                 let b = ((color >> 1) & 0x1E) >> 1;
                 (r << 11) | (g << 6) | (b << 1)
             }
-            2 => {
+            Shade::Highlight => {
                 // Highlight (double brightness + offset)
                 let r = (color >> 11) & 0x1E;
                 let g = (color >> 6) & 0x1E;
@@ -324,12 +468,12 @@ impl Vdp {
                 let b_final = if b2 > 0x1E { 0x1E } else { b2 };
                 (r_final << 11) | (g_final << 6) | (b_final << 1)
             }
-            _ => color,
+            Shade::Normal => color,
         }
     }
 }
 
-fn render_sprite_scanline(
+pub(super) fn render_sprite_scanline(
     vram: &[u8],
     line_buf: &mut [u8; 320],
     line: u16,
@@ -441,6 +585,10 @@ impl RenderOps for Vdp {
             return;
         }
 
+        if line == 0 {
+            self.frame_h40 = Some(self.h40_mode());
+        }
+
         let draw_line = line;
         let fetch_line = line;
         let line_offset = (draw_line as usize) * 320;
@@ -451,9 +599,11 @@ impl RenderOps for Vdp {
 
         if !self.display_enabled() || line >= self.screen_height() {
             self.framebuffer[line_offset..line_offset + 320].fill(bg_color_val);
+            self.update_border_line(line, bg_color_val);
             return;
         }
 
+        self.refresh_sprite_cache();
         let mut sprite_buffer = [SpriteAttributes::default(); 80];
         let sprite_count = self.get_active_sprites(fetch_line, &mut sprite_buffer);
         let active_sprites = &sprite_buffer[..sprite_count];
@@ -475,67 +625,18 @@ impl RenderOps for Vdp {
             buf_s: &buf_s,
         };
         self.composite_line(&composite_params);
+        self.update_border_line(line, bg_color_val);
     }
 
     fn render_plane(&self, is_plane_a: bool, fetch_line: u16, line_buf: &mut [u8; 320]) {
-        let (plane_w, plane_h) = self.plane_size();
-        let name_table_base = if is_plane_a {
-            self.plane_a_address()
-        } else {
-            self.plane_b_address()
-        };
-
         let screen_width = self.screen_width();
-        let h_scroll = self.get_h_scroll(is_plane_a, fetch_line);
-        let h40_mode = self.h40_mode();
-
+        let plane_params = self.tile_params(is_plane_a, false, fetch_line);
         let mut screen_x: u16 = 0;
 
         if is_plane_a {
-            let h_pos = self.registers[REG_WINDOW_H_POS];
-            let v_pos = self.registers[REG_WINDOW_V_POS];
-            let win_h_point = (h_pos as u16 & 0x1F) * 16;
-            let v_point = (v_pos as u16 & 0x1F) * 8;
-            let win_h_dir = (h_pos & 0x80) != 0;
-            let v_dir = (v_pos & 0x80) != 0;
-            let win_in_v = if v_dir {
-                fetch_line >= v_point
-            } else {
-                fetch_line < v_point
-            };
-            let win_addr = self.window_address();
-            let win_w = if h40_mode { 64 } else { 32 };
-
-            let win_params = TileRenderParams {
-                is_plane_a: true,
-                enable_v_scroll: false,
-                name_table_base: win_addr,
-                plane_w: win_w,
-                plane_h,
-                plane_w_mask: win_w - 1,
-                h_scroll: 0,
-                fetch_line,
-                scanline_width: screen_width,
-            };
-            let plane_params = TileRenderParams {
-                is_plane_a: true,
-                enable_v_scroll: true,
-                name_table_base,
-                plane_w,
-                plane_h,
-                plane_w_mask: plane_w - 1,
-                h_scroll,
-                fetch_line,
-                scanline_width: screen_width,
-            };
-
+            let win_params = self.tile_params(true, true, fetch_line);
             while screen_x < screen_width {
-                let in_h = if win_h_dir {
-                    screen_x >= win_h_point
-                } else {
-                    screen_x < win_h_point
-                };
-                let params = if in_h || win_in_v {
+                let params = if self.window_covers(screen_x, fetch_line) {
                     &win_params
                 } else {
                     &plane_params
@@ -544,50 +645,20 @@ impl RenderOps for Vdp {
             }
         } else {
             // Plane B never has a window
-            let tile_params = TileRenderParams {
-                is_plane_a: false,
-                enable_v_scroll: true,
-                name_table_base,
-                plane_w,
-                plane_h,
-                plane_w_mask: plane_w - 1,
-                h_scroll,
-                fetch_line,
-                scanline_width: screen_width,
-            };
             while screen_x < screen_width {
-                self.render_tile(&tile_params, &mut screen_x, line_buf);
+                self.render_tile(&plane_params, &mut screen_x, line_buf);
             }
         }
     }
 
     fn render_tile(&self, params: &TileRenderParams, screen_x: &mut u16, line_buf: &mut [u8; 320]) {
         let current_x = *screen_x;
-        // Horizontal position in plane
-        let scrolled_h = current_x.wrapping_sub(params.h_scroll);
-        let pixel_h = scrolled_h & 0x07;
-        let tile_h = ((scrolled_h >> 3) as usize) & params.plane_w_mask;
-
-        // Fetch V-scroll for this specific column (per-column VS support)
-        // If not using scroll (e.g. Window plane), V-scroll is 0.
-        let v_scroll = if params.enable_v_scroll {
-            self.get_v_scroll(params.is_plane_a, (current_x >> 3) as usize)
-        } else {
-            0
-        };
-
-        // Vertical position in plane
-        let scrolled_v = params.fetch_line.wrapping_add(v_scroll);
-        let tile_v = ((scrolled_v / 8) as usize) % params.plane_h;
-        let pixel_v = scrolled_v % 8;
+        let (entry, pixel_h, pixel_v) = self.tile_at(params, current_x);
 
         let pixels_left_in_tile = 8 - pixel_h;
         let pixels_to_process =
             std::cmp::min(pixels_left_in_tile, params.scanline_width - current_x);
 
-        let entry =
-            self.fetch_nametable_entry(params.name_table_base, tile_v, tile_h, params.plane_w);
-
         if pixels_to_process == 8 && pixel_h == 0 {
             // Fast path for full aligned tile
             self.draw_full_tile_row(entry, pixel_v, current_x as usize, line_buf);
@@ -605,51 +676,18 @@ impl RenderOps for Vdp {
     }
 
     fn get_active_sprites(&self, line: u16, sprites: &mut [SpriteAttributes]) -> usize {
-        let mut count = 0;
-        let mut pixels = 0;
-
-        let sat_base = self.sprite_table_address();
-        let max_sprites = if self.h40_mode() { 80 } else { 64 };
-        let line_limit = if self.h40_mode() { 20 } else { 16 };
-        let pixel_limit = if self.h40_mode() { 320 } else { 256 };
+        if self.sat_cache_valid {
+            return self.select_line_sprites(self.sat_cache.iter().copied(), line, sprites);
+        }
 
         let iter = SpriteIterator {
             vram: &self.vram,
             next_idx: 0,
             count: 0,
-            max_sprites,
-            sat_base,
+            max_sprites: if self.h40_mode() { 80 } else { 64 },
+            sat_base: self.sprite_table_address(),
         };
-
-        for attr in iter {
-            let sprite_v_px = (attr.v_size as u16) * 8;
-
-            // X=0 suppression mode
-            if attr.h_pos == 0 {
-                // Technically hardware suppresses rendering but continues processing,
-                // but for our buffer we can just skip it visually
-            }
-
-            // Handle wrapping v_pos (top clipping) correctly using wrapping subtraction
-            if line.wrapping_sub(attr.v_pos) < sprite_v_px {
-                // If the sprite falls on this scanline, add it
-                if count < sprites.len() {
-                    sprites[count] = attr;
-                    count += 1;
-
-                    pixels += (attr.h_size as usize) * 8;
-                }
-
-                if count >= line_limit {
-                    break;
-                }
-
-                if pixels >= pixel_limit {
-                    break;
-                }
-            }
-        }
-        count
+        self.select_line_sprites(iter, line, sprites)
     }
 
     fn render_sprites(
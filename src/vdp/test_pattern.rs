@@ -0,0 +1,144 @@
+//! Built-in test images for validating the renderer without a ROM
+//!
+//! `Vdp::load_test_pattern` programs registers, VRAM and CRAM so that plane A
+//! alone draws a known H40 image. `Pattern::expected_color` gives the colour
+//! each pixel should come out as, for comparing against the framebuffer.
+
+use super::constants::*;
+use super::Vdp;
+
+const PLANE_A_BASE: usize = 0xC000;
+/// 64 cells wide, so one nametable row covers the 40 visible cells
+const PLANE_WIDTH: usize = 64;
+const PLANE_HEIGHT: usize = 32;
+
+/// CRAM values for palette 0 entries 1-8: white, yellow, cyan, green,
+/// magenta, red, blue and black
+const BAR_COLORS: [u16; 8] = [
+    0x0EEE, 0x00EE, 0x0EE0, 0x00E0, 0x0E0E, 0x000E, 0x0E00, 0x0000,
+];
+/// Width in pixels of each colour bar (320 / 8)
+const BAR_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Eight vertical bars, 40 pixels each, from white to black
+    ColorBars,
+    /// Alternating white and black 8x8 cells
+    Checkerboard,
+    /// Blue cells outlined in white along their top and left edges
+    TileGrid,
+}
+
+impl Pattern {
+    /// RGB565 colour the pattern should render at `(x, y)`
+    pub fn expected_color(self, x: usize, y: usize) -> u16 {
+        let index = match self {
+            Pattern::ColorBars => x / BAR_WIDTH,
+            Pattern::Checkerboard => {
+                if (x / 8 + y / 8).is_multiple_of(2) {
+                    0
+                } else {
+                    7
+                }
+            }
+            Pattern::TileGrid => {
+                if x.is_multiple_of(8) || y.is_multiple_of(8) {
+                    0
+                } else {
+                    6
+                }
+            }
+        };
+        Vdp::genesis_color_to_rgb565(BAR_COLORS[index])
+    }
+
+    /// Palette 0 entry of pixel `(col, row)` in `tile` of this pattern's
+    /// tile set
+    fn tile_pixel(self, tile: usize, row: usize, col: usize) -> u8 {
+        match self {
+            // One solid tile per bar
+            Pattern::ColorBars => tile as u8 + 1,
+            // Solid white, then solid black
+            Pattern::Checkerboard => [1, 8][tile],
+            Pattern::TileGrid => {
+                if row == 0 || col == 0 {
+                    1
+                } else {
+                    7
+                }
+            }
+        }
+    }
+
+    fn tile_count(self) -> usize {
+        match self {
+            Pattern::ColorBars => BAR_COLORS.len(),
+            Pattern::Checkerboard => 2,
+            Pattern::TileGrid => 1,
+        }
+    }
+
+    /// Tile set index shown in nametable cell `(cx, cy)`
+    fn cell_tile(self, cx: usize, cy: usize) -> usize {
+        match self {
+            Pattern::ColorBars => (cx * 8 / BAR_WIDTH).min(BAR_COLORS.len() - 1),
+            Pattern::Checkerboard => (cx + cy) % 2,
+            Pattern::TileGrid => 0,
+        }
+    }
+}
+
+impl Vdp {
+    /// Replace VRAM, CRAM, VSRAM and the display registers with `pattern`.
+    ///
+    /// The result is an H40, 224-line display with only plane A visible:
+    /// no scrolling, no window and no sprites.
+    pub fn load_test_pattern(&mut self, pattern: Pattern) {
+        self.vram.fill(0);
+        self.cram.fill(0);
+        self.vsram.fill(0);
+        self.registers.fill(0);
+
+        self.registers[REG_MODE1] = 0x04;
+        self.registers[REG_MODE2] = MODE2_DISPLAY_ENABLE | 0x04;
+        self.registers[REG_PLANE_A] = (PLANE_A_BASE >> 10) as u8;
+        self.registers[REG_PLANE_B] = (0xE000 >> 13) as u8;
+        self.registers[REG_SPRITE_TABLE] = (0xF800 >> 9) as u8;
+        self.registers[REG_MODE4] = MODE4_H40_MODE;
+        self.registers[REG_HSCROLL] = (0xFC00 >> 10) as u8;
+        self.registers[REG_AUTO_INC] = 2;
+        // 64x32 cells
+        self.registers[REG_PLANE_SIZE] = 0x01;
+
+        // Palette 0 entries 1-8, as the data port would store them
+        for (i, &color) in BAR_COLORS.iter().enumerate() {
+            let addr = (i + 1) * 2;
+            self.cram[addr] = (color & 0xFF) as u8;
+            self.cram[addr + 1] = (color >> 8) as u8;
+        }
+        self.reconstruct_cram_cache();
+
+        // The tile set starts at tile 1; tile 0 stays blank
+        for tile in 0..pattern.tile_count() {
+            let base = (tile + 1) * 32;
+            for row in 0..8 {
+                for pair in 0..4 {
+                    let hi = pattern.tile_pixel(tile, row, pair * 2);
+                    let lo = pattern.tile_pixel(tile, row, pair * 2 + 1);
+                    self.vram[base + row * 4 + pair] = (hi << 4) | lo;
+                }
+            }
+        }
+
+        for cy in 0..PLANE_HEIGHT {
+            for cx in 0..PLANE_WIDTH {
+                let entry = pattern.cell_tile(cx, cy) as u16 + 1;
+                let addr = PLANE_A_BASE + (cy * PLANE_WIDTH + cx) * 2;
+                self.write_vram_word(addr as u16, entry);
+            }
+        }
+
+        self.invalidate_sprite_cache();
+    }
+}
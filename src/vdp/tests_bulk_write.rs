@@ -100,3 +100,28 @@ fn test_bulk_write_wrapping() {
     assert_eq!(vdp.vram[0x0001], 0x44);
     assert_eq!(vdp.command.address, 0x0002);
 }
+
+#[test]
+fn test_word_write_at_odd_top_address_stays_in_word_pair() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.write_control(0x8F02);
+
+    // VRAM write at 0xFFFF
+    vdp.write_control(0x7FFF);
+    vdp.write_control(0x0003);
+    assert_eq!(vdp.command.address, 0xFFFF);
+
+    vdp.write_data(0x1122);
+    // Odd address swaps the bytes within 0xFFFE-0xFFFF; nothing spills to 0x0000
+    assert_eq!(vdp.vram[0xFFFF], 0x11);
+    assert_eq!(vdp.vram[0xFFFE], 0x22);
+    assert_eq!(vdp.vram[0x0000], 0x00);
+    assert_eq!(vdp.command.address, 0x0001);
+
+    // The next write wraps as a unit to the first word pair
+    vdp.write_data(0x3344);
+    assert_eq!(vdp.vram[0x0001], 0x33);
+    assert_eq!(vdp.vram[0x0000], 0x44);
+    assert_eq!(vdp.command.address, 0x0003);
+}
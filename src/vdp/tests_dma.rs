@@ -85,6 +85,38 @@ fn test_dma_fill_vram() {
     assert_eq!(vdp.vram[0x10], 0x00, "Should stop at 0x10");
 }
 
+#[test]
+fn test_dma_fill_waits_for_data_port_write() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.write_control(0x8114); // DMA enable
+    vdp.write_control(0x9308); // Length 8
+    vdp.write_control(0x9400);
+    vdp.write_control(0x9780); // Fill
+    vdp.write_control(0x8F01); // Auto-increment 1
+
+    // Fill destination VRAM 0x2000
+    vdp.write_control(0x6000);
+    vdp.write_control(0x0080);
+    assert!(vdp.command.dma_pending);
+    assert!(vdp.command.dma_fill_awaiting_data);
+
+    // Free access slots must not start the fill before the value arrives
+    vdp.set_vblank(true);
+    vdp.tick(3420, |_| 0);
+    assert!(vdp.command.dma_pending);
+    assert!(vdp.vram[0x2000..0x2010].iter().all(|&b| b == 0));
+    assert_eq!(vdp.command.address, 0x2000);
+
+    vdp.write_data(0x5A00);
+    assert!(!vdp.command.dma_fill_awaiting_data);
+    assert!(!vdp.command.dma_pending);
+    assert_eq!(vdp.vram[0x2000], 0x00, "First write is the LSB");
+    assert!(vdp.vram[0x2001..0x2008].iter().all(|&b| b == 0x5A));
+    assert_eq!(vdp.vram[0x2008], 0x00);
+    assert_eq!(vdp.command.address, 0x2008);
+}
+
 #[test]
 fn test_dma_copy_vram() {
     let mut vdp = Vdp::new();
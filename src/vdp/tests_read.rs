@@ -1,4 +1,4 @@
-use crate::vdp::{Vdp, VRAM_READ};
+use crate::vdp::{Vdp, VRAM_READ, VRAM_READ_8BIT};
 
 #[test]
 fn test_vram_read_prefetch_wraps_at_end_of_vram() {
@@ -28,3 +28,61 @@ fn test_vram_read_prefetch_uses_vram_word_layout_for_odd_addresses() {
     assert_eq!(vdp.command.read_buffer, 0xABCD);
     assert_eq!(vdp.command.address, 0x0003);
 }
+
+#[test]
+fn test_data_write_with_vram_read_code_is_dropped() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.registers[15] = 2;
+    vdp.vram[0x0100] = 0x11;
+    vdp.vram[0x0101] = 0x22;
+
+    // VRAM read at 0x0100
+    vdp.write_control(0x0100);
+    vdp.write_control(0x0000);
+    vdp.write_data(0xBEEF);
+
+    assert_eq!(vdp.vram[0x0100..0x0102], [0x11, 0x22]);
+    assert_eq!(
+        vdp.command.address, 0x0104,
+        "Prefetch and the write both advance"
+    );
+    assert!(vdp
+        .vram
+        .iter()
+        .all(|&b| b == 0x00 || b == 0x11 || b == 0x22));
+}
+
+#[test]
+fn test_data_read_with_vram_write_code_returns_fifo_word() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.registers[15] = 2;
+
+    // VRAM write at 0x0200
+    vdp.write_control(0x4200);
+    vdp.write_control(0x0000);
+    vdp.write_data(0x1234);
+    assert_eq!(vdp.command.address, 0x0202);
+
+    assert_eq!(vdp.read_data(), 0x1234);
+    assert_eq!(vdp.read_data(), 0x1234);
+    assert_eq!(vdp.command.address, 0x0202, "Address is left alone");
+    assert_eq!(vdp.vram[0x0200..0x0202], [0x12, 0x34]);
+}
+
+#[test]
+fn test_8bit_vram_read_takes_high_byte_from_fifo() {
+    let mut vdp = Vdp::new();
+    vdp.command.code = VRAM_READ_8BIT;
+    vdp.command.address = 0x0010;
+    vdp.registers[15] = 1;
+    vdp.vram[0x0010] = 0xAA;
+    vdp.vram[0x0011] = 0x55;
+    vdp.last_data_write = 0x9876;
+
+    vdp.try_prefetch();
+
+    assert_eq!(vdp.command.read_buffer, 0x9855);
+    assert_eq!(vdp.command.address, 0x0011);
+}
@@ -620,3 +620,516 @@ fn test_sprite_fully_oob_rendering() {
     // This should not panic.
     vdp.render_line(0);
 }
+
+fn write_vram_via_port(vdp: &mut Vdp, addr: u16, words: &[u16]) {
+    vdp.write_control(0x4000 | (addr & 0x3FFF));
+    vdp.write_control(addr >> 14);
+    for &w in words {
+        vdp.write_data(w);
+    }
+}
+
+#[test]
+fn test_sprite_cache_tracks_sat_writes() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.write_control(0x8C81); // H40
+    vdp.write_control(0x856A); // SAT at 0xD400
+    vdp.write_control(0x8F02); // Auto-increment 2
+
+    // Sprite 0: y=16, 1x1, link 0, tile 1, x=32
+    write_vram_via_port(&mut vdp, 0xD400, &[0x0090, 0x0000, 0x0001, 0x00A0]);
+    let sprites = vdp.sprite_attributes();
+    assert_eq!(sprites.len(), 1);
+    assert_eq!((sprites[0].h_pos, sprites[0].v_pos), (32, 16));
+
+    // Move the sprite horizontally; the next access must see it
+    write_vram_via_port(&mut vdp, 0xD406, &[0x00C0]);
+    assert_eq!(vdp.sprite_attributes()[0].h_pos, 64);
+
+    // Link in sprite 1
+    write_vram_via_port(&mut vdp, 0xD402, &[0x0001]);
+    write_vram_via_port(&mut vdp, 0xD408, &[0x0080, 0x0500, 0x0002, 0x0080]);
+    let sprites = vdp.sprite_attributes();
+    assert_eq!(sprites.len(), 2);
+    assert_eq!((sprites[1].h_size, sprites[1].v_size), (2, 2));
+}
+
+#[test]
+fn test_sprite_cache_ignores_writes_outside_sat() {
+    let mut vdp = Vdp::new();
+    vdp.bypass_fifo = true;
+    vdp.write_control(0x8C81);
+    vdp.write_control(0x856A);
+    vdp.write_control(0x8F02);
+    write_vram_via_port(&mut vdp, 0xD400, &[0x0090, 0x0000, 0x0001, 0x00A0]);
+    vdp.sprite_attributes();
+    assert!(vdp.sat_cache_valid);
+
+    // Pattern and nametable writes leave the parsed SAT alone
+    write_vram_via_port(&mut vdp, 0x0020, &[0x1234, 0x5678]);
+    write_vram_via_port(&mut vdp, 0xC000, &[0x0001]);
+    write_vram_via_port(&mut vdp, 0xD400 + 80 * 8, &[0xFFFF]);
+    assert!(vdp.sat_cache_valid);
+
+    // Moving the SAT does invalidate it
+    vdp.write_control(0x8568);
+    assert!(!vdp.sat_cache_valid);
+    assert_eq!(vdp.sprite_attributes()[0].v_pos, 0u16.wrapping_sub(128));
+}
+
+/// Plane A tile plus one sprite, enough to exercise every layer
+fn build_static_scene() -> Vdp {
+    let mut vdp = Vdp::new();
+    vdp.registers[1] = 0x40; // Display enable
+    vdp.registers[2] = 0x30; // Plane A at 0xC000
+    vdp.registers[5] = 0x6A; // SAT at 0xD400
+    vdp.registers[7] = 0x02; // Backdrop: palette 0, colour 2
+    vdp.registers[12] = 0x81; // H40
+    vdp.cram_cache[1] = 0xF800;
+    vdp.cram_cache[2] = 0x001F;
+    vdp.cram_cache[17] = 0x07E0;
+    for i in 0..32 {
+        vdp.vram[32 + i] = 0x11;
+    }
+    vdp.vram[0xC000 + 1] = 0x01;
+    vdp.vram[0xD400..0xD408].copy_from_slice(&[0x00, 0x88, 0x05, 0x00, 0x20, 0x01, 0x00, 0x90]);
+    vdp
+}
+
+#[test]
+fn test_border_filled_with_backdrop_around_scene() {
+    let mut vdp = build_static_scene();
+    assert!(vdp.bordered_framebuffer().is_empty());
+    vdp.set_border_width(8);
+    vdp.render_frame();
+    vdp.present();
+
+    let (width, height) = vdp.bordered_size();
+    assert_eq!((width, height), (336, 240));
+    let bordered = vdp.bordered_framebuffer();
+    assert_eq!(bordered.len(), width * height);
+
+    let backdrop = 0x001F;
+    for y in 0..height {
+        for x in 0..width {
+            let inside = (8..8 + 320).contains(&x) && (8..8 + 224).contains(&y);
+            if !inside {
+                assert_eq!(
+                    bordered[y * width + x],
+                    backdrop,
+                    "border at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+    // Active area matches the plain framebuffer, scene included
+    for y in 0..224 {
+        let row = &bordered[(y + 8) * width + 8..(y + 8) * width + 8 + 320];
+        assert_eq!(row, &vdp.framebuffer[y * 320..y * 320 + 320]);
+    }
+    assert!(bordered.contains(&0x07E0));
+}
+
+#[test]
+fn test_border_fits_h32_display() {
+    let mut vdp = build_static_scene();
+    vdp.registers[REG_MODE4] &= !MODE4_H40_MODE;
+    vdp.set_border_width(8);
+    vdp.render_frame();
+    vdp.present();
+
+    let (width, height) = vdp.bordered_size();
+    assert_eq!((width, height), (272, 240));
+    let bordered = vdp.bordered_framebuffer();
+    assert_eq!(bordered.len(), width * height);
+
+    // The right border starts straight after the 256 active pixels
+    for y in 0..224 {
+        let row = &bordered[(y + 8) * width..(y + 9) * width];
+        assert_eq!(&row[8..8 + 256], &vdp.framebuffer[y * 320..y * 320 + 256]);
+        assert!(
+            row[8 + 256..].iter().all(|&pixel| pixel == 0x001F),
+            "line {}",
+            y
+        );
+    }
+}
+
+#[test]
+fn test_dumped_state_renders_identically() {
+    // Deserializing VRAM by value needs more than the default test stack
+    std::thread::Builder::new()
+        .stack_size(16 * 1024 * 1024)
+        .spawn(|| {
+            let mut original = build_static_scene();
+            // The scene's palette lives in the cache only; write it to CRAM proper
+            original.bypass_fifo = true;
+            original.registers[15] = 2;
+            original.write_control(0xC002);
+            original.write_control(0x0000);
+            original.write_data(0x000E); // 1: red
+            original.write_data(0x0E00); // 2: blue
+            original.write_control(0xC022);
+            original.write_control(0x0000);
+            original.write_data(0x00E0); // 17: green
+            original.registers[16] = 0x01; // 64x32 plane
+            original.vsram[0] = 0x04;
+
+            let dump = original.dump_state().unwrap();
+            let mut restored = Vdp::new();
+            restored.load_state(&dump).unwrap();
+            assert_eq!(restored.vram[..], original.vram[..]);
+            assert_eq!(restored.registers, original.registers);
+
+            original.render_frame();
+            restored.render_frame();
+            assert!(original.framebuffer.iter().any(|&p| p != 0));
+            assert_eq!(restored.framebuffer, original.framebuffer);
+
+            assert!(restored.load_state(b"not a vdp").is_err());
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn test_resolve_pixel_priority_order() {
+    use super::render::{resolve_pixel, PixelLayerData, ResolvedPixel, Shade};
+    const HIGH: u8 = 0x80;
+    let resolve =
+        |b: u8, a: u8, s: u8| resolve_pixel(&PixelLayerData::from_layers(0x01, b, a, s), false);
+    let normal = |color_idx| ResolvedPixel {
+        color_idx,
+        shade: Shade::Normal,
+    };
+
+    // High sprite beats high plane A
+    assert_eq!(resolve(HIGH | 0x12, HIGH | 0x23, HIGH | 0x34), normal(0x34));
+    // A high plane beats a low sprite, even plane B under a transparent A
+    assert_eq!(resolve(0x12, HIGH | 0x23, 0x34), normal(0x23));
+    assert_eq!(resolve(HIGH | 0x12, 0x23, 0x34), normal(0x12));
+    // Among low layers: sprite over A over B
+    assert_eq!(resolve(0x12, 0x23, 0x34), normal(0x34));
+    assert_eq!(resolve(0x12, 0x23, 0x30), normal(0x23));
+    // High B beats low A; a transparent high layer doesn't count
+    assert_eq!(resolve(HIGH | 0x12, 0x23, 0x00), normal(0x12));
+    assert_eq!(resolve(0x12, HIGH | 0x20, 0x00), normal(0x12));
+    // Nothing opaque shows the backdrop
+    assert_eq!(resolve(HIGH | 0x10, 0x20, 0x30), normal(0x01));
+}
+
+#[test]
+fn test_resolve_pixel_shadow_highlight() {
+    use super::render::{resolve_pixel, PixelLayerData, ResolvedPixel, Shade};
+    let resolve =
+        |b: u8, a: u8, s: u8| resolve_pixel(&PixelLayerData::from_layers(0x01, b, a, s), true);
+
+    // All-low pixels are shadowed; any high layer lifts them to normal
+    assert_eq!(resolve(0x12, 0x00, 0x00).shade, Shade::Shadow);
+    assert_eq!(resolve(0x12, 0x80, 0x00).shade, Shade::Normal);
+    // Palette 3 colour 14 highlights and 15 shadows what's under the sprite
+    assert_eq!(
+        resolve(0x12, 0x00, 0x80 | 0x3E),
+        ResolvedPixel {
+            color_idx: 0x12,
+            shade: Shade::Highlight
+        }
+    );
+    assert_eq!(
+        resolve(0x12, 0x00, 0x80 | 0x3F),
+        ResolvedPixel {
+            color_idx: 0x12,
+            shade: Shade::Shadow
+        }
+    );
+    assert_eq!(resolve(0x12, 0x00, 0x3F).shade, Shade::Shadow);
+    // The operator only applies when the sprite is the top layer
+    assert_eq!(
+        resolve(0x80 | 0x12, 0x00, 0x3E),
+        ResolvedPixel {
+            color_idx: 0x12,
+            shade: Shade::Normal
+        }
+    );
+    // Colour 14 in other palettes is drawn, never shadowed
+    assert_eq!(
+        resolve(0x12, 0x00, 0x2E),
+        ResolvedPixel {
+            color_idx: 0x2E,
+            shade: Shade::Normal
+        }
+    );
+}
+
+#[test]
+fn test_mid_frame_h40_switch_keeps_frame_layout() {
+    let mut vdp = Vdp::new();
+    vdp.registers[1] = 0x40; // Display enable
+    vdp.registers[2] = 0x30; // Plane A at 0xC000
+    vdp.registers[12] = 0x81; // H40
+    vdp.registers[16] = 0x01; // 64x32, wide enough for H40
+    vdp.cram_cache[1] = 0xF800;
+    vdp.vram[32..64].fill(0x11); // Tile 1: solid colour 1
+    for cell in 0..64 * 32 {
+        vdp.vram[0xC000 + cell * 2 + 1] = 0x01;
+    }
+    vdp.set_border_width(4);
+    let backdrop = vdp.cram_cache[0];
+
+    for line in 0..224 {
+        if line == 100 {
+            vdp.registers[12] = 0x00; // Switch to H32 mid-frame
+        }
+        vdp.render_line(line);
+    }
+    vdp.present();
+
+    assert_eq!(vdp.frame_width(), 320, "Layout follows the mode at line 0");
+    let (width, _) = vdp.bordered_size();
+    for line in 0..224usize {
+        let row = &vdp.framebuffer[line * 320..line * 320 + 320];
+        let drawn = if line < 100 { 320 } else { 256 };
+        assert!(row[..drawn].iter().all(|&p| p == 0xF800), "line {}", line);
+        assert!(row[drawn..].iter().all(|&p| p == backdrop), "line {}", line);
+
+        let bordered = &vdp.bordered_framebuffer()[(line + 4) * width + 4..];
+        assert_eq!(&bordered[..320], row, "bordered line {}", line);
+    }
+
+    // The next frame starts in H32 and clips a late switch back to H40
+    vdp.render_line(0);
+    assert_eq!(vdp.frame_width(), 256);
+    vdp.registers[12] = 0x81;
+    vdp.render_line(1);
+    let row = &vdp.framebuffer[320..640];
+    assert!(row[..256].iter().all(|&p| p == 0xF800));
+    assert!(row[256..].iter().all(|&p| p == backdrop));
+}
+
+#[test]
+fn test_color_bars_pattern_renders_expected_columns() {
+    let mut vdp = Vdp::new();
+    vdp.load_test_pattern(Pattern::ColorBars);
+    vdp.render_frame();
+
+    assert_eq!(vdp.screen_width(), 320);
+    let white = Vdp::genesis_color_to_rgb565(0x0EEE);
+    let blue = Vdp::genesis_color_to_rgb565(0x0E00);
+    for y in [0, 100, 223] {
+        let row = &vdp.framebuffer[y * 320..(y + 1) * 320];
+        assert_eq!(row[0], white, "line {} starts with the white bar", y);
+        assert_eq!(row[6 * 40 + 20], blue, "line {} has blue as bar 7", y);
+        for (x, &pixel) in row.iter().enumerate() {
+            assert_eq!(
+                pixel,
+                Pattern::ColorBars.expected_color(x, y),
+                "({}, {})",
+                x,
+                y
+            );
+        }
+    }
+    // Every bar is a distinct colour
+    let bars: std::collections::HashSet<u16> =
+        (0..8).map(|bar| vdp.framebuffer[bar * 40]).collect();
+    assert_eq!(bars.len(), 8);
+}
+
+#[test]
+fn test_checkerboard_and_grid_patterns_match_expected() {
+    for pattern in [Pattern::Checkerboard, Pattern::TileGrid] {
+        let mut vdp = Vdp::new();
+        vdp.load_test_pattern(pattern);
+        vdp.render_frame();
+        for y in 0..224 {
+            for x in 0..320 {
+                assert_eq!(
+                    vdp.framebuffer[y * 320 + x],
+                    pattern.expected_color(x, y),
+                    "{:?} at ({}, {})",
+                    pattern,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_front_buffer_changes_only_on_present() {
+    let mut vdp = Vdp::new();
+    vdp.load_test_pattern(Pattern::ColorBars);
+    vdp.render_frame();
+
+    assert!(
+        vdp.front_buffer().iter().all(|&p| p == 0),
+        "rendering only touches the back buffer"
+    );
+
+    let rendered = vdp.framebuffer.clone();
+    vdp.present();
+    assert_eq!(vdp.front_buffer(), &rendered[..]);
+
+    // Drawing the next frame leaves the presented one alone
+    vdp.load_test_pattern(Pattern::Checkerboard);
+    vdp.render_frame();
+    assert_eq!(vdp.front_buffer(), &rendered[..]);
+    vdp.present();
+    assert_eq!(
+        vdp.front_buffer()[8],
+        Pattern::Checkerboard.expected_color(8, 0)
+    );
+}
+
+#[test]
+fn test_present_clears_rows_below_a_shorter_frame() {
+    let mut vdp = Vdp::new();
+    vdp.load_test_pattern(Pattern::ColorBars);
+    vdp.registers[REG_MODE2] |= MODE2_V30_MODE;
+    vdp.render_frame();
+    vdp.present();
+    assert!(vdp.front_buffer()[239 * 320..].iter().any(|&p| p != 0));
+
+    // A V28 frame leaves lines 224-239 of the back buffer as they were
+    vdp.registers[REG_MODE2] &= !MODE2_V30_MODE;
+    vdp.render_frame();
+    vdp.present();
+    assert_eq!(
+        vdp.front_buffer()[..224 * 320],
+        vdp.framebuffer[..224 * 320]
+    );
+    assert!(vdp.front_buffer()[224 * 320..].iter().all(|&p| p == 0));
+}
+
+#[test]
+fn test_bordered_output_changes_only_on_present() {
+    let mut vdp = Vdp::new();
+    vdp.load_test_pattern(Pattern::ColorBars);
+    vdp.set_border_width(4);
+    vdp.render_frame();
+    assert!(vdp.bordered_framebuffer().is_empty());
+    vdp.present();
+    let presented = vdp.bordered_framebuffer().to_vec();
+    assert_eq!(presented.len(), 328 * 232);
+
+    // Part of the next frame is drawn, but the presented one stays whole
+    vdp.load_test_pattern(Pattern::Checkerboard);
+    for line in 0..100 {
+        vdp.render_line(line);
+    }
+    assert_eq!(vdp.bordered_framebuffer(), &presented[..]);
+}
+
+/// Render line 10 of the colour bars, then write green to CRAM entry 1 with
+/// the beam `mclk` into the line
+fn cram_write_mid_line(cram_dots: bool, mclk: u32) -> Vdp {
+    let mut vdp = Vdp::new();
+    vdp.set_cram_dots(cram_dots);
+    vdp.load_test_pattern(Pattern::ColorBars);
+    vdp.bypass_fifo = true;
+    vdp.render_scanline(10);
+    vdp.v_counter = 10;
+    vdp.mclk_line_clocks = mclk;
+    // CRAM write to entry 1
+    vdp.write_control(0xC002);
+    vdp.write_control(0x0000);
+    vdp.write_data(0x00E0);
+    vdp
+}
+
+#[test]
+fn test_mid_line_cram_write_draws_dot_under_the_beam() {
+    let green = Vdp::genesis_color_to_rgb565(0x00E0);
+    // 800 MCLKs in, the H40 beam is at pixel 100
+    let vdp = cram_write_mid_line(true, 800);
+    let row = &vdp.framebuffer[10 * 320..11 * 320];
+    assert_eq!(row[100], green, "the dot");
+    for x in (0..320).filter(|&x| x != 100) {
+        assert_eq!(
+            row[x],
+            Pattern::ColorBars.expected_color(x, 10),
+            "x = {}",
+            x
+        );
+    }
+    assert_eq!(vdp.cram_cache[1], green, "the write itself landed");
+
+    let clean = |vdp: &Vdp| {
+        (0..320).all(|x| vdp.framebuffer[10 * 320 + x] == Pattern::ColorBars.expected_color(x, 10))
+    };
+    assert!(clean(&cram_write_mid_line(false, 800)), "off by default");
+    assert!(clean(&cram_write_mid_line(true, 3000)), "none in HBlank");
+}
+
+#[test]
+fn test_pixel_sources_report_the_winning_layer() {
+    let mut vdp = Vdp::new();
+    vdp.registers[REG_MODE2] = MODE2_DISPLAY_ENABLE | 0x04;
+    vdp.registers[REG_PLANE_A] = (0xC000 >> 10) as u8;
+    vdp.registers[REG_WINDOW] = (0xB000 >> 10) as u8;
+    vdp.registers[REG_PLANE_B] = (0xE000 >> 13) as u8;
+    vdp.registers[REG_SPRITE_TABLE] = (0xD400 >> 9) as u8;
+    vdp.registers[REG_BG_COLOR] = 0x05;
+    vdp.registers[REG_MODE4] = MODE4_H40_MODE;
+    vdp.registers[REG_HSCROLL] = (0xFC00 >> 10) as u8;
+    vdp.registers[REG_PLANE_SIZE] = 0x01; // 64x32
+    vdp.registers[REG_WINDOW_V_POS] = 0x82; // Window from line 16 down
+
+    // Tiles 1-4 are solid in colours 1-4
+    for tile in 1..=4u8 {
+        vdp.vram[tile as usize * 32..][..32].fill(tile * 0x11);
+    }
+    // Plane B row 0 is all tile 2; plane A has tile 1 at cell 2 and a high
+    // priority tile 3 at cell 5; the window's row 2 starts with tile 1
+    for cx in 0..40 {
+        vdp.write_vram_word(0xE000 + cx * 2, 0x0002);
+    }
+    vdp.write_vram_word(0xC000 + 2 * 2, 0x0001);
+    vdp.write_vram_word(0xC000 + 5 * 2, 0x8003);
+    vdp.write_vram_word(0xB000 + 2 * 64 * 2, 0x0001);
+
+    // Sprite 0: tile 4 at x=16, linked to sprite 1: tile 1, palette 1, x=36
+    for (i, word) in [
+        0x0080, 0x0001, 0x0004, 0x0090, 0x0080, 0x0000, 0x2001, 0x00A4,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        vdp.write_vram_word(0xD400 + i as u16 * 2, word);
+    }
+
+    let source = |x, y| vdp.pixel_source(x, y).unwrap();
+    let expect = |layer, index, color_idx| PixelSource {
+        layer,
+        index,
+        color_idx,
+    };
+    assert_eq!(source(0, 0), expect(PixelLayer::PlaneB, 2, 2));
+    // A low sprite is in front of low plane A
+    assert_eq!(source(18, 0), expect(PixelLayer::Sprite, 0, 4));
+    assert_eq!(source(37, 0), expect(PixelLayer::Sprite, 1, 0x11));
+    // ...but behind high plane A
+    assert_eq!(source(41, 0), expect(PixelLayer::PlaneA, 3, 3));
+    assert_eq!(source(0, 8), expect(PixelLayer::Backdrop, 0, 5));
+    assert_eq!(source(3, 16), expect(PixelLayer::Window, 1, 1));
+    assert_eq!(vdp.pixel_source(320, 0), None);
+
+    // The overlay agrees with what the renderer draws
+    for (i, color) in vdp.cram_cache.iter_mut().enumerate() {
+        *color = i as u16 + 1;
+    }
+    let sources = vdp.pixel_sources();
+    assert_eq!(sources.len(), 320 * 224);
+    vdp.render_line(0);
+    for (x, source) in sources[..320].iter().enumerate() {
+        assert_eq!(
+            vdp.framebuffer[x], vdp.cram_cache[source.color_idx as usize],
+            "x={x}"
+        );
+    }
+}
@@ -36,6 +36,16 @@ pub mod op_ed;
 
 pub mod op_index;
 
+/// Silicon variant, for the few undocumented behaviours that differ
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Z80Model {
+    /// Original NMOS part, as fitted to the Mega Drive. `OUT (C),0` drives 0.
+    #[default]
+    Nmos,
+    /// CMOS part (Z84C00). `OUT (C),0` drives 0xFF.
+    Cmos,
+}
+
 /// Decoded parameters for a Z80 instruction opcode
 #[derive(Clone, Copy)]
 pub struct OpParams {
@@ -109,6 +119,9 @@ pub struct Z80<M: MemoryInterface, I: IoInterface> {
 
     // Debug flag
     pub debug: bool,
+
+    // Silicon variant
+    pub model: Z80Model,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -142,6 +155,8 @@ pub struct Z80SaveState {
     pub halted: bool,
     pub pending_ei: bool,
     pub cycles: u64,
+    #[serde(default)]
+    pub model: Z80Model,
 }
 
 impl<M: MemoryInterface, I: IoInterface> Serialize for Z80<M, I> {
@@ -179,6 +194,7 @@ impl<M: MemoryInterface, I: IoInterface> Serialize for Z80<M, I> {
             halted: self.halted,
             pending_ei: self.pending_ei,
             cycles: self.cycles,
+            model: self.model,
         };
         state.serialize(serializer)
     }
@@ -223,6 +239,7 @@ impl<'de, M: MemoryInterface + Default, I: IoInterface + Default> Deserialize<'d
             memory: M::default(),
             io: I::default(),
             debug: false,
+            model: state.model,
         })
     }
 }
@@ -262,6 +279,7 @@ impl<M: MemoryInterface, I: IoInterface> Z80<M, I> {
             io,
             cycles: 0,
             debug: false,
+            model: Z80Model::default(),
         }
     }
 
@@ -804,7 +822,11 @@ impl<M: MemoryInterface, I: IoInterface> Z80<M, I> {
 
     /// Execute one instruction, returns number of T-states used
     pub fn step(&mut self) -> u8 {
+        // A halted Z80 keeps running NOP cycles, refreshing R, until an
+        // interrupt wakes it
         if self.halted {
+            self.r = (self.r & 0x80) | ((self.r.wrapping_add(1)) & 0x7F);
+            self.cycles += 4;
             return 4;
         }
 
@@ -836,6 +858,9 @@ impl<M: MemoryInterface, I: IoInterface> Z80<M, I> {
             3 => self.execute_x3(op_params),
             _ => 4,
         };
+        // Stalls the memory charged while the instruction ran
+        let wait = self.memory.take_wait_cycles().min(u8::MAX as u32) as u8;
+        let t_states = t_states.saturating_add(wait);
 
         self.cycles += t_states as u64;
         t_states
@@ -1,5 +1,5 @@
 use crate::memory::{IoInterface, MemoryInterface};
-use crate::z80::{flags, OpParams, Z80};
+use crate::z80::{flags, OpParams, Z80Model, Z80};
 
 pub trait EdOps {
     fn execute_ed_prefix(&mut self) -> u8;
@@ -39,7 +39,7 @@ fn execute_ed_in_r_c<M: MemoryInterface, I: IoInterface>(
     cpu: &mut Z80<M, I>,
     params: &OpParams,
 ) -> u8 {
-    // IN r, (C)
+    // IN r, (C); r == 6 is IN F,(C), which only sets the flags
     let port = cpu.bc();
     let val = cpu.read_port(port);
     if params.y != 6 {
@@ -56,10 +56,14 @@ fn execute_ed_out_c_r<M: MemoryInterface, I: IoInterface>(
     cpu: &mut Z80<M, I>,
     params: &OpParams,
 ) -> u8 {
-    // OUT (C), r
+    // OUT (C), r; r == 6 is the undocumented OUT (C),0
     let port = cpu.bc();
     let val = if params.y == 6 {
-        0
+        // NMOS drives 0 and CMOS 0xFF, as measured on real parts
+        match cpu.model {
+            Z80Model::Nmos => 0x00,
+            Z80Model::Cmos => 0xFF,
+        }
     } else {
         cpu.get_reg(params.y)
     };
@@ -93,7 +93,7 @@ fn execute_x0_control_misc<M: MemoryInterface, I: IoInterface>(cpu: &mut Z80<M,
             let d = cpu.fetch_byte() as i8;
             cpu.b = cpu.b.wrapping_sub(1);
             if cpu.b != 0 {
-                cpu.pc = (cpu.pc as i32 + d as i32) as u16;
+                cpu.pc = cpu.pc.wrapping_add(d as u16);
                 13
             } else {
                 8
@@ -102,14 +102,14 @@ fn execute_x0_control_misc<M: MemoryInterface, I: IoInterface>(cpu: &mut Z80<M,
         3 => {
             // JR d
             let d = cpu.fetch_byte() as i8;
-            cpu.pc = (cpu.pc as i32 + d as i32) as u16;
+            cpu.pc = cpu.pc.wrapping_add(d as u16);
             12
         }
         4..=7 => {
             // JR cc, d
             let d = cpu.fetch_byte() as i8;
             if cpu.check_condition(y - 4) {
-                cpu.pc = (cpu.pc as i32 + d as i32) as u16;
+                cpu.pc = cpu.pc.wrapping_add(d as u16);
                 12
             } else {
                 7
@@ -513,8 +513,9 @@ fn execute_x3_alu_n<M: MemoryInterface, I: IoInterface>(cpu: &mut Z80<M, I>, y:
 }
 
 fn execute_x3_rst<M: MemoryInterface, I: IoInterface>(cpu: &mut Z80<M, I>, y: u8) -> u8 {
-    // RST y*8
+    // RST y*8: pushes the address after the opcode, WZ follows the jump
     cpu.push(cpu.pc);
     cpu.pc = (y as u16) * 8;
+    cpu.memptr = cpu.pc;
     11
 }
@@ -88,7 +88,7 @@ fn calc_index_addr<M: MemoryInterface, I: IoInterface>(
     is_ix: bool,
 ) -> u16 {
     let idx = get_index_val(cpu, is_ix);
-    let addr = (idx as i16 + offset as i16) as u16;
+    let addr = idx.wrapping_add(offset as u16);
     cpu.memptr = addr;
     addr
 }
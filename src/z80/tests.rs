@@ -960,6 +960,20 @@ fn test_check_condition() {
     }
 }
 
+#[test]
+fn test_halted_step_runs_nop_cycles() {
+    let mut z80 = create_z80(&[0x76][..]); // HALT
+    z80.step();
+    assert!(z80.halted);
+    let (cycles, r) = (z80.cycles, z80.r);
+    for _ in 0..3 {
+        assert_eq!(z80.step(), 4);
+    }
+    assert_eq!(z80.cycles, cycles + 12);
+    assert_eq!(z80.r, r + 3);
+    assert_eq!(z80.pc, 1, "PC stays past the HALT");
+}
+
 #[test]
 fn test_trigger_nmi() {
     let mut z80 = create_z80(&[][..]);
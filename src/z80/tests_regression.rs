@@ -608,6 +608,70 @@ fn regression_sbc_hl_no_carry() {
     assert_eq!(c.hl(), 0x1134);
 }
 
+// ============ P/V: parity for logic, overflow for arithmetic ============
+
+fn pv_after(op: &[u8], a: u8, b: u8) -> bool {
+    let mut c = create_z80(op);
+    c.a = a;
+    c.b = b;
+    c.step();
+    c.get_flag(flags::PARITY)
+}
+
+// Operand pairs where AND's parity disagrees with ADD's overflow either way
+// round, one where only SUB overflows, and one that sets P/V for none, so a
+// P/V computed the wrong way for either kind of operation shows up
+#[test]
+fn regression_pv_logic_vs_arithmetic_matrix() {
+    // (a, b, AND parity, ADD overflow, SUB overflow)
+    let cases = [
+        (0x7F, 0x01, false, true, false),
+        (0x0F, 0x0F, true, false, false),
+        (0x80, 0x80, false, true, false),
+        (0xC0, 0xC0, true, false, false),
+        (0x80, 0x01, true, false, true),
+        (0x03, 0x05, false, false, false),
+    ];
+    for (a, b, and_pv, add_pv, sub_pv) in cases {
+        assert_eq!(pv_after(&[0xA0], a, b), and_pv, "AND {:02X},{:02X}", a, b);
+        assert_eq!(pv_after(&[0x80], a, b), add_pv, "ADD {:02X},{:02X}", a, b);
+        assert_eq!(pv_after(&[0x90], a, b), sub_pv, "SUB {:02X},{:02X}", a, b);
+
+        let or = a | b;
+        let xor = a ^ b;
+        assert_eq!(pv_after(&[0xB0], a, b), or.count_ones().is_multiple_of(2));
+        assert_eq!(pv_after(&[0xA8], a, b), xor.count_ones().is_multiple_of(2));
+    }
+}
+
+#[test]
+fn regression_pv_inc_dec_overflow_only() {
+    // INC A: overflow only on 0x7F -> 0x80, regardless of result parity
+    for a in 0..=0xFFu8 {
+        assert_eq!(pv_after(&[0x3C], a, 0), a == 0x7F, "INC {:02X}", a);
+        assert_eq!(pv_after(&[0x3D], a, 0), a == 0x80, "DEC {:02X}", a);
+    }
+}
+
+#[test]
+fn regression_pv_cb_rotates_set_parity() {
+    // RLC, RRC, RL, RR, SLA, SRA, SLL, SRL on A
+    for op in [0x07u8, 0x0F, 0x17, 0x1F, 0x27, 0x2F, 0x37, 0x3F] {
+        for a in [0x00u8, 0x01, 0x7F, 0x80, 0x81, 0xC3, 0xFF] {
+            let mut c = create_z80(&[0xCB, op]);
+            c.a = a;
+            c.step();
+            assert_eq!(
+                c.get_flag(flags::PARITY),
+                c.a.count_ones().is_multiple_of(2),
+                "CB {:02X} on {:02X}",
+                op,
+                a
+            );
+        }
+    }
+}
+
 // ============ Boundary conditions ============
 
 #[test]
@@ -641,6 +705,61 @@ fn regression_pc_wrap() {
     assert_eq!(c.pc, 0x0000);
 }
 
+#[test]
+fn regression_jr_backward_wraps_to_high_memory() {
+    // 0x0003: JR -16 -> 0x0005 - 16 = 0xFFF5
+    let mut c = create_z80(&[0x00, 0x00, 0x00, 0x18, 0xF0]);
+    c.pc = 0x0003;
+    c.step();
+    assert_eq!(c.pc, 0xFFF5);
+}
+
+#[test]
+fn regression_jr_forward_wraps_to_low_memory() {
+    // 0xFFFE: JR +4 -> 0x0000 + 4 = 0x0004
+    let mut c = create_z80(&[]);
+    c.memory.data[0xFFFE] = 0x18;
+    c.memory.data[0xFFFF] = 0x04;
+    c.pc = 0xFFFE;
+    c.step();
+    assert_eq!(c.pc, 0x0004);
+}
+
+#[test]
+fn regression_jr_cc_and_djnz_wrap() {
+    // 0x0000: JR Z, -8 -> 0xFFFA
+    let mut c = create_z80(&[0x28, 0xF8]);
+    c.set_flag(flags::ZERO, true);
+    c.step();
+    assert_eq!(c.pc, 0xFFFA);
+
+    // 0xFFFE: DJNZ +2 -> 0x0002
+    let mut c = create_z80(&[]);
+    c.memory.data[0xFFFE] = 0x10;
+    c.memory.data[0xFFFF] = 0x02;
+    c.pc = 0xFFFE;
+    c.b = 2;
+    c.step();
+    assert_eq!(c.pc, 0x0002);
+}
+
+#[test]
+fn regression_rst_pushes_return_and_sets_memptr() {
+    // 0x1234: RST 20h
+    let mut c = create_z80(&[]);
+    c.memory.data[0x1234] = 0xE7;
+    c.pc = 0x1234;
+    c.sp = 0x8000;
+    c.memptr = 0;
+    let cycles = c.step();
+    assert_eq!(cycles, 11);
+    assert_eq!(c.pc, 0x0020);
+    assert_eq!(c.sp, 0x7FFE);
+    assert_eq!(c.memory.data[0x7FFE], 0x35);
+    assert_eq!(c.memory.data[0x7FFF], 0x12);
+    assert_eq!(c.memptr, 0x0020);
+}
+
 // ============ Instruction interaction ============
 
 #[test]
@@ -687,3 +806,84 @@ fn regression_daa_after_sub_carry_half() {
     assert_eq!(c.a, 0x94);
     assert!(c.get_flag(flags::CARRY));
 }
+
+#[test]
+fn regression_out_c_0_depends_on_model() {
+    // OUT (C),0
+    let mut c = create_z80(&[0xED, 0x71]);
+    c.set_bc(0x0040);
+    c.a = 0x55;
+    c.step();
+    assert_eq!(c.io.ports.get(&0x0040), Some(&0x00), "NMOS drives zero");
+
+    let mut c = create_z80(&[0xED, 0x71]);
+    c.model = Z80Model::Cmos;
+    c.set_bc(0x0040);
+    c.step();
+    assert_eq!(c.io.ports.get(&0x0040), Some(&0xFF), "CMOS drives 0xFF");
+    assert_eq!(c.pc, 2);
+}
+
+#[test]
+fn regression_in_f_c_sets_flags_only() {
+    // IN F,(C)
+    let mut c = create_z80(&[0xED, 0x70, 0xED, 0x70]);
+    c.set_bc(0x0012);
+    c.io.ports.insert(0x0012, 0x81);
+    c.f = flags::ZERO | flags::HALF_CARRY | flags::ADD_SUB | flags::CARRY;
+    let before = (c.a, c.b, c.c, c.d, c.e, c.h, c.l);
+    c.step();
+    assert_eq!((c.a, c.b, c.c, c.d, c.e, c.h, c.l), before);
+    assert!(c.get_flag(flags::SIGN));
+    assert!(!c.get_flag(flags::ZERO));
+    assert!(c.get_flag(flags::PARITY), "0x81 has even parity");
+    assert!(!c.get_flag(flags::HALF_CARRY));
+    assert!(!c.get_flag(flags::ADD_SUB));
+    assert!(c.get_flag(flags::CARRY), "Carry is preserved");
+
+    c.io.ports.insert(0x0012, 0x00);
+    c.step();
+    assert!(c.get_flag(flags::ZERO));
+    assert!(!c.get_flag(flags::SIGN));
+    assert!(c.get_flag(flags::PARITY));
+}
+
+#[test]
+fn regression_ldi_xy_flags_from_a_plus_byte() {
+    // (A, transferred byte, Y expected, X expected): Y is bit 1 and X is
+    // bit 3 of A + byte, not bits 5 and 3 of either value
+    let cases = [
+        (0x04, 0x06, true, true),  // 0x0A
+        (0x01, 0x01, true, false), // 0x02
+        (0x20, 0x08, false, true), // 0x28
+        (0x28, 0x00, false, true), // 0x28: A's bit 5 doesn't reach Y
+        (0xFF, 0x03, true, false), // 0x02 after wrapping
+    ];
+    for (a, byte, y, x) in cases {
+        // LDI
+        let mut c = create_z80(&[0xED, 0xA0]);
+        c.memory.write_byte(0x4000_u32, byte);
+        c.set_hl(0x4000);
+        c.set_de(0x5000);
+        c.set_bc(0x0002);
+        c.a = a;
+        c.f = 0;
+        c.step();
+        assert_eq!(c.memory.read_byte(0x5000_u32), byte);
+        assert_eq!(
+            c.get_flag(flags::Y_FLAG),
+            y,
+            "Y for A={:02X} byte={:02X}",
+            a,
+            byte
+        );
+        assert_eq!(
+            c.get_flag(flags::X_FLAG),
+            x,
+            "X for A={:02X} byte={:02X}",
+            a,
+            byte
+        );
+        assert!(c.get_flag(flags::PARITY), "BC is still non-zero");
+    }
+}